@@ -0,0 +1,193 @@
+use crossterm::event::{KeyCode, KeyEvent};
+
+use crate::{
+    app::{
+        keymap::{Action, KeyContext},
+        state::App,
+    },
+    database::{
+        alerts::{self, Alert},
+        gaps::{format_date, format_duration},
+    },
+    locale::{tr, trf},
+};
+
+use self::state::{AlertsState, ThresholdState};
+
+pub mod state;
+pub mod ui;
+
+/// Заголовки столбцов отчёта о превышениях порогов
+pub const ALERTS_HEADER: [&str; 8] = [
+    "Датчик",
+    "Поле",
+    "Серийник",
+    "Порог",
+    "Начало",
+    "Конец",
+    "Длительность",
+    "Пик",
+];
+
+/// Количество строк, на которое перемещают `PageUp` и `PageDown`
+const PAGE_LENGTH: isize = 10;
+
+impl App<'_> {
+    /// Открывает окно порогов для выбранного в дереве поля датчика
+    pub fn open_threshold(&mut self) {
+        // Пороги задаются только для полей данных
+        let Some((sensor, field)) = self.sensors_state().get_selected_field() else {
+            return;
+        };
+        if ["id", "serial", "date"].contains(&field.as_str()) {
+            return;
+        }
+
+        // Загружаем сохранённые пороги, если они есть
+        let result = {
+            let database = self.database.lock().unwrap();
+            alerts::load_threshold(&database, &sensor, &field)
+        };
+        let state = match result {
+            Ok(threshold) => ThresholdState::new(sensor, field, threshold.unwrap_or_default()),
+            Err(err) => {
+                let mut state = ThresholdState::new(sensor, field, Default::default());
+                state.error = Some(trf("Не удалось загрузить пороги: {}", &[&err]));
+                state
+            }
+        };
+        self.sensors_state_mut().threshold_state = Some(state);
+    }
+
+    /// Обрабатывает все события, связанные с нажатием клавиш в окне порогов
+    pub fn on_key_event_threshold(&mut self, event: KeyEvent) {
+        // Действие, назначенное на клавишу в окне порогов (символы без действия вводятся в поле)
+        let action = self.keymap.action(KeyContext::Thresholds, event);
+
+        // Получаем состояние окна порогов
+        let sensors_state = self.sensors_state_mut();
+        let state = sensors_state.threshold_state.as_mut().unwrap();
+
+        match (action, event.code) {
+            (Some(Action::Close), _) => sensors_state.threshold_state = None,
+            (Some(Action::Save), _) => self.save_threshold(),
+            (Some(Action::Up | Action::Down), _) => state.toggle(),
+            (Some(Action::Left), _) => state.selected_input_mut().goto_prev(),
+            (Some(Action::Right), _) => state.selected_input_mut().goto_next(),
+            (Some(Action::Home), _) => state.selected_input_mut().goto_start(),
+            (Some(Action::End), _) => state.selected_input_mut().goto_end(),
+            (None, KeyCode::Char(ch)) => state.selected_input_mut().insert(ch),
+            (Some(Action::Erase), _) => state.selected_input_mut().remove(),
+            (Some(Action::Help), _) => self.open_help(KeyContext::Thresholds),
+            _ => (),
+        }
+    }
+
+    /// Сохраняет пороги из окна и заново ищет превышения
+    fn save_threshold(&mut self) {
+        // Получаем пороги из полей ввода
+        let database = self.database.clone();
+        let state = self.sensors_state_mut().threshold_state.as_mut().unwrap();
+        state.status = None;
+        let Some(threshold) = state.get_threshold() else {
+            state.error = Some(tr("В порогах есть ошибки").to_owned());
+            return;
+        };
+
+        // Сохраняем пороги и считаем превышения этого поля
+        let result = {
+            let database = database.lock().unwrap();
+            alerts::save_threshold(&database, &state.sensor, &state.field, &threshold)
+                .and_then(|_| Ok(alerts::load_alerts(&database)?))
+        };
+        match result {
+            Ok(alerts) => {
+                let count = alerts
+                    .iter()
+                    .filter(|alert| alert.sensor == state.sensor && alert.field == state.field)
+                    .count();
+                state.status = Some(trf("Превышений порогов: {}", &[&count]));
+                state.error = None;
+            }
+            Err(err) => state.error = Some(trf("Не удалось сохранить пороги: {}", &[&err])),
+        }
+    }
+
+    /// Открывает окно превышений порогов. `status` - почему окно открывается, если не по запросу
+    pub fn open_alerts(&mut self, status: Option<String>) {
+        let result = {
+            let database = self.database.lock().unwrap();
+            alerts::load_alerts(&database)
+        };
+        let state = match result {
+            Ok(alerts) => AlertsState {
+                status,
+                ..AlertsState::new(alerts)
+            },
+            Err(err) => AlertsState {
+                error: Some(trf("Не удалось загрузить превышения: {}", &[&err])),
+                ..Default::default()
+            },
+        };
+        self.sensors_state_mut().alerts_state = Some(state);
+    }
+
+    /// Обрабатывает все события, связанные с нажатием клавиш в окне превышений порогов
+    pub fn on_key_event_alerts(&mut self, event: KeyEvent) {
+        // Действие, назначенное на клавишу в окне превышений
+        let action = self.keymap.action(KeyContext::Alerts, event);
+
+        // Получаем состояние окна превышений
+        let sensors_state = self.sensors_state_mut();
+        let state = sensors_state.alerts_state.as_mut().unwrap();
+
+        match action {
+            Some(Action::Close) => sensors_state.alerts_state = None,
+            Some(Action::Up) => state.move_selection(-1),
+            Some(Action::Down) => state.move_selection(1),
+            Some(Action::PageUp) => state.move_selection(-PAGE_LENGTH),
+            Some(Action::PageDown) => state.move_selection(PAGE_LENGTH),
+            Some(Action::Home) => state.move_selection(-(state.alerts.len() as isize)),
+            Some(Action::End) => state.move_selection(state.alerts.len() as isize),
+            Some(Action::Help) => self.open_help(KeyContext::Alerts),
+            _ => (),
+        }
+    }
+}
+
+/// Возвращает значения столбцов отчёта для одного превышения
+pub fn get_alert_row(alert: &Alert) -> [String; 8] {
+    [
+        alert.sensor.clone(),
+        alert.field.clone(),
+        alert.serial.clone(),
+        format!("{} {}", alert.kind.sign(), alert.threshold),
+        format_date(alert.start),
+        format_date(alert.end),
+        format_duration(alert.duration()),
+        alert.peak.to_string(),
+    ]
+}
+
+/// Заново ищет превышения порогов и выводит их в stdout (команда `check`), столбцы разделены табуляцией.
+/// Возвращает количество новых превышений и тех, что ещё продолжаются
+pub fn print_alerts_report(
+    database: &rusqlite::Connection,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let new_alerts = alerts::detect_all_alerts(database)?;
+    let alerts = alerts::load_alerts(database)?;
+
+    // Заголовки не переводятся, чтобы вывод одинаково разбирался скриптами при любом языке
+    println!("{}", ALERTS_HEADER.join("\t"));
+    let mut active = 0;
+    for alert in &alerts {
+        println!("{}", get_alert_row(alert).join("\t"));
+
+        // Закончившиеся превышения, о которых уже сообщалось, не учитываются
+        if new_alerts.contains(alert) || alerts::is_alert_open(database, alert)? {
+            active += 1;
+        }
+    }
+
+    Ok(active)
+}
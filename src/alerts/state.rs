@@ -0,0 +1,131 @@
+use tui::widgets::TableState;
+
+use crate::{
+    database::alerts::{Alert, Threshold},
+    locale::tr,
+    ui::input::InputState,
+};
+
+/// Названия полей порогов в порядке отображения в окне
+pub const THRESHOLD_FIELDS: [&str; 2] = ["Нижний порог", "Верхний порог"];
+
+/// Описывает состояние окна порогов одного поля датчика
+#[derive(Debug)]
+pub struct ThresholdState {
+    /// Название датчика
+    pub sensor: String,
+
+    /// Название поля датчика
+    pub field: String,
+
+    /// Сохраняет поля ввода порогов (в порядке `THRESHOLD_FIELDS`)
+    pub inputs: [InputState; 2],
+
+    /// Сохраняет индекс выбранного поля ввода
+    pub selection_index: usize,
+
+    /// Сохраняет результат последнего поиска превышений
+    pub status: Option<String>,
+
+    /// Сохраняет ошибку последнего действия, если она была
+    pub error: Option<String>,
+}
+
+/// Описывает состояние окна превышений порогов
+#[derive(Debug, Default)]
+pub struct AlertsState {
+    /// Сохраняет найденные превышения
+    pub alerts: Vec<Alert>,
+
+    /// Сохраняет состояние таблицы (выбранную строку и прокрутку)
+    pub table_state: TableState,
+
+    /// Сохраняет сообщение о том, почему окно открылось само (например, после импорта)
+    pub status: Option<String>,
+
+    /// Сохраняет ошибку загрузки превышений, если она была
+    pub error: Option<String>,
+}
+
+impl ThresholdState {
+    /// Создаёт новое состояние окна, заполняя поля сохранёнными порогами
+    pub fn new(sensor: String, field: String, threshold: Threshold) -> Self {
+        let mut inputs: [InputState; 2] = Default::default();
+        for (input, value) in inputs.iter_mut().zip([threshold.min, threshold.max]) {
+            input.set_content(value.map(|value| value.to_string()).unwrap_or_default());
+        }
+        inputs[0].open();
+
+        Self {
+            sensor,
+            field,
+            inputs,
+            selection_index: 0,
+            status: None,
+            error: None,
+        }
+    }
+
+    /// Возвращает изменяемую ссылку на выбранное поле ввода
+    pub fn selected_input_mut(&mut self) -> &mut InputState {
+        &mut self.inputs[self.selection_index]
+    }
+
+    /// Переводит ввод на другое поле (полей всего два)
+    pub fn toggle(&mut self) {
+        self.selected_input_mut().close();
+        self.selection_index = 1 - self.selection_index;
+        self.selected_input_mut().open();
+    }
+
+    /// Собирает пороги из полей ввода. Если в каком-то поле ошибка, отмечает его и возвращает `None`
+    pub fn get_threshold(&mut self) -> Option<Threshold> {
+        // Пустое поле означает, что порог не задан
+        let values: Vec<Result<Option<f64>, ()>> = self
+            .inputs
+            .iter_mut()
+            .map(|input| {
+                let result = match input.content().trim() {
+                    "" => Ok(None),
+                    content => content.parse().map(Some).map_err(|_| ()),
+                };
+                let error = result.is_err().then(|| tr("Ожидалось число").to_owned());
+                input.set_error(error);
+                result
+            })
+            .collect();
+
+        match values[..] {
+            [Ok(min), Ok(max)] => Some(Threshold { min, max }),
+            _ => None,
+        }
+    }
+}
+
+impl AlertsState {
+    /// Создаёт новое состояние окна превышений
+    pub fn new(alerts: Vec<Alert>) -> Self {
+        let mut table_state = TableState::default();
+        if !alerts.is_empty() {
+            table_state.select(Some(0));
+        }
+
+        Self {
+            alerts,
+            table_state,
+            status: None,
+            error: None,
+        }
+    }
+
+    /// Выбирает строку, отстоящую от текущей на `offset` (с ограничением по краям)
+    pub fn move_selection(&mut self, offset: isize) {
+        if self.alerts.is_empty() {
+            return;
+        }
+
+        let selected = self.table_state.selected().unwrap_or(0) as isize;
+        let selected = (selected + offset).clamp(0, self.alerts.len() as isize - 1);
+        self.table_state.select(Some(selected as usize));
+    }
+}
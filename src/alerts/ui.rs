@@ -0,0 +1,156 @@
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::Text,
+    widgets::{Block, Borders, Clear, Paragraph, Row, Table, Wrap},
+    Frame,
+};
+
+use crate::{
+    locale::{tr, trf},
+    ui::{input::Input, utils::get_popup_area},
+};
+
+use super::{
+    get_alert_row,
+    state::{AlertsState, ThresholdState, THRESHOLD_FIELDS},
+    ALERTS_HEADER,
+};
+
+/// Рендерит окно порогов поля датчика
+pub fn draw_threshold<B: Backend>(frame: &mut Frame<B>, state: &mut ThresholdState, area: Rect) {
+    // Выделяем область под окошко и очищаем её, чтобы не видеть дерево под ним
+    let popup_area = get_popup_area(50, 40, area);
+    frame.render_widget(Clear, popup_area);
+
+    // Делаем блок
+    let title = trf("Пороги: {}/{}", &[&state.sensor, &state.field]);
+    let block = Block::default().title(title).borders(Borders::ALL);
+    let inner_area = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    // Сверху поля порогов, снизу подсказка, результат и ошибка
+    let area_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(THRESHOLD_FIELDS.len() as u16),
+            Constraint::Min(0),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .split(inner_area);
+
+    // Рендерим поля порогов: подпись слева, поле ввода справа
+    let label_width = THRESHOLD_FIELDS
+        .iter()
+        .map(|label| tr(label).chars().count())
+        .max()
+        .unwrap_or(0) as u16
+        + 2;
+    let fields_area = area_chunks[0];
+    for (i, (label, input_state)) in THRESHOLD_FIELDS.iter().zip(&mut state.inputs).enumerate() {
+        let y = fields_area.y + i as u16;
+        let style = match i == state.selection_index {
+            true => Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD),
+            false => Style::default(),
+        };
+        let paragraph = Paragraph::new(Text::from(tr(label))).style(style);
+        frame.render_widget(paragraph, Rect::new(fields_area.x, y, label_width, 1));
+
+        let input = Input::new().style(Style::default().add_modifier(Modifier::UNDERLINED));
+        let input_width = fields_area.width.saturating_sub(label_width).max(1);
+        let input_area = Rect::new(fields_area.x + label_width, y, input_width, 1);
+        frame.render_stateful_widget(input, input_area, input_state);
+    }
+
+    // Подсказка о том, как работают пороги
+    let text = "Превышение - записи прибора подряд ниже нижнего или выше верхнего порога. \
+                Пустое поле выключает порог. Enter - сохранить пороги и найти превышения, \
+                Esc - закрыть окно.";
+    let paragraph = Paragraph::new(Text::from(tr(text)))
+        .style(Style::default().fg(Color::DarkGray))
+        .wrap(Wrap { trim: true });
+    let hint_area = Rect {
+        y: area_chunks[1].y + 1,
+        height: area_chunks[1].height.saturating_sub(1),
+        ..area_chunks[1]
+    };
+    frame.render_widget(paragraph, hint_area);
+
+    // Рендерим результат последнего поиска и ошибку
+    if let Some(status) = &state.status {
+        let paragraph = Paragraph::new(Text::from(status.as_str()));
+        frame.render_widget(paragraph, area_chunks[2]);
+    }
+    if let Some(error) = &state.error {
+        let paragraph =
+            Paragraph::new(Text::from(error.as_str())).style(Style::default().fg(Color::Red));
+        frame.render_widget(paragraph, area_chunks[3]);
+    }
+}
+
+/// Рендерит окно превышений порогов
+pub fn draw_alerts<B: Backend>(frame: &mut Frame<B>, state: &mut AlertsState, area: Rect) {
+    // Выделяем область под окошко и очищаем её, чтобы не видеть дерево под ним
+    let popup_area = get_popup_area(90, 80, area);
+    frame.render_widget(Clear, popup_area);
+
+    // Делаем блок (в заголовке - количество превышений)
+    let title = trf("Превышения порогов ({})", &[&state.alerts.len()]);
+    let block = Block::default().title(title).borders(Borders::ALL);
+    let inner_area = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    // Если превышений нет, сообщаем об этом (или об ошибке их загрузки)
+    if state.alerts.is_empty() {
+        let paragraph = match &state.error {
+            Some(error) => {
+                Paragraph::new(Text::from(error.as_str())).style(Style::default().fg(Color::Red))
+            }
+            None => Paragraph::new(Text::from(tr("--- Превышений не найдено ---"))),
+        };
+        frame.render_widget(paragraph, inner_area);
+        return;
+    }
+
+    // Сверху причина открытия окна, если она есть
+    let area_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(state.status.is_some() as u16),
+            Constraint::Min(0),
+        ])
+        .split(inner_area);
+    if let Some(status) = &state.status {
+        let paragraph =
+            Paragraph::new(Text::from(status.as_str())).style(Style::default().fg(Color::LightRed));
+        frame.render_widget(paragraph, area_chunks[0]);
+    }
+
+    let rows = state
+        .alerts
+        .iter()
+        .map(|alert| Row::new(get_alert_row(alert)));
+    let widths = [
+        Constraint::Percentage(10),
+        Constraint::Percentage(8),
+        Constraint::Percentage(9),
+        Constraint::Percentage(9),
+        Constraint::Percentage(19),
+        Constraint::Percentage(19),
+        Constraint::Percentage(14),
+        Constraint::Percentage(12),
+    ];
+    let table = Table::new(rows)
+        .header(Row::new(ALERTS_HEADER.map(tr)).style(Style::default().fg(Color::Yellow)))
+        .highlight_style(
+            Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD),
+        )
+        .widths(&widths);
+    frame.render_stateful_widget(table, area_chunks[1], &mut state.table_state);
+}
@@ -0,0 +1,154 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use itertools::Itertools;
+
+use crate::{
+    app::{
+        keymap::{Action, KeyContext},
+        state::App,
+    },
+    database::{
+        annotations::{self, Annotation},
+        gaps::format_date,
+    },
+    locale::{tr, trf},
+};
+
+use self::state::AnnotationState;
+
+pub mod state;
+pub mod ui;
+
+/// Заголовки столбцов выгрузки меток
+pub const ANNOTATIONS_HEADER: [&str; 4] = ["Начало", "Конец", "Серийник", "Текст"];
+
+impl App<'_> {
+    /// Открывает окно метки в точке курсора: метки под курсором можно изменить (переключаясь между ними),
+    /// а последней идёт новая метка с датой курсора
+    pub fn open_annotation(&mut self) {
+        // Метки ставятся только на шкалу времени
+        let state = self.graph_state();
+        if !state.dataset_x_is_date || state.dataset_scatter {
+            return;
+        }
+        let Some(x) = state.cursor_x() else {
+            return;
+        };
+
+        let mut choices: Vec<_> = state.annotations_at_cursor().into_iter().cloned().collect();
+        choices.push(Annotation {
+            start: x as i64,
+            ..Default::default()
+        });
+        let serials = self
+            .sensor_serials
+            .borrow()
+            .values()
+            .flatten()
+            .cloned()
+            .sorted()
+            .dedup()
+            .collect();
+        self.graph_state_mut().annotation_state = Some(AnnotationState::new(choices, serials));
+    }
+
+    /// Закрывает окно метки и перестраивает график, чтобы на нём были видны изменения
+    fn close_annotation(&mut self, changed: bool) {
+        let state = self.graph_state_mut();
+        state.annotation_state = None;
+        if changed {
+            state.was_edited = true;
+            state.update_datasets();
+        }
+    }
+
+    /// Обрабатывает все события, связанные с нажатием клавиш в окне метки
+    pub fn on_key_event_annotation(&mut self, event: KeyEvent) {
+        // Действие, назначенное на клавишу в окне метки (символы без действия вводятся в поле)
+        let action = self.keymap.action(KeyContext::Annotation, event);
+
+        // Получаем состояние окна метки
+        let state = self.graph_state_mut().annotation_state.as_mut().unwrap();
+
+        match (action, event.code) {
+            (Some(Action::Close), _) => self.close_annotation(false),
+            (Some(Action::Save), _) => self.save_annotation(),
+            (Some(Action::Delete), _) => self.delete_annotation(),
+            (Some(Action::PageUp), _) => state.switch(-1),
+            (Some(Action::PageDown), _) => state.switch(1),
+            (Some(Action::Up), _) => state.prev(),
+            (Some(Action::Down), _) => state.next(),
+            (Some(Action::Left), _) => state.selected_input_mut().goto_prev(),
+            (Some(Action::Right), _) => state.selected_input_mut().goto_next(),
+            (Some(Action::Home), _) => state.selected_input_mut().goto_start(),
+            (Some(Action::End), _) => state.selected_input_mut().goto_end(),
+            (None, KeyCode::Char(ch)) => state.selected_input_mut().insert(ch),
+            (Some(Action::Erase), _) => state.selected_input_mut().remove(),
+            (Some(Action::Help), _) => self.open_help(KeyContext::Annotation),
+            _ => (),
+        }
+    }
+
+    /// Сохраняет метку из окна
+    fn save_annotation(&mut self) {
+        // Получаем метку из полей ввода
+        let database = self.database.clone();
+        let state = self.graph_state_mut().annotation_state.as_mut().unwrap();
+        let Some(annotation) = state.get_annotation() else {
+            state.error = Some(tr("В метке есть ошибки").to_owned());
+            return;
+        };
+
+        let result = {
+            let database = database.lock().unwrap();
+            annotations::save_annotation(&database, &annotation)
+        };
+        match result {
+            Ok(_) => self.close_annotation(true),
+            Err(err) => state.error = Some(trf("Не удалось сохранить метку: {}", &[&err])),
+        }
+    }
+
+    /// Удаляет метку из окна, если она уже была сохранена
+    fn delete_annotation(&mut self) {
+        let database = self.database.clone();
+        let state = self.graph_state_mut().annotation_state.as_mut().unwrap();
+        let Some(id) = state.id else {
+            state.error = Some(tr("Метка ещё не сохранена").to_owned());
+            return;
+        };
+
+        let result = {
+            let database = database.lock().unwrap();
+            annotations::delete_annotation(&database, id)
+        };
+        match result {
+            Ok(()) => self.close_annotation(true),
+            Err(err) => state.error = Some(trf("Не удалось удалить метку: {}", &[&err])),
+        }
+    }
+}
+
+/// Возвращает значения столбцов выгрузки для одной метки
+pub fn get_annotation_row(annotation: &Annotation) -> [String; 4] {
+    [
+        format_date(annotation.start),
+        annotation.end.map(format_date).unwrap_or_default(),
+        annotation.serial.clone().unwrap_or_default(),
+        annotation.text.clone(),
+    ]
+}
+
+/// Выводит все метки в stdout (команда `annotations`), столбцы разделены табуляцией
+pub fn print_annotations_report(
+    database: &rusqlite::Connection,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let annotations = annotations::load_annotations(database, None)?;
+
+    // Заголовки не переводятся, чтобы вывод одинаково разбирался скриптами при любом языке
+    println!("{}", ANNOTATIONS_HEADER.join("\t"));
+    for annotation in &annotations {
+        println!("{}", get_annotation_row(annotation).join("\t"));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,169 @@
+use chrono::NaiveDateTime;
+
+use crate::{
+    database::{annotations::Annotation, gaps::format_date},
+    locale::tr,
+    ui::input::InputState,
+};
+
+/// Названия полей метки в порядке отображения в окне
+pub const ANNOTATION_FIELDS: [&str; 4] = ["Текст", "Начало", "Конец", "Серийник"];
+
+/// Описывает состояние окна метки события
+#[derive(Debug)]
+pub struct AnnotationState {
+    /// Номер редактируемой метки (`None`, если метка новая)
+    pub id: Option<i64>,
+
+    /// Сохраняет метки, между которыми можно переключаться (метки в точке курсора и новая метка)
+    pub choices: Vec<Annotation>,
+
+    /// Сохраняет индекс метки из `choices`, которая сейчас в полях ввода
+    pub choice: usize,
+
+    /// Сохраняет серийники всех приборов, чтобы проверять введённый
+    pub serials: Vec<String>,
+
+    /// Сохраняет поля ввода метки (в порядке `ANNOTATION_FIELDS`)
+    pub inputs: [InputState; 4],
+
+    /// Сохраняет индекс выбранного поля ввода
+    pub selection_index: usize,
+
+    /// Сохраняет ошибку последнего действия, если она была
+    pub error: Option<String>,
+}
+
+impl AnnotationState {
+    /// Создаёт новое состояние окна, заполняя поля первой из меток
+    pub fn new(choices: Vec<Annotation>, serials: Vec<String>) -> Self {
+        let mut state = Self {
+            id: None,
+            choices,
+            choice: 0,
+            serials,
+            inputs: Default::default(),
+            selection_index: 0,
+            error: None,
+        };
+        state.load();
+        state.inputs[0].open();
+        state
+    }
+
+    /// Переключается на другую метку из `choices` (со сдвигом `offset` по кругу). Несохранённые
+    /// изменения в полях ввода при этом теряются
+    pub fn switch(&mut self, offset: isize) {
+        let count = self.choices.len() as isize;
+        self.choice = (self.choice as isize + offset).rem_euclid(count.max(1)) as usize;
+        self.load();
+        self.error = None;
+    }
+
+    /// Заполняет поля ввода выбранной меткой
+    fn load(&mut self) {
+        let Some(annotation) = self.choices.get(self.choice) else {
+            return;
+        };
+        let values = [
+            annotation.text.clone(),
+            format_date(annotation.start),
+            annotation.end.map(format_date).unwrap_or_default(),
+            annotation.serial.clone().unwrap_or_default(),
+        ];
+        for (input, value) in self.inputs.iter_mut().zip(values) {
+            input.set_content(value);
+            input.set_error(None);
+        }
+        self.id = annotation.id;
+    }
+
+    /// Возвращает изменяемую ссылку на выбранное поле ввода
+    pub fn selected_input_mut(&mut self) -> &mut InputState {
+        &mut self.inputs[self.selection_index]
+    }
+
+    /// Выбирает предыдущее поле ввода
+    pub fn prev(&mut self) {
+        self.select((self.selection_index + self.inputs.len() - 1) % self.inputs.len());
+    }
+
+    /// Выбирает следующее поле ввода
+    pub fn next(&mut self) {
+        self.select((self.selection_index + 1) % self.inputs.len());
+    }
+
+    /// Собирает метку из полей ввода. Если в каком-то поле ошибка, отмечает его и возвращает `None`
+    pub fn get_annotation(&mut self) -> Option<Annotation> {
+        fn parse_date(input: &mut InputState) -> Result<Option<i64>, ()> {
+            let result = match input.content().trim() {
+                "" => Ok(None),
+                content => NaiveDateTime::parse_from_str(content, "%Y-%m-%d %H:%M:%S")
+                    .map(|date| Some(date.timestamp()))
+                    .map_err(|_| ()),
+            };
+            let error = result
+                .is_err()
+                .then(|| tr("Ожидалась дата формата %Y-%m-%d %H:%M:%S").to_owned());
+            input.set_error(error);
+            result
+        }
+
+        let [text, start, end, serial] = &mut self.inputs;
+
+        // Метка без текста ничего не сообщает
+        let text_content = text.content().trim().to_owned();
+        let error = text_content
+            .is_empty()
+            .then(|| tr("Введите текст метки").to_owned());
+        text.set_error(error);
+
+        // Начало обязательно, а пустой конец означает метку-момент
+        let start_date = match parse_date(start) {
+            Ok(None) => {
+                start.set_error(Some(tr("Введите дату события").to_owned()));
+                Err(())
+            }
+            result => result,
+        };
+        let end_date = parse_date(end);
+        if let (Ok(Some(start)), Ok(Some(end_value))) = (start_date, end_date) {
+            if end_value < start {
+                end.set_error(Some(tr("Конец должен быть не раньше начала").to_owned()));
+                return None;
+            }
+        }
+
+        // Пустой серийник означает, что метка относится ко всем приборам
+        let serial_content = serial.content().trim().to_owned();
+        let serial_value = match serial_content.as_str() {
+            "" => Ok(None),
+            content if self.serials.iter().any(|serial| serial == content) => {
+                Ok(Some(serial_content.clone()))
+            }
+            _ => Err(()),
+        };
+        let error = serial_value
+            .is_err()
+            .then(|| tr("Нет прибора с таким серийником").to_owned());
+        serial.set_error(error);
+
+        match (text_content.is_empty(), start_date, end_date, serial_value) {
+            (false, Ok(Some(start)), Ok(end), Ok(serial)) => Some(Annotation {
+                id: self.id,
+                start,
+                end,
+                serial,
+                text: text_content,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Переводит ввод на поле с данным индексом
+    fn select(&mut self, index: usize) {
+        self.selected_input_mut().close();
+        self.selection_index = index;
+        self.selected_input_mut().open();
+    }
+}
@@ -0,0 +1,87 @@
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::Text,
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::{
+    locale::tr,
+    ui::{input::Input, utils::get_popup_area},
+};
+
+use super::state::{AnnotationState, ANNOTATION_FIELDS};
+
+/// Рендерит окно метки события
+pub fn draw_annotation<B: Backend>(frame: &mut Frame<B>, state: &mut AnnotationState, area: Rect) {
+    // Выделяем область под окошко и очищаем её, чтобы не видеть график под ним
+    let popup_area = get_popup_area(60, 40, area);
+    frame.render_widget(Clear, popup_area);
+
+    // Делаем блок
+    let title = match state.id {
+        Some(_) => tr("Изменение метки"),
+        None => tr("Новая метка"),
+    };
+    // Если в точке курсора есть метки, то показываем, какая из них открыта
+    let title = match state.choices.len() {
+        0 | 1 => title.to_owned(),
+        count => format!("{title} ({}/{count})", state.choice + 1),
+    };
+    let block = Block::default().title(title).borders(Borders::ALL);
+    let inner_area = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    // Сверху поля метки, снизу подсказка и ошибка
+    let area_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(ANNOTATION_FIELDS.len() as u16 + 1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .split(inner_area);
+
+    // Рендерим поля метки: подпись слева, поле ввода справа
+    let label_width = ANNOTATION_FIELDS
+        .iter()
+        .map(|label| tr(label).chars().count())
+        .max()
+        .unwrap_or(0) as u16
+        + 2;
+    let fields_area = area_chunks[0];
+    for (i, (label, input_state)) in ANNOTATION_FIELDS.iter().zip(&mut state.inputs).enumerate() {
+        let y = fields_area.y + i as u16;
+        let style = match i == state.selection_index {
+            true => Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD),
+            false => Style::default(),
+        };
+        let paragraph = Paragraph::new(Text::from(tr(label))).style(style);
+        frame.render_widget(paragraph, Rect::new(fields_area.x, y, label_width, 1));
+
+        let input = Input::new().style(Style::default().add_modifier(Modifier::UNDERLINED));
+        let input_width = fields_area.width.saturating_sub(label_width).max(1);
+        let input_area = Rect::new(fields_area.x + label_width, y, input_width, 1);
+        frame.render_stateful_widget(input, input_area, input_state);
+    }
+
+    // Подсказка о том, как работают метки
+    let text = "Даты в формате %Y-%m-%d %H:%M:%S. Пустой конец - событие в один момент, \
+                пустой серийник - метка для всех приборов. PageUp/PageDown - другие метки \
+                в точке курсора, Enter - сохранить метку, Ctrl+D - удалить её, Esc - закрыть окно.";
+    let paragraph = Paragraph::new(Text::from(tr(text)))
+        .style(Style::default().fg(Color::DarkGray))
+        .wrap(Wrap { trim: true });
+    frame.render_widget(paragraph, area_chunks[1]);
+
+    // Рендерим ошибку последнего действия
+    if let Some(error) = &state.error {
+        let paragraph =
+            Paragraph::new(Text::from(error.as_str())).style(Style::default().fg(Color::Red));
+        frame.render_widget(paragraph, area_chunks[2]);
+    }
+}
@@ -0,0 +1,32 @@
+use serde::Deserialize;
+
+use crate::locale::Language;
+
+use super::keymap::KeymapConfig;
+
+/// Путь до файла настроек (рядом с базой данных)
+pub const CONFIG_PATH: &str = "config.toml";
+
+/// Настройки приложения из файла `config.toml`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Язык интерфейса. Если не задан, то определяется по локали из переменных окружения
+    pub language: Option<Language>,
+
+    /// Переназначения клавиш по режимам
+    pub keys: KeymapConfig,
+}
+
+impl Config {
+    /// Загружает настройки из файла. Если файла нет, то используются настройки по умолчанию
+    pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
+        let text = match std::fs::read_to_string(CONFIG_PATH) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(err.into()),
+        };
+
+        toml::from_str(&text).map_err(|err| format!("{CONFIG_PATH}: {err}").into())
+    }
+}
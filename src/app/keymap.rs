@@ -0,0 +1,785 @@
+use std::collections::HashMap;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+/// Режим приложения, в котором у клавиш своё назначение
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyContext {
+    /// Обычный режим вкладки дерева датчиков
+    Sensors,
+
+    /// Окно выбора файла для импорта
+    FilePicker,
+
+    /// Обычный режим вкладки графика
+    Graph,
+
+    /// Режим редактирования полей графика
+    GraphEdit,
+
+    /// Открытое поле ввода текста на графике
+    GraphInput,
+
+    /// Открытое меню на графике
+    GraphMenu,
+
+    /// Окно отчёта о пропусках данных
+    Gaps,
+
+    /// Окно превышений порогов
+    Alerts,
+
+    /// Окно открытия сохранённого вида
+    Views,
+
+    /// Окно сохранения вида (ввод названия)
+    ViewName,
+
+    /// Окно метки события
+    Annotation,
+
+    /// Окно правила поиска выбросов
+    Outliers,
+
+    /// Окно поправок приборов
+    Calibration,
+
+    /// Окно порогов поля
+    Thresholds,
+}
+
+/// Действие, которое можно назначить на клавишу
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// Выход из приложения
+    Quit,
+    /// Переход к предыдущей вкладке
+    PrevTab,
+    /// Переход к следующей вкладке
+    NextTab,
+    /// Открытие новой вкладки графика
+    NewTab,
+    /// Закрытие активной вкладки
+    CloseTab,
+    /// Закрытие окна или выход из режима
+    Close,
+
+    /// Навигация вверх
+    Up,
+    /// Навигация вниз
+    Down,
+    /// Навигация влево
+    Left,
+    /// Навигация вправо
+    Right,
+    /// Быстрая навигация влево
+    FastLeft,
+    /// Быстрая навигация вправо
+    FastRight,
+    /// Переход в начало
+    Home,
+    /// Переход в конец
+    End,
+    /// Переход на страницу вверх
+    PageUp,
+    /// Переход на страницу вниз
+    PageDown,
+
+    /// Раскрытие/скрытие пункта дерева
+    Toggle,
+    /// Открытие выделенного элемента
+    Open,
+    /// Выбор выделенного элемента
+    Select,
+    /// Удаление последнего символа
+    Erase,
+    /// Сохранение введённого в окне
+    Save,
+    /// Удаление выбранного элемента
+    Delete,
+    /// Подбор значений по введённому
+    Fit,
+
+    /// Открытие окна импорта
+    Import,
+    /// Импорт всех файлов директории
+    ImportDirectory,
+    /// Открытие отчёта о пропусках данных
+    Gaps,
+    /// Открытие правила поиска выбросов
+    Outliers,
+    /// Открытие поправок приборов
+    Calibration,
+    /// Открытие порогов поля
+    Thresholds,
+    /// Открытие окна превышений порогов
+    Alerts,
+
+    /// Вход в режим редактирования
+    Edit,
+    /// Добавление графика Y(x)
+    AddGraph,
+    /// Удаление графика Y(x)
+    RemoveGraph,
+    /// Переключение точечного режима
+    Scatter,
+    /// Переключение панели статистики
+    Statistics,
+    /// Переключение разрывов линий на пропусках
+    GapBreaks,
+    /// Переключение исключения выбросов
+    ExcludeOutliers,
+    /// Переключение поправок приборов
+    Calibrated,
+    /// Сохранение вида
+    SaveView,
+    /// Открытие сохранённых видов
+    OpenViews,
+    /// Открытие метки события
+    Annotation,
+    /// Удаление курсора с графика
+    ClearCursor,
+
+    /// Открытие справки по клавишам
+    Help,
+}
+
+impl KeyContext {
+    /// Возвращает название режима для справки
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::Sensors => "Дерево сенсоров",
+            Self::FilePicker => "Окно импорта файла/директории",
+            Self::Graph => "График",
+            Self::GraphEdit => "Режим редактирования графика",
+            Self::GraphInput => "Поле \"Ввод текста\"",
+            Self::GraphMenu => "Поле \"Меню\"",
+            Self::Gaps => "Окно отчёта о пропусках",
+            Self::Alerts => "Окно превышений порогов",
+            Self::Views => "Окно сохранённых видов",
+            Self::ViewName => "Окно сохранения вида",
+            Self::Annotation => "Окно метки события",
+            Self::Outliers => "Окно правила поиска выбросов",
+            Self::Calibration => "Окно поправок приборов",
+            Self::Thresholds => "Окно порогов поля",
+        }
+    }
+
+    /// Возвращает, что происходит с символами, на которые не назначено действие
+    pub fn typing_hint(self) -> Option<&'static str> {
+        match self {
+            Self::GraphInput
+            | Self::ViewName
+            | Self::Annotation
+            | Self::Outliers
+            | Self::Calibration
+            | Self::Thresholds => Some("Остальные символы вводятся в поле"),
+            Self::GraphMenu => Some("Остальные символы добавляются в строку поиска"),
+            _ => None,
+        }
+    }
+}
+
+impl Action {
+    /// Возвращает описание действия в данном режиме для справки
+    pub fn description(self, context: KeyContext) -> &'static str {
+        use KeyContext::*;
+
+        match (context, self) {
+            (_, Self::Quit) => "Выход из приложения",
+            (_, Self::PrevTab) => "Переход к предыдущей вкладке",
+            (_, Self::NextTab) => "Переход к следующей вкладке",
+            (_, Self::NewTab) => "Открытие новой вкладки \"График\"",
+            (_, Self::CloseTab) => "Закрытие вкладки",
+            (GraphEdit, Self::Close) => "Выход из режима редактирования",
+            (GraphInput, Self::Close) => "Сохранение поля",
+            (GraphMenu, Self::Close) => "Выход из меню без сохранения выбора",
+            (_, Self::Close) => "Закрытие окна",
+
+            (Sensors, Self::Up) => "Переход к пункту дерева выше",
+            (Sensors, Self::Down) => "Переход к пункту дерева ниже",
+            (Sensors, Self::Left) => "Скрытие пункта дерева или переход к родителю",
+            (Sensors, Self::Right) => "Раскрытие пункта дерева",
+            (FilePicker, Self::Up) => "Переход к файлу выше",
+            (FilePicker, Self::Down) => "Переход к файлу ниже",
+            (FilePicker, Self::Left) => "Переход в родительскую директорию",
+            (Graph, Self::Left) => "Перемещение курсора на точку влево",
+            (Graph, Self::Right) => "Перемещение курсора на точку вправо",
+            (Graph, Self::Home) => "Перемещение курсора на первую точку",
+            (Graph, Self::End) => "Перемещение курсора на последнюю точку",
+            (GraphEdit, Self::Up) => "Переход к полю выше",
+            (GraphEdit, Self::Down) => "Переход к полю ниже",
+            (GraphEdit, Self::Left) => "Переход к предыдущему полю",
+            (GraphEdit, Self::Right) => "Переход к следующему полю",
+            (GraphMenu, Self::Up) => "Переход к пункту меню выше",
+            (GraphMenu, Self::Down) => "Переход к пункту меню ниже",
+            (Gaps | Alerts | Views, Self::Up) => "Переход к строке выше",
+            (Gaps | Alerts | Views, Self::Down) => "Переход к строке ниже",
+            (Gaps | Alerts, Self::Home) => "Переход к первой строке",
+            (Gaps | Alerts, Self::End) => "Переход к последней строке",
+            (ViewName, Self::Up) => "Переход к виду выше",
+            (ViewName, Self::Down) => "Переход к виду ниже",
+            (Annotation | Outliers | Calibration | Thresholds, Self::Up) => "Переход к полю выше",
+            (Annotation | Outliers | Calibration | Thresholds, Self::Down) => "Переход к полю ниже",
+            (_, Self::Up) => "Навигация вверх",
+            (_, Self::Down) => "Навигация вниз",
+            (_, Self::Left) => "Перемещение курсора влево",
+            (_, Self::Right) => "Перемещение курсора вправо",
+            (_, Self::FastLeft) => "Перемещение курсора на 10 точек влево",
+            (_, Self::FastRight) => "Перемещение курсора на 10 точек вправо",
+            (_, Self::Home) => "Перемещение курсора в начало поля",
+            (_, Self::End) => "Перемещение курсора в конец поля",
+            (Annotation, Self::PageUp) => "Переход к предыдущей метке в точке курсора",
+            (Annotation, Self::PageDown) => "Переход к следующей метке в точке курсора",
+            (_, Self::PageUp) => "Переход на 10 строк вверх",
+            (_, Self::PageDown) => "Переход на 10 строк вниз",
+
+            (_, Self::Toggle) => "Раскрытие/скрытие пункта дерева",
+            (GraphEdit, Self::Open) => "Открытие редактирования поля",
+            (Views, Self::Open) => "Открытие выбранного вида",
+            (_, Self::Open) => "Открытие файла или переход в директорию",
+            (_, Self::Select) => "Выход из меню с сохранением выбора",
+            (GraphMenu, Self::Erase) => "Удаление последнего символа строки поиска",
+            (_, Self::Erase) => "Удаление символа перед курсором",
+            (ViewName, Self::Save) => "Сохранение вида под введённым названием",
+            (Annotation, Self::Save) => "Сохранение метки",
+            (Outliers, Self::Save) => "Сохранение правила и пометка выбросов",
+            (Calibration, Self::Save) => "Сохранение поправки прибора",
+            (_, Self::Save) => "Сохранение порогов и поиск превышений",
+            (Annotation, Self::Delete) => "Удаление метки",
+            (_, Self::Delete) => "Удаление выбранного вида",
+            (_, Self::Fit) => "Подбор поправки по эталонному прибору",
+
+            (_, Self::Import) => "Открытие окна импорта файла/директории",
+            (_, Self::ImportDirectory) => "Импорт всех файлов в данной директории",
+            (_, Self::Gaps) => "Открытие отчёта о пропусках данных",
+            (_, Self::Outliers) => "Открытие правила поиска выбросов выбранного поля",
+            (_, Self::Calibration) => "Открытие поправок приборов выбранного поля",
+            (_, Self::Thresholds) => "Открытие порогов выбранного поля",
+            (_, Self::Alerts) => "Открытие окна превышений порогов",
+
+            (_, Self::Edit) => "Вход в режим редактирования полей",
+            (_, Self::AddGraph) => "Добавление графика Y(x)",
+            (_, Self::RemoveGraph) => "Удаление последнего графика Y(x)",
+            (_, Self::Scatter) => "Переключение точечного режима",
+            (_, Self::Statistics) => "Переключение панели статистики",
+            (_, Self::GapBreaks) => "Переключение разрывов линий на пропусках данных",
+            (_, Self::ExcludeOutliers) => "Переключение исключения выбросов",
+            (_, Self::Calibrated) => "Переключение поправок приборов",
+            (_, Self::SaveView) => "Открытие окна сохранения вида",
+            (_, Self::OpenViews) => "Открытие окна сохранённых видов",
+            (_, Self::Annotation) => "Открытие окна метки события в точке курсора",
+            (_, Self::ClearCursor) => "Удаление курсора с графика",
+
+            (_, Self::Help) => "Открытие этой справки",
+        }
+    }
+}
+
+/// Клавиши по умолчанию (совпадают с описанными в README)
+const DEFAULT_BINDINGS: &[(KeyContext, Action, &[&str])] = &[
+    (KeyContext::Sensors, Action::Quit, &["q", "C-q"]),
+    (KeyContext::Sensors, Action::PrevTab, &["S-Tab"]),
+    (KeyContext::Sensors, Action::NextTab, &["Tab"]),
+    (KeyContext::Sensors, Action::NewTab, &["N"]),
+    (KeyContext::Sensors, Action::Up, &["Up"]),
+    (KeyContext::Sensors, Action::Down, &["Down"]),
+    (KeyContext::Sensors, Action::Left, &["Left"]),
+    (KeyContext::Sensors, Action::Right, &["Right"]),
+    (KeyContext::Sensors, Action::Toggle, &["Space", "Enter"]),
+    (KeyContext::Sensors, Action::Import, &["i"]),
+    (KeyContext::Sensors, Action::Gaps, &["g"]),
+    (KeyContext::Sensors, Action::Outliers, &["o"]),
+    (KeyContext::Sensors, Action::Calibration, &["c"]),
+    (KeyContext::Sensors, Action::Thresholds, &["t"]),
+    (KeyContext::Sensors, Action::Alerts, &["a"]),
+    (KeyContext::Sensors, Action::Help, &["?", "F1"]),
+    (KeyContext::FilePicker, Action::Close, &["Esc", "q"]),
+    (KeyContext::FilePicker, Action::Up, &["Up"]),
+    (KeyContext::FilePicker, Action::Down, &["Down"]),
+    (KeyContext::FilePicker, Action::Left, &["Left"]),
+    (KeyContext::FilePicker, Action::Open, &["Right", "Enter"]),
+    (KeyContext::FilePicker, Action::ImportDirectory, &["I"]),
+    (KeyContext::FilePicker, Action::Help, &["?", "F1"]),
+    (KeyContext::Graph, Action::Quit, &["C-q"]),
+    (KeyContext::Graph, Action::PrevTab, &["S-Tab"]),
+    (KeyContext::Graph, Action::NextTab, &["Tab"]),
+    (KeyContext::Graph, Action::NewTab, &["N"]),
+    (KeyContext::Graph, Action::CloseTab, &["q"]),
+    (KeyContext::Graph, Action::Edit, &["e"]),
+    (KeyContext::Graph, Action::Scatter, &["s"]),
+    (KeyContext::Graph, Action::Statistics, &["i"]),
+    (KeyContext::Graph, Action::GapBreaks, &["b"]),
+    (KeyContext::Graph, Action::ExcludeOutliers, &["x"]),
+    (KeyContext::Graph, Action::Calibrated, &["c"]),
+    (KeyContext::Graph, Action::SaveView, &["w"]),
+    (KeyContext::Graph, Action::OpenViews, &["o"]),
+    (KeyContext::Graph, Action::Annotation, &["m"]),
+    (KeyContext::Graph, Action::Left, &["Left"]),
+    (KeyContext::Graph, Action::Right, &["Right"]),
+    (KeyContext::Graph, Action::FastLeft, &["S-Left"]),
+    (KeyContext::Graph, Action::FastRight, &["S-Right"]),
+    (KeyContext::Graph, Action::Home, &["Home"]),
+    (KeyContext::Graph, Action::End, &["End"]),
+    (KeyContext::Graph, Action::ClearCursor, &["Esc"]),
+    (KeyContext::Graph, Action::Help, &["?", "F1"]),
+    (KeyContext::GraphEdit, Action::Close, &["Esc", "q"]),
+    (KeyContext::GraphEdit, Action::AddGraph, &["a"]),
+    (KeyContext::GraphEdit, Action::RemoveGraph, &["d"]),
+    (KeyContext::GraphEdit, Action::Up, &["Up"]),
+    (KeyContext::GraphEdit, Action::Down, &["Down"]),
+    (KeyContext::GraphEdit, Action::Left, &["Left", "S-Tab"]),
+    (KeyContext::GraphEdit, Action::Right, &["Right", "Tab"]),
+    (KeyContext::GraphEdit, Action::Open, &["Enter"]),
+    (KeyContext::GraphEdit, Action::Help, &["?", "F1"]),
+    (KeyContext::GraphInput, Action::Close, &["Esc", "Enter"]),
+    (KeyContext::GraphInput, Action::Left, &["Left"]),
+    (KeyContext::GraphInput, Action::Right, &["Right"]),
+    (KeyContext::GraphInput, Action::Home, &["Home"]),
+    (KeyContext::GraphInput, Action::End, &["End"]),
+    (KeyContext::GraphInput, Action::Erase, &["Backspace"]),
+    (KeyContext::GraphInput, Action::Help, &["F1"]),
+    (KeyContext::GraphMenu, Action::Close, &["Esc"]),
+    (KeyContext::GraphMenu, Action::Up, &["Up"]),
+    (KeyContext::GraphMenu, Action::Down, &["Down"]),
+    (KeyContext::GraphMenu, Action::Select, &["Enter"]),
+    (KeyContext::GraphMenu, Action::Erase, &["Backspace"]),
+    (KeyContext::GraphMenu, Action::Help, &["F1"]),
+    (KeyContext::Gaps, Action::Close, &["Esc", "q"]),
+    (KeyContext::Gaps, Action::Up, &["Up"]),
+    (KeyContext::Gaps, Action::Down, &["Down"]),
+    (KeyContext::Gaps, Action::PageUp, &["PageUp"]),
+    (KeyContext::Gaps, Action::PageDown, &["PageDown"]),
+    (KeyContext::Gaps, Action::Home, &["Home"]),
+    (KeyContext::Gaps, Action::End, &["End"]),
+    (KeyContext::Gaps, Action::Help, &["?", "F1"]),
+    (KeyContext::Alerts, Action::Close, &["Esc", "q"]),
+    (KeyContext::Alerts, Action::Up, &["Up"]),
+    (KeyContext::Alerts, Action::Down, &["Down"]),
+    (KeyContext::Alerts, Action::PageUp, &["PageUp"]),
+    (KeyContext::Alerts, Action::PageDown, &["PageDown"]),
+    (KeyContext::Alerts, Action::Home, &["Home"]),
+    (KeyContext::Alerts, Action::End, &["End"]),
+    (KeyContext::Alerts, Action::Help, &["?", "F1"]),
+    (KeyContext::Views, Action::Close, &["Esc", "q"]),
+    (KeyContext::Views, Action::Up, &["Up"]),
+    (KeyContext::Views, Action::Down, &["Down"]),
+    (KeyContext::Views, Action::Open, &["Enter"]),
+    (KeyContext::Views, Action::Delete, &["d", "Delete"]),
+    (KeyContext::Views, Action::Help, &["?", "F1"]),
+    (KeyContext::ViewName, Action::Close, &["Esc"]),
+    (KeyContext::ViewName, Action::Save, &["Enter"]),
+    (KeyContext::ViewName, Action::Up, &["Up"]),
+    (KeyContext::ViewName, Action::Down, &["Down"]),
+    (KeyContext::ViewName, Action::Left, &["Left"]),
+    (KeyContext::ViewName, Action::Right, &["Right"]),
+    (KeyContext::ViewName, Action::Home, &["Home"]),
+    (KeyContext::ViewName, Action::End, &["End"]),
+    (KeyContext::ViewName, Action::Erase, &["Backspace"]),
+    (KeyContext::ViewName, Action::Help, &["F1"]),
+    (KeyContext::Annotation, Action::Close, &["Esc"]),
+    (KeyContext::Annotation, Action::Save, &["Enter"]),
+    (KeyContext::Annotation, Action::Delete, &["C-d"]),
+    (KeyContext::Annotation, Action::PageUp, &["PageUp"]),
+    (KeyContext::Annotation, Action::PageDown, &["PageDown"]),
+    (KeyContext::Annotation, Action::Up, &["Up", "S-Tab"]),
+    (KeyContext::Annotation, Action::Down, &["Down", "Tab"]),
+    (KeyContext::Annotation, Action::Left, &["Left"]),
+    (KeyContext::Annotation, Action::Right, &["Right"]),
+    (KeyContext::Annotation, Action::Home, &["Home"]),
+    (KeyContext::Annotation, Action::End, &["End"]),
+    (KeyContext::Annotation, Action::Erase, &["Backspace"]),
+    (KeyContext::Annotation, Action::Help, &["F1"]),
+    (KeyContext::Outliers, Action::Close, &["Esc"]),
+    (KeyContext::Outliers, Action::Save, &["Enter"]),
+    (KeyContext::Outliers, Action::Up, &["Up", "S-Tab"]),
+    (KeyContext::Outliers, Action::Down, &["Down", "Tab"]),
+    (KeyContext::Outliers, Action::Left, &["Left"]),
+    (KeyContext::Outliers, Action::Right, &["Right"]),
+    (KeyContext::Outliers, Action::Home, &["Home"]),
+    (KeyContext::Outliers, Action::End, &["End"]),
+    (KeyContext::Outliers, Action::Erase, &["Backspace"]),
+    (KeyContext::Outliers, Action::Help, &["F1"]),
+    (KeyContext::Calibration, Action::Close, &["Esc"]),
+    (KeyContext::Calibration, Action::Save, &["Enter"]),
+    (KeyContext::Calibration, Action::Fit, &["C-f"]),
+    (KeyContext::Calibration, Action::Up, &["Up", "S-Tab"]),
+    (KeyContext::Calibration, Action::Down, &["Down", "Tab"]),
+    (KeyContext::Calibration, Action::Left, &["Left"]),
+    (KeyContext::Calibration, Action::Right, &["Right"]),
+    (KeyContext::Calibration, Action::Home, &["Home"]),
+    (KeyContext::Calibration, Action::End, &["End"]),
+    (KeyContext::Calibration, Action::Erase, &["Backspace"]),
+    (KeyContext::Calibration, Action::Help, &["F1"]),
+    (KeyContext::Thresholds, Action::Close, &["Esc"]),
+    (KeyContext::Thresholds, Action::Save, &["Enter"]),
+    (KeyContext::Thresholds, Action::Up, &["Up", "S-Tab"]),
+    (KeyContext::Thresholds, Action::Down, &["Down", "Tab"]),
+    (KeyContext::Thresholds, Action::Left, &["Left"]),
+    (KeyContext::Thresholds, Action::Right, &["Right"]),
+    (KeyContext::Thresholds, Action::Home, &["Home"]),
+    (KeyContext::Thresholds, Action::End, &["End"]),
+    (KeyContext::Thresholds, Action::Erase, &["Backspace"]),
+    (KeyContext::Thresholds, Action::Help, &["F1"]),
+];
+
+/// Сочетание клавиш в нотации README: `n`, `N` или `S-n`, `C-n`, `S-Tab`, `Enter`...
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyBinding {
+    /// Клавиша
+    pub code: KeyCode,
+
+    /// Модификаторы (Shift у символов учитывается в их регистре)
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    /// Разбирает сочетание клавиш из строки
+    pub fn parse(text: &str) -> Result<Self, String> {
+        // Сначала отделяем модификаторы
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text;
+        while rest.chars().count() > 2 {
+            let modifier = match rest.get(..2) {
+                Some("C-") => KeyModifiers::CONTROL,
+                Some("S-") => KeyModifiers::SHIFT,
+                Some("A-") => KeyModifiers::ALT,
+                _ => break,
+            };
+            modifiers |= modifier;
+            rest = &rest[2..];
+        }
+
+        // Затем саму клавишу
+        let code = match rest {
+            "Up" => KeyCode::Up,
+            "Down" => KeyCode::Down,
+            "Left" => KeyCode::Left,
+            "Right" => KeyCode::Right,
+            "Home" => KeyCode::Home,
+            "End" => KeyCode::End,
+            "PageUp" => KeyCode::PageUp,
+            "PageDown" => KeyCode::PageDown,
+            "Enter" => KeyCode::Enter,
+            "Esc" => KeyCode::Esc,
+            "Tab" => KeyCode::Tab,
+            "Backspace" => KeyCode::Backspace,
+            "Delete" => KeyCode::Delete,
+            "Insert" => KeyCode::Insert,
+            "Space" => KeyCode::Char(' '),
+            _ => match rest.strip_prefix('F').and_then(|n| n.parse().ok()) {
+                Some(n) => KeyCode::F(n),
+                None => {
+                    let mut chars = rest.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(ch), None) => KeyCode::Char(ch),
+                        _ => return Err(format!("Unknown key: {text}")),
+                    }
+                }
+            },
+        };
+
+        Ok(Self { code, modifiers }.normalized())
+    }
+
+    /// Приводит сочетание к тому виду, в котором его сравниваем с нажатием
+    fn normalized(mut self) -> Self {
+        // Shift у символов и Tab выражается в самой клавише, а не в модификаторе
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            match self.code {
+                KeyCode::Char(ch) => {
+                    self.code = KeyCode::Char(ch.to_uppercase().next().unwrap_or(ch));
+                    self.modifiers.remove(KeyModifiers::SHIFT);
+                }
+                KeyCode::Tab | KeyCode::BackTab => {
+                    self.code = KeyCode::BackTab;
+                    self.modifiers.remove(KeyModifiers::SHIFT);
+                }
+                _ => (),
+            }
+        }
+
+        // Остальные модификаторы (Super, Hyper...) не используем
+        self.modifiers &= KeyModifiers::CONTROL | KeyModifiers::SHIFT | KeyModifiers::ALT;
+        self
+    }
+}
+
+impl std::fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Модификаторы в той же нотации, в которой их разбираем
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "C-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "A-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "S-")?;
+        }
+
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(ch) => write!(f, "{ch}"),
+            KeyCode::BackTab => write!(f, "S-Tab"),
+            KeyCode::F(n) => write!(f, "F{n}"),
+            KeyCode::PageUp => write!(f, "PageUp"),
+            KeyCode::PageDown => write!(f, "PageDown"),
+            code => write!(f, "{code:?}"),
+        }
+    }
+}
+
+impl From<KeyEvent> for KeyBinding {
+    fn from(event: KeyEvent) -> Self {
+        Self {
+            code: event.code,
+            modifiers: event.modifiers,
+        }
+        .normalized()
+    }
+}
+
+/// Возвращает название режима или действия так, как оно пишется в конфиге (`exclude_outliers`)
+fn config_name(value: &impl std::fmt::Debug) -> String {
+    let mut name = String::new();
+    for (i, ch) in format!("{value:?}").chars().enumerate() {
+        if ch.is_uppercase() && i > 0 {
+            name.push('_');
+        }
+        name.extend(ch.to_lowercase());
+    }
+    name
+}
+
+/// Клавиши, переназначенные в конфиге: режим -> действие -> список сочетаний
+pub type KeymapConfig = HashMap<KeyContext, HashMap<Action, Vec<String>>>;
+
+/// Назначения клавиш во всех режимах приложения
+#[derive(Clone, Debug)]
+pub struct Keymap {
+    /// Сочетания клавиш и их действия (при совпадении побеждает первое)
+    bindings: HashMap<KeyContext, Vec<(KeyBinding, Action)>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let mut keymap = Self {
+            bindings: HashMap::new(),
+        };
+        for &(context, action, keys) in DEFAULT_BINDINGS {
+            let bindings = keymap.bindings.entry(context).or_default();
+            for key in keys {
+                bindings.push((KeyBinding::parse(key).unwrap(), action));
+            }
+        }
+        keymap
+    }
+}
+
+impl Keymap {
+    /// Создаёт назначения клавиш по умолчанию и применяет к ним переназначения из конфига.
+    /// Переназначенное действие теряет клавиши по умолчанию. Если после этого одна клавиша режима
+    /// назначена на два действия, то возвращается ошибка
+    pub fn new(config: &KeymapConfig) -> Result<Self, String> {
+        let mut keymap = Self::default();
+        for (context, actions) in config {
+            let bindings = keymap.bindings.entry(*context).or_default();
+            for (action, keys) in actions {
+                bindings.retain(|(_, bound_action)| bound_action != action);
+                for key in keys.iter().rev() {
+                    bindings.insert(0, (KeyBinding::parse(key)?, *action));
+                }
+            }
+        }
+
+        // Иначе одно из действий молча перестало бы срабатывать
+        for (context, bindings) in &keymap.bindings {
+            for (i, (binding, action)) in bindings.iter().enumerate() {
+                let duplicate = bindings[..i]
+                    .iter()
+                    .find(|(other, other_action)| other == binding && other_action != action);
+                if let Some((_, other_action)) = duplicate {
+                    return Err(format!(
+                        "Key {binding} in {} is bound to both {} and {}",
+                        config_name(context),
+                        config_name(other_action),
+                        config_name(action),
+                    ));
+                }
+            }
+        }
+
+        Ok(keymap)
+    }
+
+    /// Возвращает действия данного режима с их клавишами (в порядке назначения)
+    pub fn bindings(&self, context: KeyContext) -> Vec<(Action, Vec<KeyBinding>)> {
+        let mut actions: Vec<(Action, Vec<KeyBinding>)> = vec![];
+        for &(binding, action) in self.bindings.get(&context).into_iter().flatten() {
+            match actions
+                .iter_mut()
+                .find(|(bound_action, _)| *bound_action == action)
+            {
+                Some((_, bindings)) => bindings.push(binding),
+                None => actions.push((action, vec![binding])),
+            }
+        }
+        actions
+    }
+
+    /// Возвращает действие, назначенное на нажатие в данном режиме
+    pub fn action(&self, context: KeyContext, event: KeyEvent) -> Option<Action> {
+        let key = KeyBinding::from(event);
+        self.bindings
+            .get(&context)?
+            .iter()
+            .find(|(binding, _)| *binding == key)
+            .map(|&(_, action)| action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn binding(code: KeyCode, modifiers: KeyModifiers) -> KeyBinding {
+        KeyBinding { code, modifiers }
+    }
+
+    #[test]
+    fn normalizes_shift() {
+        let back_tab = binding(KeyCode::BackTab, KeyModifiers::NONE);
+        assert_eq!(KeyBinding::parse("S-Tab"), Ok(back_tab));
+        // Терминал присылает Shift+Tab как BackTab с модификатором Shift
+        let event = KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT);
+        assert_eq!(KeyBinding::from(event), back_tab);
+
+        let upper = binding(KeyCode::Char('N'), KeyModifiers::NONE);
+        assert_eq!(KeyBinding::parse("N"), Ok(upper));
+        assert_eq!(KeyBinding::parse("S-n"), Ok(upper));
+        let event = KeyEvent::new(KeyCode::Char('N'), KeyModifiers::SHIFT);
+        assert_eq!(KeyBinding::from(event), upper);
+    }
+
+    #[test]
+    fn parses_modifiers_and_keys() {
+        let ctrl_q = binding(KeyCode::Char('q'), KeyModifiers::CONTROL);
+        assert_eq!(KeyBinding::parse("C-q"), Ok(ctrl_q));
+        assert_eq!(ctrl_q.to_string(), "C-q");
+        assert_eq!(
+            KeyBinding::parse("S-Left"),
+            Ok(binding(KeyCode::Left, KeyModifiers::SHIFT))
+        );
+        assert_eq!(
+            KeyBinding::parse("Space"),
+            Ok(binding(KeyCode::Char(' '), KeyModifiers::NONE))
+        );
+        assert_eq!(
+            KeyBinding::parse("F1"),
+            Ok(binding(KeyCode::F(1), KeyModifiers::NONE))
+        );
+        // Одиночные символы `C` и `-` - это клавиши, а не модификаторы
+        assert_eq!(
+            KeyBinding::parse("-"),
+            Ok(binding(KeyCode::Char('-'), KeyModifiers::NONE))
+        );
+        assert!(KeyBinding::parse("Foo").is_err());
+        assert!(KeyBinding::parse("C-").is_err());
+    }
+
+    #[test]
+    fn remaps_actions() {
+        let config = KeymapConfig::from([(
+            KeyContext::Graph,
+            HashMap::from([(Action::Quit, vec!["Q".to_owned()])]),
+        )]);
+        let keymap = Keymap::new(&config).unwrap();
+        let press =
+            |code, modifiers| keymap.action(KeyContext::Graph, KeyEvent::new(code, modifiers));
+        assert_eq!(
+            press(KeyCode::Char('Q'), KeyModifiers::SHIFT),
+            Some(Action::Quit)
+        );
+        assert_eq!(press(KeyCode::Char('q'), KeyModifiers::CONTROL), None);
+    }
+
+    #[test]
+    fn rejects_duplicate_keys() {
+        // У клавиш по умолчанию повторов нет
+        assert!(Keymap::new(&KeymapConfig::new()).is_ok());
+
+        let config = KeymapConfig::from([(
+            KeyContext::Graph,
+            HashMap::from([(Action::Quit, vec!["x".to_owned()])]),
+        )]);
+        let error = Keymap::new(&config).unwrap_err();
+        assert!(error.contains("graph"), "{error}");
+        assert!(error.contains("quit"), "{error}");
+        assert!(error.contains("exclude_outliers"), "{error}");
+
+        // В разных режимах одна и та же клавиша может означать разное
+        let config = KeymapConfig::from([(
+            KeyContext::Sensors,
+            HashMap::from([(Action::Quit, vec!["x".to_owned()])]),
+        )]);
+        assert!(Keymap::new(&config).is_ok());
+    }
+
+    #[test]
+    fn leaves_characters_for_typing() {
+        // В режимах с вводом текста символы без модификаторов по умолчанию ни на что не назначены
+        let keymap = Keymap::default();
+        for context in [
+            KeyContext::GraphInput,
+            KeyContext::GraphMenu,
+            KeyContext::ViewName,
+            KeyContext::Annotation,
+            KeyContext::Outliers,
+            KeyContext::Calibration,
+            KeyContext::Thresholds,
+        ] {
+            assert!(context.typing_hint().is_some(), "{context:?}");
+            for (action, bindings) in keymap.bindings(context) {
+                for binding in bindings {
+                    let is_char = matches!(binding.code, KeyCode::Char(_));
+                    assert!(
+                        !is_char || binding.modifiers.contains(KeyModifiers::CONTROL),
+                        "{binding} в {context:?} назначена на {action:?}"
+                    );
+                }
+            }
+        }
+
+        // В окнах с полями ввода Ctrl с символом - действие, а не ввод
+        let press =
+            |context, code, modifiers| keymap.action(context, KeyEvent::new(code, modifiers));
+        assert_eq!(
+            press(
+                KeyContext::Annotation,
+                KeyCode::Char('d'),
+                KeyModifiers::CONTROL
+            ),
+            Some(Action::Delete)
+        );
+        assert_eq!(
+            press(
+                KeyContext::Calibration,
+                KeyCode::Char('f'),
+                KeyModifiers::CONTROL
+            ),
+            Some(Action::Fit)
+        );
+        assert_eq!(
+            press(
+                KeyContext::Annotation,
+                KeyCode::Char('d'),
+                KeyModifiers::NONE
+            ),
+            None
+        );
+    }
+}
@@ -5,6 +5,9 @@ use tui::{backend::Backend, Terminal};
 
 use self::state::App;
 
+pub mod config;
+pub mod keymap;
+pub mod session;
 pub mod state;
 pub mod tabs;
 
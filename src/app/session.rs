@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+
+use crate::graph::view::GraphView;
+
+use super::{state::App, tabs::TabState};
+
+/// Сессия приложения: всё, что нужно, чтобы после перезапуска интерфейс выглядел так же
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    /// Виды открытых вкладок графиков по порядку
+    pub tabs: Vec<GraphView>,
+
+    /// Индекс активной вкладки
+    pub current: usize,
+
+    /// Раскрытые узлы дерева датчиков (пути по названиям)
+    #[serde(default)]
+    pub opened_sensors: Vec<Vec<String>>,
+}
+
+impl Session {
+    /// Переводит сессию в JSON для сохранения
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Читает сессию из JSON
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+}
+
+impl App<'_> {
+    /// Сохраняет открытые вкладки, активную вкладку и раскрытые узлы дерева датчиков
+    pub fn save_session(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let mut session = Session {
+            current: self.tabs.current,
+            ..Default::default()
+        };
+
+        for state in self.tabs.states() {
+            match state {
+                TabState::Graph(state) => session.tabs.push(state.get_view()),
+                TabState::Sensors(state) => session.opened_sensors = state.get_opened_nodes(),
+            }
+        }
+
+        self.store_session(&session)
+    }
+
+    /// Восстанавливает сессию, сохранённую при прошлом выходе из приложения
+    pub fn restore_session(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(session) = self.fetch_session()? else {
+            return Ok(());
+        };
+
+        // Раскрываем узлы дерева датчиков (при запуске открыта только вкладка датчиков)
+        self.sensors_state_mut().open_nodes(&session.opened_sensors);
+
+        // Открываем вкладки графиков
+        for view in &session.tabs {
+            self.open_new_tab();
+            self.apply_graph_view(view);
+        }
+
+        // Возвращаемся на ту вкладку, на которой были
+        self.tabs.select(session.current);
+
+        Ok(())
+    }
+}
@@ -4,14 +4,17 @@ use std::{
     sync::{Arc, Mutex},
 };
 
-use crossterm::event::{KeyEvent, MouseEvent};
+use crossterm::event::{KeyEvent, MouseButton, MouseEvent, MouseEventKind};
 
 use crate::{
     database::SensorsFields, filepicker::state::FilePickerState, graph::state::GraphState,
-    sensors::state::SensorsState,
+    help::state::HelpState, sensors::state::SensorsState,
 };
 
-use super::tabs::{TabState, Tabs};
+use super::{
+    keymap::Keymap,
+    tabs::{TabState, Tabs},
+};
 
 /// Структура, определяющая состояние приложения
 /// Используется везде, где только можно
@@ -31,11 +34,20 @@ pub struct App<'a> {
 
     /// Определяет вкладки, открытые в приложении
     pub tabs: Tabs<'a>,
+
+    /// Назначения клавиш во всех режимах приложения
+    pub keymap: Keymap,
+
+    /// Состояние окна справки по клавишам, если оно открыто
+    pub help_state: Option<HelpState>,
 }
 
 impl<'a> App<'a> {
     /// Создаёт новое состояние приложения
-    pub fn new(database: rusqlite::Connection) -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn new(
+        database: rusqlite::Connection,
+        keymap: Keymap,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
         // Делаем базовый экземпляр состояния приложения
         let mut app = Self {
             running: true,
@@ -43,6 +55,8 @@ impl<'a> App<'a> {
             sensor_fields: Rc::new(RefCell::new(SensorsFields::new())),
             sensor_serials: Rc::new(RefCell::new(SensorsFields::new())),
             tabs: Tabs::default(),
+            keymap,
+            help_state: None,
         };
 
         // Подготавливаем первую вкладку - вкладка сенсоров
@@ -54,6 +68,9 @@ impl<'a> App<'a> {
         // Подготавливаем дерево сенсоров
         app.update_sensor_data()?;
 
+        // Восстанавливаем вкладки, открытые при прошлом выходе
+        app.restore_session()?;
+
         Ok(app)
     }
 
@@ -67,6 +84,12 @@ impl<'a> App<'a> {
 
     /// Обрабатывает все события, связанные с нажатием клавиш
     pub fn on_key_event(&mut self, event: KeyEvent) -> std::io::Result<()> {
+        // Окно справки перекрывает любую вкладку
+        if self.help_state.is_some() {
+            self.on_key_event_help(event);
+            return Ok(());
+        }
+
         match self.tabs.state() {
             TabState::Graph(_) => self.on_key_event_graph(event),
             TabState::Sensors(_) => self.on_key_event_sensors(event)?,
@@ -76,8 +99,25 @@ impl<'a> App<'a> {
     }
 
     /// Обрабатывает все события, связанные с мышкой
-    pub fn on_mouse_event(&mut self, _event: MouseEvent) -> std::io::Result<()> {
-        // dbg!(event);
+    pub fn on_mouse_event(&mut self, event: MouseEvent) -> std::io::Result<()> {
+        // Окно справки управляется только клавишами
+        if self.help_state.is_some() {
+            return Ok(());
+        }
+
+        // Нажатие на заголовок вкладки переключает на неё
+        if let MouseEventKind::Down(MouseButton::Left) = event.kind {
+            if let Some(i) = self.tabs.tab_at(event.column, event.row) {
+                self.tabs.select(i);
+                return Ok(());
+            }
+        }
+
+        match self.tabs.state() {
+            TabState::Graph(_) => self.on_mouse_event_graph(event),
+            TabState::Sensors(_) => self.on_mouse_event_sensors(event),
+        }
+
         Ok(())
     }
 
@@ -1,4 +1,11 @@
-use crate::{graph::state::GraphState, sensors::state::SensorsState};
+use tui::layout::Rect;
+
+use crate::{
+    graph::state::GraphState,
+    locale::{tr, trf},
+    sensors::state::SensorsState,
+    ui::utils::area_contains,
+};
 
 /// Структура определяющая состояние вкладок
 /// Используется для определения того, в какой вкладке мы находимся, и что должны отображать
@@ -9,6 +16,9 @@ pub struct Tabs<'a> {
 
     /// Содержит индекс активной вкладки
     pub current: usize,
+
+    /// Содержит области, в которых были отрисованы заголовки вкладок
+    pub areas: Vec<Rect>,
 }
 
 impl Default for Tabs<'_> {
@@ -24,6 +34,7 @@ impl<'a> Tabs<'a> {
             // Первая вкладка всегда данные и не может быть закрыта
             states: Vec::new(),
             current: 0,
+            areas: Vec::new(),
         }
     }
 
@@ -60,6 +71,23 @@ impl<'a> Tabs<'a> {
         self.current = (self.current + self.states.len() - 1) % self.states.len();
     }
 
+    /// Переключает на вкладку с данным индексом (или на последнюю, если такой нет)
+    pub fn select(&mut self, index: usize) {
+        self.current = index.min(self.states.len() - 1);
+    }
+
+    /// Возвращает индекс вкладки, заголовок которой находится в данной клетке
+    pub fn tab_at(&self, column: u16, row: u16) -> Option<usize> {
+        self.areas
+            .iter()
+            .position(|&area| area_contains(area, column, row))
+    }
+
+    /// Возвращает ссылки на состояния всех вкладок
+    pub fn states(&self) -> &[TabState<'a>] {
+        &self.states
+    }
+
     /// Обновляет поля датчиков во всех вкладках
     pub fn update_sensor_data(&mut self) {
         for tab in &mut self.states {
@@ -86,8 +114,8 @@ impl<'a> Tabs<'a> {
             .iter()
             .enumerate()
             .map(|(i, state)| match state {
-                TabState::Sensors(_) => "Датчики".to_owned(),
-                TabState::Graph(_) => format!("График {i}"),
+                TabState::Sensors(_) => tr("Датчики").to_owned(),
+                TabState::Graph(_) => trf("График {}", &[&i]),
             })
             .collect()
     }
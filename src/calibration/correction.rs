@@ -0,0 +1,210 @@
+use itertools::Itertools;
+
+/// Максимальная степень полинома поправки, которую можно подобрать по эталону
+pub const MAX_FIT_DEGREE: usize = 3;
+
+/// Поправка одного прибора: полином a0 + a1·x + a2·x² + …, который действует в промежутке дат
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Calibration {
+    /// Серийник прибора
+    pub serial: String,
+
+    /// Коэффициенты полинома, начиная со свободного члена
+    pub coefficients: Vec<f64>,
+
+    /// Дата, с которой действует поправка (без неё - с самого начала)
+    pub valid_from: Option<i64>,
+
+    /// Дата, по которую действует поправка (без неё - без конца)
+    pub valid_to: Option<i64>,
+}
+
+/// Результат подбора поправки по эталонному прибору
+#[derive(Clone, Debug)]
+pub struct CalibrationFit {
+    /// Коэффициенты полинома, начиная со свободного члена
+    pub coefficients: Vec<f64>,
+
+    /// Коэффициент детерминации
+    pub r2: f64,
+
+    /// Количество пар значений, по которым подобрана поправка
+    pub count: usize,
+}
+
+impl Calibration {
+    /// Возвращает формулу поправки для отображения
+    pub fn formula(&self) -> String {
+        let terms = self
+            .coefficients
+            .iter()
+            .enumerate()
+            .map(|(power, coefficient)| match power {
+                0 => format!("{coefficient}"),
+                1 => format!("{coefficient}·x"),
+                power => format!("{coefficient}·x^{power}"),
+            })
+            .join(" + ");
+        format!("y = {terms}")
+    }
+}
+
+/// Считает значение полинома с коэффициентами, начиная со свободного члена
+pub fn polyval(coefficients: &[f64], x: f64) -> f64 {
+    // Схема Горнера
+    coefficients
+        .iter()
+        .rev()
+        .fold(0., |value, coefficient| value * x + coefficient)
+}
+
+/// Относительная точность, с которой главный элемент нормальных уравнений считается нулевым
+const PIVOT_TOLERANCE: f64 = 1e-10;
+
+/// Подбирает полином степени `degree`, который переводит значения прибора в значения эталона,
+/// методом наименьших квадратов. Точки - пары (прибор, эталон)
+pub fn fit_polynomial(points: &[(f64, f64)], degree: usize) -> Option<CalibrationFit> {
+    let size = degree + 1;
+    if points.len() < size {
+        return None;
+    }
+
+    // Подбираем полином от u = (x - center) / scale, где u лежит в [-1, 1]: степени больших значений
+    // (например, давления ~1000 гПа) делают нормальные уравнения почти вырожденными
+    let center = points.iter().map(|&(x, _)| x).sum::<f64>() / points.len() as f64;
+    let scale = points
+        .iter()
+        .map(|&(x, _)| (x - center).abs())
+        .fold(0., f64::max);
+    let scale = match scale > 0. {
+        true => scale,
+        // Если все значения прибора одинаковые, то подобрать можно только сдвиг
+        false if degree == 0 => 1.,
+        false => return None,
+    };
+
+    // Составляем нормальные уравнения: Σ u^(i+j) · b_j = Σ y · u^i
+    let mut matrix = vec![vec![0.; size + 1]; size];
+    for &(x, y) in points {
+        let u = (x - center) / scale;
+        let powers: Vec<_> = (0..2 * size).map(|power| u.powi(power as i32)).collect();
+        for (i, row) in matrix.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().take(size).enumerate() {
+                *cell += powers[i + j];
+            }
+            row[size] += y * powers[i];
+        }
+    }
+
+    // Решаем их методом Гаусса с выбором главного элемента. Главный элемент сравниваем с наибольшим
+    // элементом матрицы, т.к. абсолютный порог зависит от количества точек
+    let norm = matrix
+        .iter()
+        .flat_map(|row| &row[..size])
+        .fold(0., |norm: f64, value| norm.max(value.abs()));
+    for col in 0..size {
+        let pivot = (col..size)
+            .max_by(|&a, &b| matrix[a][col].abs().total_cmp(&matrix[b][col].abs()))
+            .unwrap();
+        if matrix[pivot][col].abs() <= PIVOT_TOLERANCE * norm {
+            return None;
+        }
+        matrix.swap(col, pivot);
+
+        let pivot_row = matrix[col].clone();
+        for (row, values) in matrix.iter_mut().enumerate() {
+            if row != col {
+                let factor = values[col] / pivot_row[col];
+                for (value, pivot) in values.iter_mut().zip(&pivot_row).skip(col) {
+                    *value -= factor * pivot;
+                }
+            }
+        }
+    }
+    let scaled: Vec<_> = (0..size).map(|i| matrix[i][size] / matrix[i][i]).collect();
+
+    // Переводим коэффициенты обратно к x: b_k · ((x - center) / scale)^k раскладываем по биному
+    let mut coefficients = vec![0.; size];
+    for (k, b) in scaled.iter().enumerate() {
+        let term = b / scale.powi(k as i32);
+        let mut binomial = 1.;
+        for (j, coefficient) in coefficients.iter_mut().enumerate().take(k + 1).rev() {
+            // C(k, j) · (-center)^(k - j)
+            *coefficient += term * binomial * (-center).powi((k - j) as i32);
+            binomial = binomial * j as f64 / (k - j + 1) as f64;
+        }
+    }
+
+    // Считаем, насколько хорошо поправка приближает эталон
+    let mean = points.iter().map(|&(_, y)| y).sum::<f64>() / points.len() as f64;
+    let (residual, total) = points.iter().fold((0., 0.), |(residual, total), &(x, y)| {
+        let error = y - polyval(&coefficients, x);
+        (residual + error * error, total + (y - mean) * (y - mean))
+    });
+    let r2 = match total > 0. {
+        true => 1. - residual / total,
+        false => 1.,
+    };
+
+    Some(CalibrationFit {
+        coefficients,
+        r2,
+        count: points.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!(
+                (a - e).abs() <= 1e-6 * e.abs().max(1.),
+                "{actual:?} != {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn fits_exact_linear() {
+        let points: Vec<_> = (0..10).map(|i| (i as f64, 0.5 + 2. * i as f64)).collect();
+        let fit = fit_polynomial(&points, 1).unwrap();
+        assert_close(&fit.coefficients, &[0.5, 2.]);
+        assert!((fit.r2 - 1.).abs() < 1e-12);
+        assert_eq!(fit.count, 10);
+    }
+
+    #[test]
+    fn fits_exact_quadratic_far_from_zero() {
+        // Значения около 1000, как у давления
+        let points: Vec<_> = (0..50)
+            .map(|i| {
+                let x = 990. + i as f64 * 0.5;
+                (x, 3. - 0.5 * x + 0.001 * x * x)
+            })
+            .collect();
+        let fit = fit_polynomial(&points, 2).unwrap();
+        assert_close(&fit.coefficients, &[3., -0.5, 0.001]);
+        for &(x, y) in &points {
+            assert!((polyval(&fit.coefficients, x) - y).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn rejects_singular_fits() {
+        // Все значения прибора одинаковые
+        assert!(fit_polynomial(&[(5., 1.), (5., 2.), (5., 3.)], 1).is_none());
+        // Двух различных значений не хватает для квадратичного полинома
+        assert!(fit_polynomial(&[(1., 1.), (2., 2.), (1., 1.), (2., 2.)], 2).is_none());
+        // Точек меньше, чем коэффициентов
+        assert!(fit_polynomial(&[(1., 1.), (2., 2.)], 2).is_none());
+    }
+
+    #[test]
+    fn evaluates_polynomials() {
+        assert_eq!(polyval(&[1., 2., 3.], 2.), 17.);
+        assert_eq!(polyval(&[], 2.), 0.);
+    }
+}
@@ -0,0 +1,144 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use itertools::Itertools;
+
+use crate::{
+    app::{
+        keymap::{Action, KeyContext},
+        state::App,
+    },
+    database::calibration,
+    locale::{tr, trf},
+};
+
+use self::state::CalibrationState;
+
+pub mod correction;
+pub mod state;
+pub mod ui;
+
+impl App<'_> {
+    /// Открывает окно поправок приборов для выбранного в дереве поля датчика
+    pub fn open_calibration(&mut self) {
+        // Поправки задаются только для полей данных
+        let Some((sensor, field)) = self.sensors_state().get_selected_field() else {
+            return;
+        };
+        if ["id", "serial", "date"].contains(&field.as_str()) {
+            return;
+        }
+
+        // Загружаем сохранённые поправки приборов
+        let serials = self.sensor_serials.borrow()[&sensor].clone();
+        let result = {
+            let database = self.database.lock().unwrap();
+            calibration::load_calibrations(&database, &sensor, &field)
+        };
+        let state = match result {
+            Ok(calibrations) => CalibrationState::new(sensor, field, serials, calibrations),
+            Err(err) => {
+                let mut state = CalibrationState::new(sensor, field, serials, vec![]);
+                state.error = Some(trf("Не удалось загрузить поправки: {}", &[&err]));
+                state
+            }
+        };
+        self.sensors_state_mut().calibration_state = Some(state);
+    }
+
+    /// Обрабатывает все события, связанные с нажатием клавиш в окне поправок приборов
+    pub fn on_key_event_calibration(&mut self, event: KeyEvent) {
+        // Действие, назначенное на клавишу в окне поправок (символы без действия вводятся в поле)
+        let action = self.keymap.action(KeyContext::Calibration, event);
+
+        // Получаем состояние окна поправок
+        let sensors_state = self.sensors_state_mut();
+        let state = sensors_state.calibration_state.as_mut().unwrap();
+
+        match (action, event.code) {
+            (Some(Action::Close), _) => sensors_state.calibration_state = None,
+            (Some(Action::Save), _) => self.save_calibration(),
+            (Some(Action::Fit), _) => self.fit_calibration(),
+            (Some(Action::Up), _) => state.prev(),
+            (Some(Action::Down), _) => state.next(),
+            (Some(Action::Left), _) => state.selected_input_mut().goto_prev(),
+            (Some(Action::Right), _) => state.selected_input_mut().goto_next(),
+            (Some(Action::Home), _) => state.selected_input_mut().goto_start(),
+            (Some(Action::End), _) => state.selected_input_mut().goto_end(),
+            (None, KeyCode::Char(ch)) => state.selected_input_mut().insert(ch),
+            (Some(Action::Erase), _) => state.selected_input_mut().remove(),
+            (Some(Action::Help), _) => self.open_help(KeyContext::Calibration),
+            _ => (),
+        }
+    }
+
+    /// Сохраняет поправку прибора из окна (или удаляет её, если коэффициентов нет)
+    fn save_calibration(&mut self) {
+        // Получаем поправку из полей ввода
+        let database = self.database.clone();
+        let state = self.sensors_state_mut().calibration_state.as_mut().unwrap();
+        state.status = None;
+        let Some(calibration) = state.get_calibration() else {
+            state.error = Some(tr("В параметрах поправки есть ошибки").to_owned());
+            return;
+        };
+
+        // Сохраняем поправку и перечитываем список поправок
+        let result = {
+            let database = database.lock().unwrap();
+            calibration::save_calibration(&database, &state.sensor, &state.field, &calibration)
+                .and_then(|_| {
+                    calibration::load_calibrations(&database, &state.sensor, &state.field)
+                })
+        };
+        match result {
+            Ok(calibrations) => {
+                state.calibrations = calibrations;
+                state.status = Some(match calibration.coefficients.is_empty() {
+                    true => trf("Поправка прибора {} удалена", &[&calibration.serial]),
+                    false => trf("Поправка прибора {} сохранена", &[&calibration.serial]),
+                });
+                state.error = None;
+            }
+            Err(err) => state.error = Some(trf("Не удалось сохранить поправку: {}", &[&err])),
+        }
+    }
+
+    /// Подбирает поправку прибора по эталонному прибору и подставляет её коэффициенты в окно
+    fn fit_calibration(&mut self) {
+        // Получаем прибор, промежуток и параметры подбора из полей ввода
+        let database = self.database.clone();
+        let state = self.sensors_state_mut().calibration_state.as_mut().unwrap();
+        state.status = None;
+        let (calibration, params) = (state.get_calibration(), state.get_fit_params());
+        let (Some(calibration), Some((reference, degree))) = (calibration, params) else {
+            state.error = Some(tr("В параметрах подбора есть ошибки").to_owned());
+            return;
+        };
+
+        // Подбираем поправку по совмещённым записям приборов
+        let result = {
+            let database = database.lock().unwrap();
+            calibration::fit_calibration(
+                &database,
+                &state.sensor,
+                &state.field,
+                &calibration,
+                &reference,
+                degree,
+            )
+        };
+        match result {
+            Ok(Some(fit)) => {
+                state.inputs[1].set_content(fit.coefficients.iter().join(" "));
+                state.status = Some(trf(
+                    "Подобрано по {} парам записей, R² = {}. Enter - сохранить",
+                    &[&fit.count, &format!("{:.4}", fit.r2)],
+                ));
+                state.error = None;
+            }
+            Ok(None) => {
+                state.error = Some(tr("Недостаточно совпадающих записей с эталоном").to_owned())
+            }
+            Err(err) => state.error = Some(trf("Не удалось подобрать поправку: {}", &[&err])),
+        }
+    }
+}
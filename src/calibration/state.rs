@@ -0,0 +1,207 @@
+use chrono::NaiveDateTime;
+use itertools::Itertools;
+
+use crate::{
+    database::gaps::format_date,
+    locale::{tr, trf},
+    ui::input::InputState,
+};
+
+use super::correction::{Calibration, MAX_FIT_DEGREE};
+
+/// Названия полей поправки в порядке отображения в окне
+pub const CALIBRATION_FIELDS: [&str; 6] = [
+    "Серийник",
+    "Коэффициенты a0 a1 …",
+    "Действует с",
+    "Действует по",
+    "Эталонный серийник",
+    "Степень подбора",
+];
+
+/// Описывает состояние окна поправок приборов одного поля датчика
+#[derive(Debug)]
+pub struct CalibrationState {
+    /// Название датчика
+    pub sensor: String,
+
+    /// Название поля датчика
+    pub field: String,
+
+    /// Сохраняет серийники приборов датчика
+    pub serials: Vec<String>,
+
+    /// Сохраняет поправки приборов, которые уже есть в БД
+    pub calibrations: Vec<Calibration>,
+
+    /// Сохраняет поля ввода параметров поправки (в порядке `CALIBRATION_FIELDS`)
+    pub inputs: [InputState; 6],
+
+    /// Сохраняет индекс выбранного поля ввода
+    pub selection_index: usize,
+
+    /// Сохраняет серийник, поправка которого сейчас в полях ввода
+    loaded_serial: String,
+
+    /// Сохраняет результат последнего действия
+    pub status: Option<String>,
+
+    /// Сохраняет ошибку последнего действия, если она была
+    pub error: Option<String>,
+}
+
+impl CalibrationState {
+    /// Создаёт новое состояние окна, заполняя поля поправкой первого прибора
+    pub fn new(
+        sensor: String,
+        field: String,
+        serials: Vec<String>,
+        calibrations: Vec<Calibration>,
+    ) -> Self {
+        let mut inputs: [InputState; 6] = Default::default();
+        inputs[0].set_content(serials.first().cloned().unwrap_or_default());
+        inputs[0].open();
+
+        let mut state = Self {
+            sensor,
+            field,
+            serials,
+            calibrations,
+            inputs,
+            selection_index: 0,
+            loaded_serial: String::new(),
+            status: None,
+            error: None,
+        };
+        state.load_serial();
+        state
+    }
+
+    /// Возвращает изменяемую ссылку на выбранное поле ввода
+    pub fn selected_input_mut(&mut self) -> &mut InputState {
+        &mut self.inputs[self.selection_index]
+    }
+
+    /// Выбирает предыдущее поле ввода
+    pub fn prev(&mut self) {
+        self.select((self.selection_index + self.inputs.len() - 1) % self.inputs.len());
+    }
+
+    /// Выбирает следующее поле ввода
+    pub fn next(&mut self) {
+        self.select((self.selection_index + 1) % self.inputs.len());
+    }
+
+    /// Заполняет поля поправки последней сохранённой поправкой введённого прибора (или очищает их)
+    pub fn load_serial(&mut self) {
+        self.loaded_serial = self.inputs[0].content().trim().to_owned();
+        let calibration = self
+            .calibrations
+            .iter()
+            .rfind(|calibration| calibration.serial == self.loaded_serial)
+            .cloned()
+            .unwrap_or_default();
+
+        let values = [
+            calibration.coefficients.iter().join(" "),
+            calibration.valid_from.map(format_date).unwrap_or_default(),
+            calibration.valid_to.map(format_date).unwrap_or_default(),
+        ];
+        for (input, value) in self.inputs[1..4].iter_mut().zip(values) {
+            input.set_content(value);
+            input.set_error(None);
+        }
+    }
+
+    /// Собирает поправку из полей ввода. Если в каком-то поле ошибка, отмечает его и возвращает `None`
+    pub fn get_calibration(&mut self) -> Option<Calibration> {
+        // Пустая дата означает, что промежуток с этой стороны не ограничен
+        fn parse_date(input: &mut InputState) -> Result<Option<i64>, ()> {
+            let result = match input.content().trim() {
+                "" => Ok(None),
+                content => NaiveDateTime::parse_from_str(content, "%Y-%m-%d %H:%M:%S")
+                    .map(|date| Some(date.timestamp()))
+                    .map_err(|_| ()),
+            };
+            let error = result
+                .is_err()
+                .then(|| tr("Ожидалась дата формата %Y-%m-%d %H:%M:%S").to_owned());
+            input.set_error(error);
+            result
+        }
+
+        let serial = self.parse_serial(0);
+        let [_, coefficients, valid_from, valid_to, ..] = &mut self.inputs;
+
+        // Коэффициенты вводятся через пробел, начиная со свободного члена
+        let parsed: Result<Vec<f64>, _> = coefficients
+            .content()
+            .split_whitespace()
+            .map(str::parse)
+            .collect();
+        let error = parsed
+            .is_err()
+            .then(|| tr("Ожидались числа через пробел").to_owned());
+        coefficients.set_error(error);
+
+        match (serial, parsed, parse_date(valid_from), parse_date(valid_to)) {
+            (Some(serial), Ok(coefficients), Ok(valid_from), Ok(valid_to)) => Some(Calibration {
+                serial,
+                coefficients,
+                valid_from,
+                valid_to,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Возвращает эталонный серийник и степень подбора. Если в каком-то поле ошибка, отмечает его
+    pub fn get_fit_params(&mut self) -> Option<(String, usize)> {
+        let reference = self.parse_serial(4);
+
+        // Без степени подбирается линейная поправка
+        let degree = match self.inputs[5].content().trim() {
+            "" => Ok(1),
+            content => match content.parse() {
+                Ok(degree) if (1..=MAX_FIT_DEGREE).contains(&degree) => Ok(degree),
+                _ => Err(trf("Ожидалось целое число от 1 до {}", &[&MAX_FIT_DEGREE])),
+            },
+        };
+        self.inputs[5].set_error(degree.clone().err());
+
+        // Прибор не может быть эталоном самому себе
+        if reference.as_deref() == Some(self.inputs[0].content().trim()) {
+            self.inputs[4].set_error(Some(tr("Эталон должен быть другим прибором").to_owned()));
+            return None;
+        }
+
+        Some((reference?, degree.ok()?))
+    }
+
+    /// Проверяет, что в поле ввода с индексом введён серийник прибора датчика, и возвращает его
+    fn parse_serial(&mut self, index: usize) -> Option<String> {
+        let input = &mut self.inputs[index];
+        let serial = input.content().trim().to_owned();
+        match self.serials.contains(&serial) {
+            true => {
+                input.set_error(None);
+                Some(serial)
+            }
+            false => {
+                input.set_error(Some(tr("Нет прибора с таким серийником").to_owned()));
+                None
+            }
+        }
+    }
+
+    /// Переводит ввод на поле с данным индексом
+    fn select(&mut self, index: usize) {
+        self.selected_input_mut().close();
+        // Если сменился серийник, то показываем поправку этого прибора
+        if self.inputs[0].content().trim() != self.loaded_serial {
+            self.load_serial();
+        }
+        self.selection_index = index;
+        self.selected_input_mut().open();
+    }
+}
@@ -0,0 +1,109 @@
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Spans, Text},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::{
+    database::gaps::format_date,
+    locale::{tr, trf},
+    ui::{input::Input, utils::get_popup_area},
+};
+
+use super::state::{CalibrationState, CALIBRATION_FIELDS};
+
+/// Рендерит окно поправок приборов
+pub fn draw_calibration<B: Backend>(
+    frame: &mut Frame<B>,
+    state: &mut CalibrationState,
+    area: Rect,
+) {
+    // Выделяем область под окошко и очищаем её, чтобы не видеть дерево под ним
+    let popup_area = get_popup_area(70, 70, area);
+    frame.render_widget(Clear, popup_area);
+
+    // Делаем блок
+    let title = trf("Поправки: {}/{}", &[&state.sensor, &state.field]);
+    let block = Block::default().title(title).borders(Borders::ALL);
+    let inner_area = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    // Сверху поля поправки, затем сохранённые поправки, подсказка, результат и ошибка
+    let area_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(CALIBRATION_FIELDS.len() as u16 + 1),
+            Constraint::Min(0),
+            Constraint::Length(4),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .split(inner_area);
+
+    // Рендерим поля поправки: подпись слева, поле ввода справа
+    let label_width = CALIBRATION_FIELDS
+        .iter()
+        .map(|label| tr(label).chars().count())
+        .max()
+        .unwrap_or(0) as u16
+        + 2;
+    let fields_area = area_chunks[0];
+    for (i, (label, input_state)) in CALIBRATION_FIELDS.iter().zip(&mut state.inputs).enumerate() {
+        let y = fields_area.y + i as u16;
+        let style = match i == state.selection_index {
+            true => Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD),
+            false => Style::default(),
+        };
+        let paragraph = Paragraph::new(Text::from(tr(label))).style(style);
+        frame.render_widget(paragraph, Rect::new(fields_area.x, y, label_width, 1));
+
+        let input = Input::new().style(Style::default().add_modifier(Modifier::UNDERLINED));
+        let input_width = fields_area.width.saturating_sub(label_width).max(1);
+        let input_area = Rect::new(fields_area.x + label_width, y, input_width, 1);
+        frame.render_stateful_widget(input, input_area, input_state);
+    }
+
+    // Рендерим сохранённые поправки всех приборов поля
+    let lines: Vec<Spans> = match state.calibrations.is_empty() {
+        true => vec![Spans::from(tr("Сохранённых поправок нет"))],
+        false => state
+            .calibrations
+            .iter()
+            .map(|calibration| {
+                let from = calibration.valid_from.map_or("…".to_owned(), format_date);
+                let to = calibration.valid_to.map_or("…".to_owned(), format_date);
+                Spans::from(trf(
+                    "{}: {} (с {} по {})",
+                    &[&calibration.serial, &calibration.formula(), &from, &to],
+                ))
+            })
+            .collect(),
+    };
+    let paragraph = Paragraph::new(lines).block(Block::default().title(tr("Сохранённые поправки")));
+    frame.render_widget(paragraph, area_chunks[1]);
+
+    // Подсказка о том, как работают поправки
+    let text = "x - исходное значение прибора. Пустые даты не ограничивают промежуток, \
+                пустые коэффициенты удаляют поправку. Ctrl+F - подобрать поправку по эталону \
+                (в промежутке поправки), Enter - сохранить поправку, Esc - закрыть окно.";
+    let paragraph = Paragraph::new(Text::from(tr(text)))
+        .style(Style::default().fg(Color::DarkGray))
+        .wrap(Wrap { trim: true });
+    frame.render_widget(paragraph, area_chunks[2]);
+
+    // Рендерим результат последнего действия и ошибку
+    if let Some(status) = &state.status {
+        let paragraph = Paragraph::new(Text::from(status.as_str()));
+        frame.render_widget(paragraph, area_chunks[3]);
+    }
+    if let Some(error) = &state.error {
+        let paragraph =
+            Paragraph::new(Text::from(error.as_str())).style(Style::default().fg(Color::Red));
+        frame.render_widget(paragraph, area_chunks[4]);
+    }
+}
@@ -0,0 +1,400 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+/// Название служебной таблицы, в которой хранятся пороги полей датчиков
+pub const THRESHOLDS_TABLE: &str = "thresholds";
+
+/// Название служебной таблицы, в которой хранятся промежутки превышения порогов
+pub const ALERTS_TABLE: &str = "alerts";
+
+/// Пороги одного поля датчика. Порог учитывается, только если он задан
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Threshold {
+    /// Нижний порог: превышением считается значение меньше него
+    pub min: Option<f64>,
+
+    /// Верхний порог: превышением считается значение больше него
+    pub max: Option<f64>,
+}
+
+/// Перечисляемый тип, определяющий то, какой порог превышен
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlertKind {
+    /// Значение ниже нижнего порога
+    Below,
+    /// Значение выше верхнего порога
+    Above,
+}
+
+/// Промежуток, в котором записи прибора подряд превышают порог поля
+#[derive(Clone, Debug, PartialEq)]
+pub struct Alert {
+    /// Название датчика
+    pub sensor: String,
+
+    /// Название поля датчика
+    pub field: String,
+
+    /// Серийник прибора
+    pub serial: String,
+
+    /// Какой порог превышен
+    pub kind: AlertKind,
+
+    /// Значение превышенного порога
+    pub threshold: f64,
+
+    /// Дата первой записи, превысившей порог
+    pub start: i64,
+
+    /// Дата последней записи, превысившей порог
+    pub end: i64,
+
+    /// Самое далёкое от порога значение в промежутке
+    pub peak: f64,
+}
+
+impl Threshold {
+    /// Возвращает, не задан ли ни один порог
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Возвращает заданные пороги
+    pub fn values(&self) -> Vec<f64> {
+        [self.min, self.max].into_iter().flatten().collect()
+    }
+
+    /// Возвращает, какой порог превышает значение, если превышает
+    fn check(&self, value: f64) -> Option<(AlertKind, f64)> {
+        match (self.min, self.max) {
+            (Some(min), _) if value < min => Some((AlertKind::Below, min)),
+            (_, Some(max)) if value > max => Some((AlertKind::Above, max)),
+            _ => None,
+        }
+    }
+}
+
+impl AlertKind {
+    /// Возвращает код порога для хранения в БД
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Below => "below",
+            Self::Above => "above",
+        }
+    }
+
+    /// Возвращает знак сравнения, которым записывается превышение
+    pub fn sign(&self) -> &'static str {
+        match self {
+            Self::Below => "<",
+            Self::Above => ">",
+        }
+    }
+
+    /// Находит порог по коду из БД
+    fn from_code(code: &str) -> Self {
+        match code {
+            "below" => Self::Below,
+            _ => Self::Above,
+        }
+    }
+}
+
+impl Alert {
+    /// Возвращает длительность превышения в секундах
+    pub fn duration(&self) -> i64 {
+        self.end - self.start
+    }
+}
+
+/// Создаёт таблицы порогов и превышений, если их ещё нет
+pub fn create_alerts_tables(database: &rusqlite::Connection) -> rusqlite::Result<()> {
+    let sql = format!(
+        "CREATE TABLE IF NOT EXISTS {THRESHOLDS_TABLE} \
+         (sensor TEXT, field TEXT, threshold TEXT NOT NULL, PRIMARY KEY (sensor, field))"
+    );
+    database.execute(&sql, ())?;
+
+    let sql = format!(
+        "CREATE TABLE IF NOT EXISTS {ALERTS_TABLE} \
+         (sensor TEXT, field TEXT, serial TEXT, kind TEXT NOT NULL, threshold REAL NOT NULL, \
+         start INTEGER, end INTEGER NOT NULL, peak REAL NOT NULL, \
+         PRIMARY KEY (sensor, field, serial, start))"
+    );
+    database.execute(&sql, ())?;
+
+    Ok(())
+}
+
+/// Загружает пороги поля датчика, если они есть
+pub fn load_threshold(
+    database: &rusqlite::Connection,
+    sensor: &str,
+    field: &str,
+) -> Result<Option<Threshold>, Box<dyn std::error::Error>> {
+    let sql = format!("SELECT threshold FROM {THRESHOLDS_TABLE} WHERE sensor = ?1 AND field = ?2");
+    let mut statement = database.prepare_cached(&sql)?;
+    let mut rows = statement.query((sensor, field))?;
+    match rows.next()? {
+        Some(row) => {
+            let threshold: String = row.get(0)?;
+            Ok(Some(serde_json::from_str(&threshold)?))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Сохраняет пороги поля датчика (пустые удаляются) и заново ищет превышения всех порогов
+pub fn save_threshold(
+    database: &rusqlite::Connection,
+    sensor: &str,
+    field: &str,
+    threshold: &Threshold,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if threshold.is_empty() {
+        let sql = format!("DELETE FROM {THRESHOLDS_TABLE} WHERE sensor = ?1 AND field = ?2");
+        database.execute(&sql, (sensor, field))?;
+    } else {
+        let sql = format!(
+            "INSERT OR REPLACE INTO {THRESHOLDS_TABLE} (sensor, field, threshold) VALUES (?1, ?2, ?3)"
+        );
+        database.execute(&sql, (sensor, field, serde_json::to_string(threshold)?))?;
+    }
+
+    detect_all_alerts(database)?;
+    Ok(())
+}
+
+/// Заново ищет превышения всех порогов (например, после импорта новых данных), заменяя прошлые.
+/// Возвращает новые превышения, которых не было до этого
+pub fn detect_all_alerts(
+    database: &rusqlite::Connection,
+) -> Result<Vec<Alert>, Box<dyn std::error::Error>> {
+    let sql = format!("SELECT sensor, field, threshold FROM {THRESHOLDS_TABLE}");
+    let thresholds: Vec<(String, String, String)> = database
+        .prepare(&sql)?
+        .query_map((), |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+
+    // Превышение, которое продолжилось новыми записями, остаётся тем же (начинается там же)
+    let alerts = load_alerts(database)?;
+    let known: HashSet<_> = alerts
+        .iter()
+        .map(|alert| (&alert.sensor, &alert.field, &alert.serial, alert.start))
+        .collect();
+
+    // Все превышения заменяются разом, поэтому делаем это в одной транзакции
+    let transaction = database.unchecked_transaction()?;
+    transaction.execute(&format!("DELETE FROM {ALERTS_TABLE}"), ())?;
+    let sql = format!(
+        "INSERT INTO {ALERTS_TABLE} (sensor, field, serial, kind, threshold, start, end, peak) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)"
+    );
+    let mut statement = transaction.prepare(&sql)?;
+    let mut new_alerts = vec![];
+    for (sensor, field, threshold) in thresholds {
+        let threshold: Threshold = serde_json::from_str(&threshold)?;
+        for alert in find_alerts(&transaction, &sensor, &field, &threshold)? {
+            statement.execute((
+                &alert.sensor,
+                &alert.field,
+                &alert.serial,
+                alert.kind.code(),
+                alert.threshold,
+                alert.start,
+                alert.end,
+                alert.peak,
+            ))?;
+
+            let key = (&alert.sensor, &alert.field, &alert.serial, alert.start);
+            if !known.contains(&key) {
+                new_alerts.push(alert);
+            }
+        }
+    }
+    drop(statement);
+
+    transaction.commit()?;
+    Ok(new_alerts)
+}
+
+/// Загружает все найденные превышения, отсортированные по датчику, полю, серийнику и дате
+pub fn load_alerts(database: &rusqlite::Connection) -> rusqlite::Result<Vec<Alert>> {
+    let sql = format!(
+        "SELECT sensor, field, serial, kind, threshold, start, end, peak FROM {ALERTS_TABLE} \
+         ORDER BY sensor, field, serial, start"
+    );
+    let mut statement = database.prepare(&sql)?;
+    let alerts = statement
+        .query_map((), |row| {
+            let kind: String = row.get(3)?;
+            Ok(Alert {
+                sensor: row.get(0)?,
+                field: row.get(1)?,
+                serial: row.get(2)?,
+                kind: AlertKind::from_code(&kind),
+                threshold: row.get(4)?,
+                start: row.get(5)?,
+                end: row.get(6)?,
+                peak: row.get(7)?,
+            })
+        })?
+        .collect();
+    alerts
+}
+
+/// Проверяет, продолжается ли превышение: последняя запись прибора с этим полем тоже превышает порог
+pub fn is_alert_open(database: &rusqlite::Connection, alert: &Alert) -> rusqlite::Result<bool> {
+    let Alert {
+        sensor,
+        field,
+        serial,
+        ..
+    } = alert;
+    let sql = format!("SELECT MAX(date) FROM {sensor} WHERE serial = ?1 AND {field} IS NOT NULL");
+    let last: Option<i64> = database.query_row(&sql, [serial], |row| row.get(0))?;
+    Ok(last == Some(alert.end))
+}
+
+/// Находит промежутки, в которых записи каждого прибора подряд превышают один и тот же порог поля.
+/// Сравниваются исходные значения полей, без поправок приборов
+fn find_alerts(
+    database: &rusqlite::Connection,
+    sensor: &str,
+    field: &str,
+    threshold: &Threshold,
+) -> rusqlite::Result<Vec<Alert>> {
+    let sql = format!(
+        "SELECT serial, date, {field} FROM {sensor} WHERE {field} IS NOT NULL ORDER BY serial, date"
+    );
+    let mut statement = database.prepare(&sql)?;
+    let mut rows = statement.query(())?;
+
+    let mut alerts: Vec<Alert> = vec![];
+    // Превышение продолжается, пока следующая запись того же прибора превышает тот же порог
+    let mut is_open = false;
+    while let Some(row) = rows.next()? {
+        let (serial, date, value): (String, i64, f64) = (row.get(0)?, row.get(1)?, row.get(2)?);
+        let Some((kind, limit)) = threshold.check(value) else {
+            is_open = false;
+            continue;
+        };
+
+        match alerts.last_mut() {
+            Some(alert) if is_open && alert.serial == serial && alert.kind == kind => {
+                alert.end = date;
+                alert.peak = match kind {
+                    AlertKind::Below => alert.peak.min(value),
+                    AlertKind::Above => alert.peak.max(value),
+                };
+            }
+            _ => alerts.push(Alert {
+                sensor: sensor.to_owned(),
+                field: field.to_owned(),
+                serial,
+                kind,
+                threshold: limit,
+                start: date,
+                end: date,
+                peak: value,
+            }),
+        }
+        is_open = true;
+    }
+
+    Ok(alerts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Создаёт БД в памяти с таблицей датчика из записей (серийник, дата, значение)
+    fn database(rows: &[(&str, i64, Option<f64>)]) -> rusqlite::Connection {
+        let database = rusqlite::Connection::open_in_memory().unwrap();
+        database
+            .execute("CREATE TABLE meteo (serial TEXT, date INTEGER, t REAL)", ())
+            .unwrap();
+        for row in rows {
+            database
+                .execute("INSERT INTO meteo VALUES (?1, ?2, ?3)", *row)
+                .unwrap();
+        }
+        database
+    }
+
+    /// Находит превышения порогов поля и возвращает их (серийник, порог, начало, конец, пик)
+    fn alerts(
+        database: &rusqlite::Connection,
+        min: Option<f64>,
+        max: Option<f64>,
+    ) -> Vec<(String, AlertKind, i64, i64, f64)> {
+        find_alerts(database, "meteo", "t", &Threshold { min, max })
+            .unwrap()
+            .into_iter()
+            .map(|alert| (alert.serial, alert.kind, alert.start, alert.end, alert.peak))
+            .collect()
+    }
+
+    #[test]
+    fn merges_consecutive_records_and_keeps_peak() {
+        let database = database(&[
+            ("a", 0, Some(5.)),
+            ("a", 60, Some(12.)),
+            ("a", 120, Some(15.)),
+            ("a", 180, None),
+            ("a", 240, Some(11.)),
+            ("a", 300, Some(5.)),
+            ("a", 360, Some(-3.)),
+            ("a", 420, Some(-1.)),
+        ]);
+
+        assert_eq!(
+            alerts(&database, Some(0.), Some(10.)),
+            [
+                ("a".to_owned(), AlertKind::Above, 60, 240, 15.),
+                ("a".to_owned(), AlertKind::Below, 360, 420, -3.),
+            ]
+        );
+    }
+
+    #[test]
+    fn splits_runs_when_threshold_changes() {
+        // Скачок из-под нижнего порога сразу выше верхнего — это два разных превышения
+        let database = database(&[
+            ("a", 0, Some(-1.)),
+            ("a", 60, Some(-4.)),
+            ("a", 120, Some(20.)),
+            ("a", 180, Some(30.)),
+        ]);
+
+        assert_eq!(
+            alerts(&database, Some(0.), Some(10.)),
+            [
+                ("a".to_owned(), AlertKind::Below, 0, 60, -4.),
+                ("a".to_owned(), AlertKind::Above, 120, 180, 30.),
+            ]
+        );
+    }
+
+    #[test]
+    fn ends_runs_when_serial_changes() {
+        let database = database(&[
+            ("a", 0, Some(5.)),
+            ("a", 60, Some(20.)),
+            ("b", 0, Some(30.)),
+            ("b", 60, Some(25.)),
+            ("b", 120, Some(5.)),
+        ]);
+
+        assert_eq!(
+            alerts(&database, None, Some(10.)),
+            [
+                ("a".to_owned(), AlertKind::Above, 60, 60, 20.),
+                ("b".to_owned(), AlertKind::Above, 0, 60, 30.),
+            ]
+        );
+    }
+}
@@ -0,0 +1,100 @@
+/// Название служебной таблицы, в которой хранятся метки событий
+pub const ANNOTATIONS_TABLE: &str = "annotations";
+
+/// Метка события на шкале времени (например, "прибор почищен" или "перенесён на крышу")
+#[derive(Clone, Debug, Default)]
+pub struct Annotation {
+    /// Номер метки в БД (`None`, если метка ещё не сохранена)
+    pub id: Option<i64>,
+
+    /// Дата события или начала промежутка
+    pub start: i64,
+
+    /// Дата конца промежутка (`None`, если событие произошло в один момент)
+    pub end: Option<i64>,
+
+    /// Серийник прибора, к которому относится метка (`None`, если ко всем)
+    pub serial: Option<String>,
+
+    /// Текст метки
+    pub text: String,
+}
+
+impl Annotation {
+    /// Возвращает дату конца метки (у метки-момента совпадает с началом)
+    pub fn end_or_start(&self) -> i64 {
+        self.end.unwrap_or(self.start)
+    }
+
+    /// Возвращает текст метки вместе с серийником прибора, если он есть
+    pub fn label(&self) -> String {
+        match &self.serial {
+            Some(serial) => format!("{} ({serial})", self.text),
+            None => self.text.clone(),
+        }
+    }
+}
+
+/// Создаёт таблицу меток, если её ещё нет
+pub fn create_annotations_table(database: &rusqlite::Connection) -> rusqlite::Result<()> {
+    let sql = format!(
+        "CREATE TABLE IF NOT EXISTS {ANNOTATIONS_TABLE} \
+         (id INTEGER PRIMARY KEY, start INTEGER NOT NULL, end INTEGER, serial TEXT, text TEXT NOT NULL)"
+    );
+    database.execute(&sql, ())?;
+    Ok(())
+}
+
+/// Загружает метки, которые попадают в промежуток дат (или все, если он не задан), отсортированные по дате
+pub fn load_annotations(
+    database: &rusqlite::Connection,
+    range: Option<(i64, i64)>,
+) -> rusqlite::Result<Vec<Annotation>> {
+    let (min, max) = range.unwrap_or((i64::MIN, i64::MAX));
+    let sql = format!(
+        "SELECT id, start, end, serial, text FROM {ANNOTATIONS_TABLE} \
+         WHERE start <= ?2 AND COALESCE(end, start) >= ?1 ORDER BY start, id"
+    );
+    let mut statement = database.prepare_cached(&sql)?;
+    let annotations = statement
+        .query_map((min, max), |row| {
+            Ok(Annotation {
+                id: row.get(0)?,
+                start: row.get(1)?,
+                end: row.get(2)?,
+                serial: row.get(3)?,
+                text: row.get(4)?,
+            })
+        })?
+        .collect();
+    annotations
+}
+
+/// Сохраняет метку (новую добавляет, а сохранённую заменяет). Возвращает номер метки
+pub fn save_annotation(
+    database: &rusqlite::Connection,
+    annotation: &Annotation,
+) -> rusqlite::Result<i64> {
+    let sql = format!(
+        "INSERT OR REPLACE INTO {ANNOTATIONS_TABLE} (id, start, end, serial, text) \
+         VALUES (?1, ?2, ?3, ?4, ?5)"
+    );
+    database.execute(
+        &sql,
+        (
+            annotation.id,
+            annotation.start,
+            annotation.end,
+            &annotation.serial,
+            &annotation.text,
+        ),
+    )?;
+    Ok(database.last_insert_rowid())
+}
+
+/// Удаляет метку по её номеру
+pub fn delete_annotation(database: &rusqlite::Connection, id: i64) -> rusqlite::Result<()> {
+    let sql = format!("DELETE FROM {ANNOTATIONS_TABLE} WHERE id = ?1");
+    database.execute(&sql, (id,))?;
+    Ok(())
+}
@@ -0,0 +1,209 @@
+use itertools::Itertools;
+use rusqlite::OptionalExtension;
+
+use crate::{
+    calibration::correction::{fit_polynomial, Calibration, CalibrationFit},
+    database::gaps::format_date,
+    graph::join::DEFAULT_JOIN_SECONDS,
+    locale::trf,
+};
+
+/// Название служебной таблицы, в которой хранятся поправки приборов
+pub const CALIBRATIONS_TABLE: &str = "calibrations";
+
+/// Создаёт таблицу поправок, если её ещё нет
+pub fn create_calibrations_table(database: &rusqlite::Connection) -> rusqlite::Result<()> {
+    // В старой таблице у прибора могла быть только одна поправка, поэтому переносим её поправки в новую
+    let sql = format!(
+        "SELECT COUNT(*) = 0 AND EXISTS (SELECT 1 FROM pragma_table_info('{CALIBRATIONS_TABLE}')) \
+         FROM pragma_table_info('{CALIBRATIONS_TABLE}') WHERE name = 'id'"
+    );
+    let outdated: bool = database.query_row(&sql, (), |row| row.get(0))?;
+    if outdated {
+        let sql = format!("ALTER TABLE {CALIBRATIONS_TABLE} RENAME TO {CALIBRATIONS_TABLE}_old");
+        database.execute(&sql, ())?;
+    }
+
+    // Коэффициенты полинома хранятся в JSON, чтобы степень могла быть любой. У прибора может быть
+    // несколько поправок с непересекающимися промежутками, которые различаются датой начала
+    let sql = format!(
+        "CREATE TABLE IF NOT EXISTS {CALIBRATIONS_TABLE} \
+         (id INTEGER PRIMARY KEY, sensor TEXT NOT NULL, field TEXT NOT NULL, serial TEXT NOT NULL, \
+         coefficients TEXT NOT NULL, valid_from INTEGER, valid_to INTEGER)"
+    );
+    database.execute(&sql, ())?;
+    let sql = format!(
+        "CREATE INDEX IF NOT EXISTS {CALIBRATIONS_TABLE}_serial \
+         ON {CALIBRATIONS_TABLE} (sensor, field, serial, valid_from)"
+    );
+    database.execute(&sql, ())?;
+
+    if outdated {
+        let sql = format!(
+            "INSERT INTO {CALIBRATIONS_TABLE} \
+             (sensor, field, serial, coefficients, valid_from, valid_to) \
+             SELECT sensor, field, serial, coefficients, valid_from, valid_to FROM {CALIBRATIONS_TABLE}_old; \
+             DROP TABLE {CALIBRATIONS_TABLE}_old;"
+        );
+        database.execute_batch(&sql)?;
+    }
+
+    Ok(())
+}
+
+/// Загружает поправки всех приборов поля датчика, отсортированные по серийнику и дате начала
+pub fn load_calibrations(
+    database: &rusqlite::Connection,
+    sensor: &str,
+    field: &str,
+) -> Result<Vec<Calibration>, Box<dyn std::error::Error>> {
+    let sql = format!(
+        "SELECT serial, coefficients, valid_from, valid_to FROM {CALIBRATIONS_TABLE} \
+         WHERE sensor = ?1 AND field = ?2 ORDER BY serial, valid_from"
+    );
+    let mut statement = database.prepare_cached(&sql)?;
+    let rows: Vec<(String, String, Option<i64>, Option<i64>)> = statement
+        .query_map((sensor, field), |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+
+    let mut calibrations = vec![];
+    for (serial, coefficients, valid_from, valid_to) in rows {
+        calibrations.push(Calibration {
+            serial,
+            coefficients: serde_json::from_str(&coefficients)?,
+            valid_from,
+            valid_to,
+        });
+    }
+    Ok(calibrations)
+}
+
+/// Сохраняет поправку прибора, заменяя его поправку с той же датой начала. Поправка без коэффициентов
+/// удаляется. Поправка, промежуток которой пересекается с другой поправкой прибора, не сохраняется
+pub fn save_calibration(
+    database: &rusqlite::Connection,
+    sensor: &str,
+    field: &str,
+    calibration: &Calibration,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let Calibration {
+        serial,
+        valid_from,
+        valid_to,
+        ..
+    } = calibration;
+
+    let sql = format!(
+        "DELETE FROM {CALIBRATIONS_TABLE} \
+         WHERE sensor = ?1 AND field = ?2 AND serial = ?3 AND valid_from IS ?4"
+    );
+    if calibration.coefficients.is_empty() {
+        database.execute(&sql, (sensor, field, serial, valid_from))?;
+        return Ok(());
+    }
+
+    // Промежутки [с, по] пересекаются, если каждый начинается не позже конца другого
+    let overlap_sql = format!(
+        "SELECT valid_from, valid_to FROM {CALIBRATIONS_TABLE} \
+         WHERE sensor = ?1 AND field = ?2 AND serial = ?3 AND valid_from IS NOT ?4 \
+         AND (valid_from IS NULL OR ?5 IS NULL OR valid_from <= ?5) \
+         AND (valid_to IS NULL OR ?4 IS NULL OR valid_to >= ?4) LIMIT 1"
+    );
+    let overlap: Option<(Option<i64>, Option<i64>)> = database
+        .query_row(
+            &overlap_sql,
+            (sensor, field, serial, valid_from, valid_to),
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    if let Some((from, to)) = overlap {
+        let from = from.map_or("…".to_owned(), format_date);
+        let to = to.map_or("…".to_owned(), format_date);
+        return Err(trf(
+            "Промежуток пересекается с поправкой прибора с {} по {}",
+            &[&from, &to],
+        )
+        .into());
+    }
+
+    // Заменяем поправку с той же датой начала в одной транзакции
+    let transaction = database.unchecked_transaction()?;
+    transaction.execute(&sql, (sensor, field, serial, valid_from))?;
+    let sql = format!(
+        "INSERT INTO {CALIBRATIONS_TABLE} \
+         (sensor, field, serial, coefficients, valid_from, valid_to) VALUES (?1, ?2, ?3, ?4, ?5, ?6)"
+    );
+    let coefficients = serde_json::to_string(&calibration.coefficients)?;
+    transaction.execute(
+        &sql,
+        (sensor, field, serial, coefficients, valid_from, valid_to),
+    )?;
+    transaction.commit()?;
+
+    Ok(())
+}
+
+/// Подбирает поправку прибора по эталонному прибору того же датчика в промежутке дат поправки.
+/// Записи приборов совмещаются по сетке времени с шагом `DEFAULT_JOIN_SECONDS`
+pub fn fit_calibration(
+    database: &rusqlite::Connection,
+    sensor: &str,
+    field: &str,
+    calibration: &Calibration,
+    reference: &str,
+    degree: usize,
+) -> Result<Option<CalibrationFit>, Box<dyn std::error::Error>> {
+    // Средние значения каждого прибора на общей сетке времени в промежутке, в котором действует поправка
+    let grid = |serial: &str| {
+        format!(
+            "(SELECT date - date % {DEFAULT_JOIN_SECONDS} AS date, AVG({field}) AS value \
+             FROM {sensor} WHERE serial = {serial} AND {field} IS NOT NULL \
+             AND (?3 IS NULL OR date >= ?3) AND (?4 IS NULL OR date <= ?4) GROUP BY 1)"
+        )
+    };
+    let sql = format!(
+        "SELECT d.value, r.value FROM {} AS d JOIN {} AS r USING (date)",
+        grid("?1"),
+        grid("?2"),
+    );
+    let params = (
+        &calibration.serial,
+        reference,
+        calibration.valid_from,
+        calibration.valid_to,
+    );
+    let points: Vec<(f64, f64)> = database
+        .prepare(&sql)?
+        .query_map(params, |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+
+    Ok(fit_polynomial(&points, degree))
+}
+
+/// Возвращает подзапрос с записями датчика, в котором к полям `fields` применены поправки приборов.
+/// `columns` - все столбцы таблицы датчика
+pub fn corrected_source(sensor: &str, columns: &[String], fields: &[&str]) -> String {
+    // Коэффициенты разбираем из JSON один раз на весь запрос: каждая строка - член полинома a·x^power
+    let terms = format!(
+        "calibration_terms AS MATERIALIZED (\
+         SELECT c.field, c.serial, c.valid_from, c.valid_to, t.key AS power, t.value AS coefficient \
+         FROM {CALIBRATIONS_TABLE} AS c, json_each(c.coefficients) AS t WHERE c.sensor = '{sensor}')"
+    );
+    let columns = columns
+        .iter()
+        .map(|column| match fields.contains(&column.as_str()) {
+            // Промежутки поправок прибора не пересекаются, поэтому на дату записи действует не больше
+            // одной из них. Если у записи нет действующей поправки, то оставляем исходное значение
+            true => format!(
+                "COALESCE((SELECT SUM(t.coefficient * pow(s.{column}, t.power)) \
+                 FROM calibration_terms AS t WHERE t.field = '{column}' AND t.serial = s.serial \
+                 AND (t.valid_from IS NULL OR s.date >= t.valid_from) \
+                 AND (t.valid_to IS NULL OR s.date <= t.valid_to)), s.{column}) AS {column}"
+            ),
+            false => format!("s.{column}"),
+        })
+        .join(", ");
+    format!("(WITH {terms} SELECT {columns} FROM {sensor} AS s)")
+}
@@ -0,0 +1,110 @@
+use rusqlite::functions::{Aggregate, Context, FunctionFlags};
+
+use crate::graph::derived::DERIVED_FIELDS;
+
+/// Добавляет в соединение с базой данных функции, которых нет в SQLite
+pub fn register_functions(database: &rusqlite::Connection) -> rusqlite::Result<()> {
+    // Функции детерминированные, поэтому SQLite может оптимизировать их вызовы
+    let flags = || FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC;
+    database.create_aggregate_function("median", 1, flags(), Median)?;
+    database.create_aggregate_function("stddev", 1, flags(), StdDev)?;
+
+    // Производные величины считаются в SQL, чтобы их можно было группировать и прореживать
+    for field in &DERIVED_FIELDS {
+        let (inputs, formula) = (field.inputs.len(), field.formula);
+        database.create_scalar_function(field.function, inputs as i32, flags(), move |ctx| {
+            // Если хотя бы одно из значений отсутствует, то и величину посчитать нельзя
+            let values = (0..inputs)
+                .map(|i| ctx.get::<Option<f64>>(i))
+                .collect::<rusqlite::Result<Option<Vec<_>>>>()?;
+            Ok(values.map(|values| formula(&values)))
+        })?;
+    }
+
+    // Степень числа нужна для поправок приборов, а математические функции SQLite в сборку не входят
+    database.create_scalar_function("pow", 2, flags(), |ctx| {
+        let (Some(x), Some(power)) = (ctx.get::<Option<f64>>(0)?, ctx.get::<Option<f64>>(1)?)
+        else {
+            return Ok(None);
+        };
+        Ok(Some(x.powf(power)))
+    })?;
+
+    Ok(())
+}
+
+/// Агрегатная функция медианы
+struct Median;
+
+impl Aggregate<Vec<f64>, Option<f64>> for Median {
+    fn init(&self, _: &mut Context<'_>) -> rusqlite::Result<Vec<f64>> {
+        Ok(vec![])
+    }
+
+    fn step(&self, ctx: &mut Context<'_>, values: &mut Vec<f64>) -> rusqlite::Result<()> {
+        // Значения NULL пропускаем, как и встроенные агрегатные функции
+        if let Some(value) = ctx.get::<Option<f64>>(0)? {
+            values.push(value);
+        }
+
+        Ok(())
+    }
+
+    fn finalize(
+        &self,
+        _: &mut Context<'_>,
+        values: Option<Vec<f64>>,
+    ) -> rusqlite::Result<Option<f64>> {
+        let mut values = match values {
+            Some(values) if !values.is_empty() => values,
+            _ => return Ok(None),
+        };
+
+        // Сортируем значения и берём среднее (или среднее двух средних)
+        values.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+        let middle = values.len() / 2;
+        let median = if values.len() % 2 == 0 {
+            (values[middle - 1] + values[middle]) / 2.
+        } else {
+            values[middle]
+        };
+
+        Ok(Some(median))
+    }
+}
+
+/// Агрегатная функция стандартного отклонения (по выборке)
+struct StdDev;
+
+impl Aggregate<(usize, f64, f64), Option<f64>> for StdDev {
+    fn init(&self, _: &mut Context<'_>) -> rusqlite::Result<(usize, f64, f64)> {
+        Ok((0, 0., 0.))
+    }
+
+    fn step(
+        &self,
+        ctx: &mut Context<'_>,
+        (count, mean, m2): &mut (usize, f64, f64),
+    ) -> rusqlite::Result<()> {
+        // Считаем по алгоритму Уэлфорда, чтобы не терять точность
+        if let Some(value) = ctx.get::<Option<f64>>(0)? {
+            *count += 1;
+            let delta = value - *mean;
+            *mean += delta / *count as f64;
+            *m2 += delta * (value - *mean);
+        }
+
+        Ok(())
+    }
+
+    fn finalize(
+        &self,
+        _: &mut Context<'_>,
+        state: Option<(usize, f64, f64)>,
+    ) -> rusqlite::Result<Option<f64>> {
+        match state {
+            Some((count, _, m2)) if count > 1 => Ok(Some((m2 / (count - 1) as f64).sqrt())),
+            _ => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use chrono::NaiveDateTime;
+use itertools::Itertools;
+
+use crate::locale::trf;
+
+/// Во сколько раз промежуток между записями должен превышать обычный (медианный), чтобы считаться пропуском
+pub const GAP_FACTOR: f64 = 3.;
+
+/// Пропуск в данных одного прибора: промежуток между соседними записями, намного больше обычного
+#[derive(Clone, Debug)]
+pub struct DataGap {
+    /// Название датчика
+    pub sensor: String,
+
+    /// Серийник прибора
+    pub serial: String,
+
+    /// Дата последней записи перед пропуском
+    pub start: i64,
+
+    /// Дата первой записи после пропуска
+    pub end: i64,
+
+    /// Обычный (медианный) промежуток между записями прибора в секундах
+    pub period: f64,
+}
+
+impl DataGap {
+    /// Возвращает длительность пропуска в секундах
+    pub fn duration(&self) -> i64 {
+        self.end - self.start
+    }
+}
+
+/// Находит пропуски (начало, конец) в датах источника `source` (таблицы или подзапроса с полями `date` и `serial`).
+/// Пропуски ищутся у каждого прибора отдельно, а возвращаются вместе по порядку их окончания
+pub fn find_gaps(
+    database: &rusqlite::Connection,
+    source: &str,
+) -> rusqlite::Result<Vec<(i64, i64)>> {
+    let gaps = query_gaps(database, source, "serial")?
+        .into_iter()
+        .map(|(_, start, end, _)| (start, end))
+        .sorted_by_key(|&(_, end)| end)
+        .collect();
+    Ok(gaps)
+}
+
+/// Находит пропуски каждого прибора у датчиков `sensors`
+pub fn find_sensors_gaps(
+    database: &rusqlite::Connection,
+    sensors: &[String],
+) -> rusqlite::Result<Vec<DataGap>> {
+    let mut gaps = vec![];
+    for sensor in sensors {
+        // Обычный промежуток у каждого прибора свой, поэтому считаем их по отдельности
+        let sensor_gaps = query_gaps(database, sensor, "serial")?;
+        gaps.extend(
+            sensor_gaps
+                .into_iter()
+                .map(|(serial, start, end, period)| DataGap {
+                    sensor: sensor.clone(),
+                    serial,
+                    start,
+                    end,
+                    period,
+                }),
+        );
+    }
+
+    Ok(gaps)
+}
+
+/// Форматирует дату пропуска для отображения
+pub fn format_date(date: i64) -> String {
+    match NaiveDateTime::from_timestamp_opt(date, 0) {
+        Some(date) => date.format("%Y-%m-%d %H:%M:%S").to_string(),
+        None => date.to_string(),
+    }
+}
+
+/// Форматирует длительность в секундах в вид "3 д 04:05:06"
+pub fn format_duration(seconds: i64) -> String {
+    let (days, seconds) = (seconds / 86400, seconds % 86400);
+    let time = format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    );
+    match days {
+        0 => time,
+        days => trf("{} д {}", &[&days, &time]),
+    }
+}
+
+/// Находит пропуски в источнике `source`, считая обычный промежуток отдельно для каждого значения `key`.
+/// Возвращает (ключ, начало, конец, обычный промежуток)
+fn query_gaps(
+    database: &rusqlite::Connection,
+    source: &str,
+    key: &str,
+) -> rusqlite::Result<Vec<(String, i64, i64, f64)>> {
+    // Промежутки между соседними записями считаем оконной функцией, а обычный промежуток - медианой
+    let sql = format!(
+        "WITH steps AS (SELECT {key} AS key, LAG(date) OVER (PARTITION BY {key} ORDER BY date) AS gap_start, \
+         date AS gap_end FROM {source}), \
+         periods AS (SELECT key, median(gap_end - gap_start) AS period FROM steps GROUP BY key) \
+         SELECT key, gap_start, gap_end, period FROM steps JOIN periods USING (key) \
+         WHERE gap_end - gap_start > {GAP_FACTOR} * period ORDER BY key, gap_start"
+    );
+    let mut statement = database.prepare(&sql)?;
+    let gaps = statement
+        .query_map((), |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })?
+        .collect();
+    gaps
+}
@@ -1,7 +1,49 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, path::PathBuf};
 
+use rusqlite::OpenFlags;
+
+pub mod alerts;
+pub mod annotations;
+pub mod calibration;
+pub mod functions;
+pub mod gaps;
 pub mod import;
+pub mod outliers;
 pub mod sensors;
+pub mod session;
+pub mod views;
+
+/// Служебные таблицы, которые не являются таблицами датчиков
+pub const SERVICE_TABLES: [&str; 8] = [
+    views::VIEWS_TABLE,
+    session::SESSION_TABLE,
+    outliers::OUTLIER_RULES_TABLE,
+    outliers::OUTLIER_FLAGS_TABLE,
+    calibration::CALIBRATIONS_TABLE,
+    alerts::THRESHOLDS_TABLE,
+    alerts::ALERTS_TABLE,
+    annotations::ANNOTATIONS_TABLE,
+];
+
+/// Открывает ещё одно соединение с той же базой данных только для чтения, чтобы долгие запросы
+/// в отдельном потоке не держали основное соединение
+pub fn open_read_connection(
+    database: &rusqlite::Connection,
+) -> rusqlite::Result<rusqlite::Connection> {
+    // База данных в памяти не видна из другого соединения
+    let path = match database.path() {
+        Some(path) if !path.is_empty() => path.to_owned(),
+        _ => return Err(rusqlite::Error::InvalidPath(PathBuf::new())),
+    };
+
+    let flags = OpenFlags::SQLITE_OPEN_READ_ONLY
+        | OpenFlags::SQLITE_OPEN_URI
+        | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+    let connection = rusqlite::Connection::open_with_flags(path, flags)?;
+    // Функции добавляются в каждое соединение отдельно
+    functions::register_functions(&connection)?;
+    Ok(connection)
+}
 
 /// HashMap, хранящий все поля отдельных датчиков
 /// Используется для того, чтобы можно было удобно импортировать данные в БД
@@ -0,0 +1,244 @@
+use itertools::Itertools;
+
+use crate::outliers::rule::OutlierRule;
+
+/// Название служебной таблицы, в которой хранятся правила поиска выбросов
+pub const OUTLIER_RULES_TABLE: &str = "outlier_rules";
+
+/// Название служебной таблицы, в которой хранятся помеченные выбросами записи
+pub const OUTLIER_FLAGS_TABLE: &str = "outlier_flags";
+
+/// Создаёт таблицы правил и пометок выбросов, если их ещё нет
+pub fn create_outliers_tables(database: &rusqlite::Connection) -> rusqlite::Result<()> {
+    let sql = format!(
+        "CREATE TABLE IF NOT EXISTS {OUTLIER_RULES_TABLE} \
+         (sensor TEXT, field TEXT, rule TEXT NOT NULL, PRIMARY KEY (sensor, field))"
+    );
+    database.execute(&sql, ())?;
+
+    // Записи датчиков не удаляются, а только помечаются по дате и серийнику
+    let sql = format!(
+        "CREATE TABLE IF NOT EXISTS {OUTLIER_FLAGS_TABLE} \
+         (sensor TEXT, field TEXT, serial TEXT, date INTEGER, reason TEXT NOT NULL, \
+         PRIMARY KEY (sensor, field, serial, date))"
+    );
+    database.execute(&sql, ())?;
+
+    Ok(())
+}
+
+/// Загружает правило поиска выбросов поля датчика, если оно есть
+pub fn load_rule(
+    database: &rusqlite::Connection,
+    sensor: &str,
+    field: &str,
+) -> Result<Option<OutlierRule>, Box<dyn std::error::Error>> {
+    let sql = format!("SELECT rule FROM {OUTLIER_RULES_TABLE} WHERE sensor = ?1 AND field = ?2");
+    let mut statement = database.prepare_cached(&sql)?;
+    let mut rows = statement.query((sensor, field))?;
+    match rows.next()? {
+        Some(row) => {
+            let rule: String = row.get(0)?;
+            Ok(Some(serde_json::from_str(&rule)?))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Сохраняет правило поиска выбросов поля датчика и заново помечает его выбросы.
+/// Возвращает количество помеченных записей по каждой причине (в порядке `OutlierReason::ALL`)
+pub fn save_rule(
+    database: &rusqlite::Connection,
+    sensor: &str,
+    field: &str,
+    rule: &OutlierRule,
+) -> Result<[usize; 3], Box<dyn std::error::Error>> {
+    // Пустое правило означает, что выбросы у поля больше не ищутся
+    if rule.is_empty() {
+        let sql = format!("DELETE FROM {OUTLIER_RULES_TABLE} WHERE sensor = ?1 AND field = ?2");
+        database.execute(&sql, (sensor, field))?;
+    } else {
+        let sql = format!(
+            "INSERT OR REPLACE INTO {OUTLIER_RULES_TABLE} (sensor, field, rule) VALUES (?1, ?2, ?3)"
+        );
+        database.execute(&sql, (sensor, field, serde_json::to_string(rule)?))?;
+    }
+
+    Ok(detect_outliers(database, sensor, field, rule)?)
+}
+
+/// Заново помечает выбросы всех полей, у которых есть правила (например, после импорта новых данных)
+pub fn detect_all_outliers(
+    database: &rusqlite::Connection,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let sql = format!("SELECT sensor, field, rule FROM {OUTLIER_RULES_TABLE}");
+    let rules: Vec<(String, String, String)> = database
+        .prepare(&sql)?
+        .query_map((), |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+
+    for (sensor, field, rule) in rules {
+        let rule: OutlierRule = serde_json::from_str(&rule)?;
+        detect_outliers(database, &sensor, &field, &rule)?;
+    }
+
+    Ok(())
+}
+
+/// Помечает выбросы поля датчика по правилу, заменяя прошлые пометки.
+/// Возвращает количество помеченных записей по каждой причине
+fn detect_outliers(
+    database: &rusqlite::Connection,
+    sensor: &str,
+    field: &str,
+    rule: &OutlierRule,
+) -> rusqlite::Result<[usize; 3]> {
+    // Все пометки поля заменяются разом, поэтому делаем это в одной транзакции
+    let transaction = database.unchecked_transaction()?;
+    let sql = format!("DELETE FROM {OUTLIER_FLAGS_TABLE} WHERE sensor = ?1 AND field = ?2");
+    transaction.execute(&sql, (sensor, field))?;
+
+    // Получаем значения поля каждого прибора по порядку
+    let sql = format!(
+        "SELECT serial, date, {field} FROM {sensor} WHERE {field} IS NOT NULL ORDER BY serial, date"
+    );
+    let rows: Vec<(String, i64, f64)> = transaction
+        .prepare(&sql)?
+        .query_map((), |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+
+    // Ищем выбросы у каждого прибора отдельно и помечаем их
+    let sql = format!(
+        "INSERT INTO {OUTLIER_FLAGS_TABLE} (sensor, field, serial, date, reason) VALUES (?1, ?2, ?3, ?4, ?5)"
+    );
+    let mut statement = transaction.prepare(&sql)?;
+    let mut counts = [0; 3];
+    for (serial, rows) in &rows.iter().group_by(|(serial, _, _)| serial) {
+        let points: Vec<_> = rows.map(|&(_, date, value)| (date, value)).collect();
+        for (i, reason) in rule.find(&points) {
+            statement.execute((sensor, field, serial, points[i].0, reason.code()))?;
+            counts[reason as usize] += 1;
+        }
+    }
+    drop(statement);
+
+    transaction.commit()?;
+    Ok(counts)
+}
+
+/// Возвращает условие SQL о том, что запись `alias` датчика помечена выбросом хотя бы в одном из полей
+fn flagged_condition(sensor: &str, fields: &[&str], alias: &str) -> String {
+    let fields = fields.iter().map(|field| format!("'{field}'")).join(", ");
+    format!(
+        "EXISTS (SELECT 1 FROM {OUTLIER_FLAGS_TABLE} AS f WHERE f.sensor = '{sensor}' \
+         AND f.field IN ({fields}) AND f.serial = {alias}.serial AND f.date = {alias}.date)"
+    )
+}
+
+/// Возвращает подзапрос с записями источника `source` датчика без выбросов в полях `fields`
+pub fn excluding_source(sensor: &str, source: &str, fields: &[&str]) -> String {
+    let condition = flagged_condition(sensor, fields, "s");
+    format!("(SELECT * FROM {source} AS s WHERE NOT {condition})")
+}
+
+/// Возвращает подзапрос (date, serial, value) только с помеченными записями источника `source` датчика,
+/// где `value` - значение Y, посчитанное по полям `fields`
+pub fn flagged_source(sensor: &str, source: &str, fields: &[&str], value: &str) -> String {
+    let condition = flagged_condition(sensor, fields, "s");
+    format!(
+        "(SELECT date, serial, {value} AS value FROM {source} AS s \
+         WHERE {condition} AND {value} IS NOT NULL)"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Создаёт БД в памяти с таблицей датчика из записей (серийник, дата, значение)
+    fn database(rows: &[(&str, i64, Option<f64>)]) -> rusqlite::Connection {
+        let database = rusqlite::Connection::open_in_memory().unwrap();
+        create_outliers_tables(&database).unwrap();
+        database
+            .execute("CREATE TABLE meteo (serial TEXT, date INTEGER, t REAL)", ())
+            .unwrap();
+        for row in rows {
+            database
+                .execute("INSERT INTO meteo VALUES (?1, ?2, ?3)", *row)
+                .unwrap();
+        }
+        database
+    }
+
+    /// Возвращает помеченные записи поля (серийник, дата, причина)
+    fn flags(database: &rusqlite::Connection) -> Vec<(String, i64, String)> {
+        let sql =
+            format!("SELECT serial, date, reason FROM {OUTLIER_FLAGS_TABLE} ORDER BY serial, date");
+        database
+            .prepare(&sql)
+            .unwrap()
+            .query_map((), |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn flags_outliers_and_replaces_previous_flags() {
+        let database = database(&[
+            ("a", 0, Some(5.)),
+            ("a", 60, Some(50.)),
+            ("a", 120, None),
+            ("a", 180, Some(5.)),
+            ("b", 0, Some(-5.)),
+        ]);
+
+        let rule = OutlierRule {
+            min: Some(0.),
+            max: Some(10.),
+            ..Default::default()
+        };
+        let counts = detect_outliers(&database, "meteo", "t", &rule).unwrap();
+        assert_eq!(counts, [2, 0, 0]);
+        assert_eq!(
+            flags(&database),
+            [
+                ("a".to_owned(), 60, "range".to_owned()),
+                ("b".to_owned(), 0, "range".to_owned())
+            ]
+        );
+
+        // Новое правило полностью заменяет старые пометки
+        let rule = OutlierRule {
+            max: Some(10.),
+            ..Default::default()
+        };
+        assert_eq!(
+            detect_outliers(&database, "meteo", "t", &rule).unwrap(),
+            [1, 0, 0]
+        );
+        assert_eq!(flags(&database), [("a".to_owned(), 60, "range".to_owned())]);
+    }
+
+    #[test]
+    fn checks_each_serial_separately() {
+        // Записи разных приборов не считаются соседними, поэтому запись прибора b — не скачок
+        let database = database(&[
+            ("a", 0, Some(0.)),
+            ("a", 60, Some(0.)),
+            ("b", 120, Some(100.)),
+            ("c", 180, Some(0.)),
+            ("c", 240, Some(0.)),
+        ]);
+
+        let rule = OutlierRule {
+            rate: Some(1.),
+            ..Default::default()
+        };
+        assert_eq!(
+            detect_outliers(&database, "meteo", "t", &rule).unwrap(),
+            [0, 0, 0]
+        );
+        assert!(flags(&database).is_empty());
+    }
+}
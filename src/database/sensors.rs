@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use crate::app::state::App;
 
-use super::{SensorsFields, SensorsSerials};
+use super::{SensorsFields, SensorsSerials, SERVICE_TABLES};
 
 impl App<'_> {
     /// Получает структуру таблиц датчиков, загруженных в БД
@@ -12,33 +12,7 @@ impl App<'_> {
         // Получаем соединение с базой данных
         let database = database.lock().unwrap();
 
-        // Таблица, которая будет хранить все известные поля сенсоров
-        let mut sensors_fields = HashMap::new();
-
-        // SQL запрос, который получит структуру таблиц
-        let sql = "SELECT name, sql FROM sqlite_schema WHERE type = 'table'";
-        let mut statement = database.prepare_cached(sql)?;
-
-        // Получаем строки из БД и итерируем по ним
-        let mut rows = statement.query(())?;
-        while let Some(row) = rows.next()? {
-            // Получаем запрошенные поля
-            let name: String = row.get(0)?;
-            let sql: String = row.get(1)?;
-
-            // Получаем спецификацию таблицы
-            let fields = sql.split_once('(').unwrap().1;
-            let fields = fields.rsplit_once(", UNIQUE").unwrap().0;
-            let fields: Vec<_> = fields
-                .split(',')
-                .map(|field| field.trim().split_once(' ').unwrap().0.to_owned())
-                .collect();
-
-            // Кладём поля в хэш-таблицу
-            sensors_fields.insert(name, fields);
-        }
-
-        Ok(sensors_fields)
+        read_sensors_fields(&database)
     }
 
     /// Возвращает серийники датчиков, уже загруженных в состояние приложения
@@ -72,3 +46,41 @@ impl App<'_> {
         Ok(sensor_serials)
     }
 }
+
+/// Читает структуру таблиц датчиков из БД (без состояния приложения, например, для команд CLI)
+pub fn read_sensors_fields(
+    database: &rusqlite::Connection,
+) -> Result<SensorsFields, Box<dyn std::error::Error>> {
+    // Таблица, которая будет хранить все известные поля сенсоров
+    let mut sensors_fields = HashMap::new();
+
+    // SQL запрос, который получит структуру таблиц
+    let sql = "SELECT name, sql FROM sqlite_schema WHERE type = 'table'";
+    let mut statement = database.prepare_cached(sql)?;
+
+    // Получаем строки из БД и итерируем по ним
+    let mut rows = statement.query(())?;
+    while let Some(row) = rows.next()? {
+        // Получаем запрошенные поля
+        let name: String = row.get(0)?;
+        let sql: String = row.get(1)?;
+
+        // Служебные таблицы не относятся к датчикам
+        if SERVICE_TABLES.contains(&name.as_str()) {
+            continue;
+        }
+
+        // Получаем спецификацию таблицы
+        let fields = sql.split_once('(').unwrap().1;
+        let fields = fields.rsplit_once(", UNIQUE").unwrap().0;
+        let fields: Vec<_> = fields
+            .split(',')
+            .map(|field| field.trim().split_once(' ').unwrap().0.to_owned())
+            .collect();
+
+        // Кладём поля в хэш-таблицу
+        sensors_fields.insert(name, fields);
+    }
+
+    Ok(sensors_fields)
+}
@@ -0,0 +1,46 @@
+use crate::app::{session::Session, state::App};
+
+/// Название служебной таблицы, в которой хранится сессия (открытые вкладки и т.п.)
+pub const SESSION_TABLE: &str = "app_session";
+
+/// Создаёт таблицу сессии, если её ещё нет
+pub fn create_session_table(database: &rusqlite::Connection) -> rusqlite::Result<()> {
+    // Сессия всего одна, поэтому в таблице может быть только строка с id = 0
+    let sql = format!(
+        "CREATE TABLE IF NOT EXISTS {SESSION_TABLE} \
+         (id INTEGER PRIMARY KEY CHECK (id = 0), config TEXT NOT NULL)"
+    );
+    database.execute(&sql, ())?;
+
+    Ok(())
+}
+
+impl App<'_> {
+    /// Записывает сессию в базу данных
+    pub fn store_session(&self, session: &Session) -> Result<(), Box<dyn std::error::Error>> {
+        // Получаем соединение с базой данных
+        let database = self.database.lock().unwrap();
+
+        let sql = format!("INSERT OR REPLACE INTO {SESSION_TABLE} (id, config) VALUES (0, ?1)");
+        database.execute(&sql, [session.to_json()])?;
+
+        Ok(())
+    }
+
+    /// Читает сессию из базы данных. Возвращает `None`, если её нет или она повреждена
+    pub fn fetch_session(&self) -> Result<Option<Session>, Box<dyn std::error::Error>> {
+        // Получаем соединение с базой данных
+        let database = self.database.lock().unwrap();
+
+        let sql = format!("SELECT config FROM {SESSION_TABLE} WHERE id = 0");
+        let mut statement = database.prepare_cached(&sql)?;
+        let mut rows = statement.query(())?;
+        let Some(row) = rows.next()? else {
+            return Ok(None);
+        };
+
+        // Повреждённая сессия не должна мешать запуску, поэтому просто начинаем с чистого листа
+        let config: String = row.get(0)?;
+        Ok(Session::from_json(&config).ok())
+    }
+}
@@ -0,0 +1,67 @@
+use crate::{app::state::App, graph::view::GraphView};
+
+/// Название служебной таблицы, в которой хранятся сохранённые виды графиков
+pub const VIEWS_TABLE: &str = "graph_views";
+
+/// Создаёт таблицу сохранённых видов, если её ещё нет
+pub fn create_views_table(database: &rusqlite::Connection) -> rusqlite::Result<()> {
+    let sql = format!(
+        "CREATE TABLE IF NOT EXISTS {VIEWS_TABLE} (name TEXT PRIMARY KEY, config TEXT NOT NULL)"
+    );
+    database.execute(&sql, ())?;
+
+    Ok(())
+}
+
+impl App<'_> {
+    /// Возвращает названия всех сохранённых видов в алфавитном порядке
+    pub fn get_views_names(&self) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        // Получаем соединение с базой данных
+        let database = self.database.lock().unwrap();
+
+        let sql = format!("SELECT name FROM {VIEWS_TABLE} ORDER BY name");
+        let mut statement = database.prepare_cached(&sql)?;
+        let names = statement
+            .query_map((), |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+
+        Ok(names)
+    }
+
+    /// Сохраняет вид под данным названием (вид с тем же названием перезаписывается)
+    pub fn save_view(
+        &self,
+        name: &str,
+        view: &GraphView,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        // Получаем соединение с базой данных
+        let database = self.database.lock().unwrap();
+
+        let sql = format!("INSERT OR REPLACE INTO {VIEWS_TABLE} (name, config) VALUES (?1, ?2)");
+        database.execute(&sql, (name, view.to_json()))?;
+
+        Ok(())
+    }
+
+    /// Загружает вид с данным названием
+    pub fn load_view(&self, name: &str) -> Result<GraphView, Box<dyn std::error::Error>> {
+        // Получаем соединение с базой данных
+        let database = self.database.lock().unwrap();
+
+        let sql = format!("SELECT config FROM {VIEWS_TABLE} WHERE name = ?1");
+        let config: String = database.query_row(&sql, [name], |row| row.get(0))?;
+
+        Ok(GraphView::from_json(&config)?)
+    }
+
+    /// Удаляет вид с данным названием
+    pub fn delete_view(&self, name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        // Получаем соединение с базой данных
+        let database = self.database.lock().unwrap();
+
+        let sql = format!("DELETE FROM {VIEWS_TABLE} WHERE name = ?1");
+        database.execute(&sql, [name])?;
+
+        Ok(())
+    }
+}
@@ -1,6 +1,13 @@
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{KeyEvent, MouseButton, MouseEvent, MouseEventKind};
 
-use crate::app::state::App;
+use crate::{
+    app::{
+        keymap::{Action, KeyContext},
+        state::App,
+    },
+    database::{alerts::detect_all_alerts, outliers::detect_all_outliers},
+    locale::trf,
+};
 
 use self::state::{FilePickerItem, FilePickerState};
 
@@ -36,33 +43,89 @@ impl App<'_> {
                 let _ = thread.join().unwrap();
             }
 
-            // После того, как дождались - можем закрыть выбор файла
-            self.close_file_picker();
-
             // Также мы должны обновить данные датчиков
             // TODO: на всякий случай нужна обработка ошибок здесь
             let _ = self.update_sensor_data();
+
+            // Новые записи тоже нужно проверить на выбросы по сохранённым правилам
+            // и на превышения порогов
+            let new_alerts = {
+                let database = self.database.lock().unwrap();
+                detect_all_outliers(&database).and_then(|()| detect_all_alerts(&database))
+            };
+
+            // Если проверка не удалась, оставляем выбор файла открытым и показываем в нём ошибку
+            let new_alerts = match new_alerts {
+                Ok(new_alerts) => new_alerts,
+                Err(err) => {
+                    self.file_picker_state_mut().error =
+                        Some(trf("Ошибка проверки новых записей: {}", &[&err]));
+                    return;
+                }
+            };
+
+            // После того, как дождались - можем закрыть выбор файла
+            self.close_file_picker();
+
+            // О новых превышениях сразу сообщаем окном превышений
+            if !new_alerts.is_empty() {
+                self.open_alerts(Some(trf(
+                    "Новых превышений после импорта: {}",
+                    &[&new_alerts.len()],
+                )));
+            }
         }
     }
 
     /// Обрабатывает все события, связанные с нажатием клавиш в режиме выбора файла
     pub fn on_key_event_file_picker(&mut self, event: KeyEvent) -> std::io::Result<()> {
+        // Действие, назначенное на клавишу в окне выбора файла
+        let action = self.keymap.action(KeyContext::FilePicker, event);
+
         // Получаем состояние, для того чтобы поменять что-нибудь
         let state = self.file_picker_state_mut();
 
-        match event.code {
-            KeyCode::Esc | KeyCode::Char('q') => self.close_file_picker(),
-            KeyCode::Up => state.prev_file(),
-            KeyCode::Down => state.next_file(),
-            KeyCode::Left => state.goto_parent_directory(),
-            KeyCode::Right | KeyCode::Enter => self.try_import_file(),
-            KeyCode::Char('I') => self.try_import_directory(),
+        match action {
+            Some(Action::Close) => self.close_file_picker(),
+            Some(Action::Up) => state.prev_file(),
+            Some(Action::Down) => state.next_file(),
+            Some(Action::Left) => state.goto_parent_directory(),
+            Some(Action::Open) => self.try_import_file(),
+            Some(Action::ImportDirectory) => self.try_import_directory(),
+            Some(Action::Help) => self.open_help(KeyContext::FilePicker),
             _ => (),
         }
 
         Ok(())
     }
 
+    /// Обрабатывает все события, связанные с мышкой в режиме выбора файла
+    pub fn on_mouse_event_file_picker(&mut self, event: MouseEvent) {
+        // Получаем состояние выбора файла
+        let state = self.file_picker_state_mut();
+
+        // Пока идёт импорт, ничего не выбираем
+        if !state.import_threads.is_empty() {
+            return;
+        }
+
+        match event.kind {
+            // Первое нажатие выбирает элемент, нажатие на выбранный - открывает его
+            MouseEventKind::Down(MouseButton::Left) => {
+                match state.item_at(event.column, event.row) {
+                    Some(i) if i == state.selection_index => self.try_import_file(),
+                    Some(i) => state.selection_index = i,
+                    None => (),
+                }
+            }
+            // Прокрутка списка колёсиком
+            MouseEventKind::ScrollUp => state.prev_file(),
+            MouseEventKind::ScrollDown => state.next_file(),
+
+            _ => (),
+        }
+    }
+
     /// Пытается открыть файл, и если он был открыт -> импортирует данные в БД
     fn try_import_file(&mut self) {
         // Пытаемся открыть файл. Если директория, то выходим из функции
@@ -76,7 +139,9 @@ impl App<'_> {
 
         // Добавляем поток импорта в список для ожидания
         if let Some(thread) = thread {
-            self.file_picker_state_mut().import_threads.push(thread);
+            let state = self.file_picker_state_mut();
+            state.error = None;
+            state.import_threads.push(thread);
         }
     }
 
@@ -90,6 +155,7 @@ impl App<'_> {
 
         // Читаем файлы из директории и импортируем их
         let items = state.directory_contents.clone();
+        self.file_picker_state_mut().error = None;
         for item in items {
             // Если элемент - директория, пропускаем. Иначе -> импортируем
             match item {
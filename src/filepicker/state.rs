@@ -1,5 +1,9 @@
 use std::{path::PathBuf, thread::JoinHandle};
 
+use tui::layout::Rect;
+
+use crate::ui::utils::area_contains;
+
 /// Определяет одну элемент директории
 #[derive(Clone, Debug)]
 pub enum FilePickerItem {
@@ -22,8 +26,14 @@ pub struct FilePickerState {
     /// Сохраняет отступ в списке файлов
     offset: usize,
 
+    /// Сохраняет область, в которой был отрисован список файлов
+    pub list_area: Rect,
+
     /// Сохраняет потоки импорта данных
     pub import_threads: Vec<JoinHandle<Result<(), ()>>>,
+
+    /// Сохраняет ошибку проверки импортированных записей, если она была
+    pub error: Option<String>,
 }
 
 impl FilePickerState {
@@ -38,7 +48,9 @@ impl FilePickerState {
             directory_contents: Vec::new(),
             selection_index: 0,
             offset: 0,
+            list_area: Rect::default(),
             import_threads: Vec::new(),
+            error: None,
         };
 
         // Пополняем состояние файлами и директориями
@@ -151,6 +163,16 @@ impl FilePickerState {
         self.selection_index = (self.selection_index + 1) % length;
     }
 
+    /// Возвращает индекс элемента директории, который был отрисован в данной клетке
+    pub fn item_at(&self, column: u16, row: u16) -> Option<usize> {
+        if !area_contains(self.list_area, column, row) {
+            return None;
+        }
+
+        let i = self.offset + (row - self.list_area.y) as usize;
+        (i < self.directory_contents.len()).then_some(i)
+    }
+
     /// Возвращает границы списка файлов, которые необходимо рендерить
     pub fn get_render_bounds(&mut self, max_height: usize) -> (usize, usize) {
         // Получаем элементы для более удобного доступа
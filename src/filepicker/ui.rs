@@ -7,7 +7,7 @@ use tui::{
     Frame,
 };
 
-use crate::ui::utils::get_popup_area;
+use crate::{locale::tr, ui::utils::get_popup_area};
 
 use super::state::{FilePickerItem, FilePickerState};
 
@@ -18,7 +18,7 @@ pub fn draw_file_picker<B: Backend>(frame: &mut Frame<B>, state: &mut FilePicker
 
     // Делаем блок
     let block = Block::default()
-        .title("Выбор файла для импорта")
+        .title(tr("Выбор файла для импорта"))
         .borders(Borders::ALL);
 
     // Рендерим список файлов
@@ -26,6 +26,18 @@ pub fn draw_file_picker<B: Backend>(frame: &mut Frame<B>, state: &mut FilePicker
     frame.render_widget(block, popup_area);
     draw_file_list(frame, state, file_list_area);
 
+    // Если проверка импортированных записей не удалась, отображаем ошибку на нижней рамке
+    if let Some(error) = &state.error {
+        let error_area = Rect {
+            y: popup_area.bottom() - 1,
+            height: 1,
+            ..file_list_area
+        };
+        let paragraph =
+            Paragraph::new(Text::raw(format!(" {error} "))).style(Style::default().fg(Color::Red));
+        frame.render_widget(paragraph, error_area);
+    }
+
     // Если у нас сейчас происходит момент импорта файла, отображаем окошко и ждём.
     if !state.import_threads.is_empty() {
         // Выделяем новую область под окошко
@@ -37,7 +49,7 @@ pub fn draw_file_picker<B: Backend>(frame: &mut Frame<B>, state: &mut FilePicker
         frame.render_widget(block, wait_popup_area);
 
         // Делаем виджет для рендера внутри
-        let text = "Данные импортируются, подождите...";
+        let text = tr("Данные импортируются, подождите...");
         let paragraph = Paragraph::new(Text::from(text)).alignment(Alignment::Center);
         frame.render_widget(paragraph, inner_area);
     }
@@ -47,6 +59,7 @@ pub fn draw_file_picker<B: Backend>(frame: &mut Frame<B>, state: &mut FilePicker
 fn draw_file_list<B: Backend>(frame: &mut Frame<B>, state: &mut FilePickerState, area: Rect) {
     // Очищаем область рендера, чтобы не видеть артефакты
     frame.render_widget(Clear, area);
+    state.list_area = area;
 
     // Если директория не пустая -> обрабатываем файлы
     if !state.directory_contents.is_empty() {
@@ -86,7 +99,7 @@ fn draw_file_list<B: Backend>(frame: &mut Frame<B>, state: &mut FilePickerState,
         frame.render_widget(paragraph, area);
     } else {
         // Делаем виджет для рендера внутри
-        let text = "--- Директория пуста --";
+        let text = tr("--- Директория пуста --");
         let paragraph = Paragraph::new(Text::from(text));
         frame.render_widget(paragraph, area);
     }
@@ -0,0 +1,113 @@
+use crossterm::event::KeyEvent;
+use itertools::Itertools;
+
+use crate::{
+    app::{
+        keymap::{Action, KeyContext},
+        state::App,
+    },
+    database::{
+        gaps::{find_sensors_gaps, format_date, format_duration, DataGap},
+        sensors::read_sensors_fields,
+    },
+    locale::trf,
+};
+
+use self::state::GapsState;
+
+pub mod state;
+pub mod ui;
+
+/// Заголовки столбцов отчёта о пропусках
+pub const GAPS_HEADER: [&str; 6] = [
+    "Датчик",
+    "Серийник",
+    "Начало",
+    "Конец",
+    "Длительность",
+    "Обычный шаг",
+];
+
+/// Количество строк, на которое перемещают `PageUp` и `PageDown`
+const PAGE_LENGTH: isize = 10;
+
+impl App<'_> {
+    /// Ищет пропуски у всех датчиков и открывает окно отчёта
+    pub fn open_gaps_report(&mut self) {
+        let sensors: Vec<_> = self
+            .sensor_fields
+            .borrow()
+            .keys()
+            .cloned()
+            .sorted()
+            .collect();
+
+        // Ищем пропуски сразу, т.к. это один запрос на датчик
+        let result = {
+            let database = self.database.clone();
+            let database = database.lock().unwrap();
+            find_sensors_gaps(&database, &sensors)
+        };
+        let state = match result {
+            Ok(gaps) => GapsState::new(gaps),
+            Err(err) => GapsState {
+                error: Some(trf("Не удалось найти пропуски: {}", &[&err])),
+                ..Default::default()
+            },
+        };
+        self.sensors_state_mut().gaps_state = Some(state);
+    }
+
+    /// Обрабатывает все события, связанные с нажатием клавиш в окне отчёта о пропусках
+    pub fn on_key_event_gaps(&mut self, event: KeyEvent) {
+        // Действие, назначенное на клавишу в окне отчёта
+        let action = self.keymap.action(KeyContext::Gaps, event);
+
+        // Получаем состояние окна отчёта
+        let sensors_state = self.sensors_state_mut();
+        let state = sensors_state.gaps_state.as_mut().unwrap();
+
+        match action {
+            Some(Action::Close) => sensors_state.gaps_state = None,
+            Some(Action::Up) => state.move_selection(-1),
+            Some(Action::Down) => state.move_selection(1),
+            Some(Action::PageUp) => state.move_selection(-PAGE_LENGTH),
+            Some(Action::PageDown) => state.move_selection(PAGE_LENGTH),
+            Some(Action::Home) => state.move_selection(-(state.gaps.len() as isize)),
+            Some(Action::End) => state.move_selection(state.gaps.len() as isize),
+            Some(Action::Help) => self.open_help(KeyContext::Gaps),
+            _ => (),
+        }
+    }
+}
+
+/// Возвращает значения столбцов отчёта для одного пропуска
+pub fn get_gap_row(gap: &DataGap) -> [String; 6] {
+    [
+        gap.sensor.clone(),
+        gap.serial.clone(),
+        format_date(gap.start),
+        format_date(gap.end),
+        format_duration(gap.duration()),
+        format_duration(gap.period.round() as i64),
+    ]
+}
+
+/// Выводит отчёт о пропусках всех датчиков в stdout (команда `gaps`), столбцы разделены табуляцией
+pub fn print_gaps_report(
+    database: &rusqlite::Connection,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let sensors: Vec<_> = read_sensors_fields(database)?
+        .into_keys()
+        .sorted()
+        .collect();
+    let gaps = find_sensors_gaps(database, &sensors)?;
+
+    // Заголовки не переводятся, чтобы вывод одинаково разбирался скриптами при любом языке
+    println!("{}", GAPS_HEADER.join("\t"));
+    for gap in &gaps {
+        println!("{}", get_gap_row(gap).join("\t"));
+    }
+
+    Ok(())
+}
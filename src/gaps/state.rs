@@ -0,0 +1,43 @@
+use tui::widgets::TableState;
+
+use crate::database::gaps::DataGap;
+
+/// Описывает состояние окна отчёта о пропусках данных
+#[derive(Debug, Default)]
+pub struct GapsState {
+    /// Сохраняет найденные пропуски
+    pub gaps: Vec<DataGap>,
+
+    /// Сохраняет состояние таблицы (выбранную строку и прокрутку)
+    pub table_state: TableState,
+
+    /// Сохраняет ошибку поиска пропусков, если она была
+    pub error: Option<String>,
+}
+
+impl GapsState {
+    /// Создаёт новое состояние окна отчёта
+    pub fn new(gaps: Vec<DataGap>) -> Self {
+        let mut table_state = TableState::default();
+        if !gaps.is_empty() {
+            table_state.select(Some(0));
+        }
+
+        Self {
+            gaps,
+            table_state,
+            error: None,
+        }
+    }
+
+    /// Выбирает строку, отстоящую от текущей на `offset` (с ограничением по краям)
+    pub fn move_selection(&mut self, offset: isize) {
+        if self.gaps.is_empty() {
+            return;
+        }
+
+        let selected = self.table_state.selected().unwrap_or(0) as isize;
+        let selected = (selected + offset).clamp(0, self.gaps.len() as isize - 1);
+        self.table_state.select(Some(selected as usize));
+    }
+}
@@ -0,0 +1,73 @@
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::Text,
+    widgets::{Block, Borders, Clear, Paragraph, Row, Table},
+    Frame,
+};
+
+use crate::{
+    database::gaps::GAP_FACTOR,
+    locale::{tr, trf},
+    ui::utils::get_popup_area,
+};
+
+use super::{get_gap_row, state::GapsState, GAPS_HEADER};
+
+/// Рендерит окно отчёта о пропусках данных
+pub fn draw_gaps_report<B: Backend>(frame: &mut Frame<B>, state: &mut GapsState, area: Rect) {
+    // Выделяем область под окошко и очищаем её, чтобы не видеть дерево под ним
+    let popup_area = get_popup_area(90, 80, area);
+    frame.render_widget(Clear, popup_area);
+
+    // Делаем блок (в заголовке - количество найденных пропусков)
+    let title = trf("Пропуски данных ({})", &[&state.gaps.len()]);
+    let block = Block::default().title(title).borders(Borders::ALL);
+    let inner_area = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    // Если пропусков нет, сообщаем об этом (или об ошибке их поиска)
+    if state.gaps.is_empty() {
+        let paragraph = match &state.error {
+            Some(error) => {
+                Paragraph::new(Text::from(error.as_str())).style(Style::default().fg(Color::Red))
+            }
+            None => Paragraph::new(Text::from(tr("--- Пропусков не найдено ---"))),
+        };
+        frame.render_widget(paragraph, inner_area);
+        return;
+    }
+
+    // Снизу подсказка о том, что считается пропуском
+    let area_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(inner_area);
+
+    let rows = state.gaps.iter().map(|gap| Row::new(get_gap_row(gap)));
+    let widths = [
+        Constraint::Percentage(12),
+        Constraint::Percentage(10),
+        Constraint::Percentage(22),
+        Constraint::Percentage(22),
+        Constraint::Percentage(17),
+        Constraint::Percentage(17),
+    ];
+    let table = Table::new(rows)
+        .header(Row::new(GAPS_HEADER.map(tr)).style(Style::default().fg(Color::Yellow)))
+        .highlight_style(
+            Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD),
+        )
+        .widths(&widths);
+    frame.render_stateful_widget(table, area_chunks[0], &mut state.table_state);
+
+    let text = trf(
+        "Пропуск - промежуток между записями прибора, который более чем в {} раза больше обычного",
+        &[&GAP_FACTOR],
+    );
+    let paragraph = Paragraph::new(Text::from(text)).style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(paragraph, area_chunks[1]);
+}
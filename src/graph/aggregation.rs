@@ -0,0 +1,89 @@
+/// Перечисляемый тип, определяющий промежутки времени, по которым группируются данные
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeBucket {
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+}
+
+impl TimeBucket {
+    /// Все возможные промежутки в порядке отображения в меню
+    pub const ALL: [Self; 5] = [Self::Minute, Self::Hour, Self::Day, Self::Week, Self::Month];
+
+    /// Возвращает название промежутка для отображения
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Minute => "По минутам",
+            Self::Hour => "По часам",
+            Self::Day => "По дням",
+            Self::Week => "По неделям",
+            Self::Month => "По месяцам",
+        }
+    }
+
+    /// Возвращает выражение SQL, дающее начало промежутка, в котором лежит дата
+    pub fn sql(&self) -> &'static str {
+        match self {
+            Self::Minute => "date - date % 60",
+            Self::Hour => "date - date % 3600",
+            Self::Day => "date - date % 86400",
+            // Эпоха Unix началась в четверг, поэтому сдвигаемся на 4 дня, чтобы неделя начиналась с понедельника
+            Self::Week => "date - (date - 345600) % 604800",
+            Self::Month => "CAST(strftime('%s', date, 'unixepoch', 'start of month') AS INTEGER)",
+        }
+    }
+}
+
+/// Перечисляемый тип, определяющий то, какое значение берётся в каждом промежутке
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Statistic {
+    Average,
+    Min,
+    Max,
+    Median,
+    Sum,
+    Count,
+    StdDev,
+}
+
+impl Statistic {
+    /// Все возможные значения в порядке отображения в меню
+    pub const ALL: [Self; 7] = [
+        Self::Average,
+        Self::Min,
+        Self::Max,
+        Self::Median,
+        Self::Sum,
+        Self::Count,
+        Self::StdDev,
+    ];
+
+    /// Возвращает название значения для отображения
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Average => "Среднее",
+            Self::Min => "Минимум",
+            Self::Max => "Максимум",
+            Self::Median => "Медиана",
+            Self::Sum => "Сумма",
+            Self::Count => "Количество",
+            Self::StdDev => "Станд. откл.",
+        }
+    }
+
+    /// Возвращает агрегатную функцию SQL для этого значения
+    /// (`median` и `stddev` добавляются в базу данных при запуске)
+    pub fn sql_function(&self) -> &'static str {
+        match self {
+            Self::Average => "AVG",
+            Self::Min => "MIN",
+            Self::Max => "MAX",
+            Self::Median => "median",
+            Self::Sum => "SUM",
+            Self::Count => "COUNT",
+            Self::StdDev => "stddev",
+        }
+    }
+}
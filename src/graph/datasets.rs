@@ -0,0 +1,914 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashSet},
+    hash::{Hash, Hasher},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+    time::Instant,
+};
+
+use itertools::Itertools;
+use rusqlite::InterruptHandle;
+
+use crate::{
+    database::{
+        alerts,
+        annotations::{self, Annotation},
+        gaps, open_read_connection,
+    },
+    locale::{tr, trf},
+};
+
+use super::{
+    aggregation::{Statistic, TimeBucket},
+    difference::Difference,
+    scaling::{Normalization, YAxis},
+    smoothing::{Smoothing, SmoothingWindow},
+    statistics::SeriesStatistics,
+};
+
+/// Сохраняет данные одной функции Y(x) для построения
+#[derive(Debug, Default)]
+pub struct GraphDataset {
+    /// Подписи каждого набора точек в формате "датчик/поле (серийник)"
+    pub labels: Vec<String>,
+
+    /// Наборы точек графика (несколько, если X - поле датчика или выбрано несколько приборов)
+    pub data: Vec<Vec<(f64, f64)>>,
+
+    /// Линейная регрессия каждого набора точек (есть только в точечном режиме)
+    pub regressions: Vec<Option<Regression>>,
+
+    /// Ось Y, по которой отображается график
+    pub axis: YAxis,
+
+    /// Пропуски (начало, конец) каждого набора точек, в которых линия графика разрывается
+    pub gaps: Vec<Vec<(f64, f64)>>,
+
+    /// Смещение и СКО каждого набора точек относительно эталона (есть, только если график - разность)
+    pub differences: Vec<Option<Difference>>,
+
+    /// Точки, помеченные выбросами (подсвечиваются поверх графика)
+    pub outliers: Vec<(f64, f64)>,
+
+    /// Пороги поля датчика, которые рисуются горизонтальными линиями
+    pub thresholds: Vec<f64>,
+
+    /// Статистика каждого запроса функции (есть, только если открыта панель статистики)
+    pub statistics: Vec<SeriesStatistics>,
+}
+
+/// Линейная регрессия y = slope * x + intercept, посчитанная методом наименьших квадратов
+#[derive(Clone, Copy, Debug)]
+pub struct Regression {
+    /// Наклон прямой
+    pub slope: f64,
+
+    /// Сдвиг прямой
+    pub intercept: f64,
+
+    /// Коэффициент детерминации
+    pub r2: f64,
+
+    /// Количество точек, по которым посчитана регрессия
+    pub count: usize,
+}
+
+impl Regression {
+    /// Считает регрессию по средним X и Y и центрированным суммам (Σdx², Σdy², Σdx·dy).
+    /// Возвращает `None`, если прямая не определена
+    fn from_centered_sums(
+        n: usize,
+        (mean_x, mean_y): (f64, f64),
+        (sxx, syy, sxy): (f64, f64, f64),
+    ) -> Option<Self> {
+        if n < 2 || sxx <= 0. {
+            return None;
+        }
+
+        let slope = sxy / sxx;
+        let intercept = mean_y - slope * mean_x;
+        // Если Y постоянен, то прямая описывает его полностью
+        let r2 = match syy > 0. {
+            true => sxy * sxy / (sxx * syy),
+            false => 1.,
+        };
+
+        Some(Self {
+            slope,
+            intercept,
+            r2,
+            count: n,
+        })
+    }
+}
+
+/// Описывает, откуда необходимо брать данные одной функции Y(x)
+#[derive(Clone, Debug)]
+pub struct SeriesRequest {
+    /// Название таблицы датчика
+    pub sensor: String,
+
+    /// Значение Y в виде выражения SQL
+    pub value: String,
+
+    /// Часть SQL запроса для отфильтровки серийников
+    pub serial_filtering: String,
+
+    /// Подпись графика
+    pub label: String,
+
+    /// Ось Y, по которой отображается график
+    pub axis: YAxis,
+
+    /// Нормализация значений Y, если она выбрана
+    pub normalization: Option<Normalization>,
+
+    /// Фильтр сглаживания значений Y и длина его окна, если он выбран
+    pub smoothing: Option<(Smoothing, SmoothingWindow)>,
+
+    /// Флаг того, что значения - разность с эталоном, по которой нужно посчитать смещение и СКО
+    pub difference: bool,
+
+    /// Подзапрос (date, serial, value) с помеченными выбросами записями, если их нужно подсветить
+    pub outliers: Option<String>,
+
+    /// Датчик и поле, пороги которых нужно нарисовать (только если значения в исходных единицах)
+    pub thresholds: Option<(String, String)>,
+
+    /// Временные таблицы, которые нужно создать перед запросами данных
+    pub temp_tables: Vec<TempTable>,
+
+    /// Источник с записями всех приборов, если значения посчитаны по нескольким приборам
+    /// (по нему пропуски ищутся у каждого прибора отдельно)
+    pub devices: Option<String>,
+
+    /// Серийники приборов, по записям которых построен график (по ним выбираются метки событий)
+    pub serials: Vec<String>,
+}
+
+/// Временная таблица, в которую перед загрузкой сохраняются записи подзапроса, чтобы искать
+/// по ним через индекс (по обёрнутому в подзапросы источнику SQLite индекс не использует).
+/// Таблица живёт, пока открыто соединение загрузки
+#[derive(Clone, Debug)]
+pub struct TempTable {
+    /// Название таблицы (одинаковые подзапросы дают одну и ту же таблицу)
+    pub name: String,
+
+    /// Подзапрос, записи которого сохраняются
+    select: String,
+
+    /// Столбцы индекса таблицы
+    index: &'static str,
+}
+
+impl TempTable {
+    /// Создаёт описание временной таблицы с записями подзапроса `select` и индексом по столбцам `index`
+    pub fn new(select: String, index: &'static str) -> Self {
+        let mut hasher = DefaultHasher::new();
+        select.hash(&mut hasher);
+        Self {
+            name: format!("temp_{:016x}", hasher.finish()),
+            select,
+            index,
+        }
+    }
+
+    /// Создаёт таблицу в соединении, если её там ещё нет
+    fn create(&self, database: &rusqlite::Connection) -> rusqlite::Result<()> {
+        let (name, select, index) = (&self.name, &self.select, self.index);
+        database.execute_batch(&format!(
+            "CREATE TEMP TABLE IF NOT EXISTS {name} AS {select}; \
+             CREATE INDEX IF NOT EXISTS temp.{name}_index ON {name} ({index});"
+        ))
+    }
+}
+
+/// Описывает запрос на построение всех графиков вкладки
+#[derive(Clone, Debug)]
+pub struct DatasetsRequest {
+    /// Поле данных X
+    pub x_field: String,
+
+    /// Часть SQL запроса для отфильтровки значений X
+    pub x_filtering: String,
+
+    /// Границы X, заданные пользователем
+    pub x_bounds: (Option<f64>, Option<f64>),
+
+    /// Количество точек по X, до которого необходимо прореживать данные
+    pub resolution: usize,
+
+    /// Группировка данных по времени и значение, которое берётся в каждом промежутке
+    pub aggregation: Option<(TimeBucket, Statistic)>,
+
+    /// Запросы для каждой функции Y(x) (пусто, если функция ещё не настроена)
+    pub series: Vec<Vec<SeriesRequest>>,
+
+    /// Флаг точечного режима (точки Y от X с линией регрессии, если X - поле датчика)
+    pub scatter: bool,
+
+    /// Флаг того, что нужно посчитать статистику каждого графика
+    pub statistics: bool,
+
+    /// Флаг того, что линии графиков разрываются на пропусках данных (только если X - дата)
+    pub gap_breaks: bool,
+}
+
+/// Содержит готовые данные для построения графиков
+#[derive(Debug, Default)]
+pub struct Datasets {
+    /// Данные каждой функции Y(x)
+    pub datasets: Vec<GraphDataset>,
+
+    /// Границы данных ((x_min, x_max), (y_min, y_max)) по левой оси Y
+    pub ranges: ((f64, f64), (f64, f64)),
+
+    /// Границы данных (y_min, y_max) по правой оси Y, если на ней есть графики
+    pub right_range: Option<(f64, f64)>,
+
+    /// Флаг того, что X является датой
+    pub x_is_date: bool,
+
+    /// Флаг того, что графики построены в точечном режиме
+    pub scatter: bool,
+
+    /// Метки событий в границах X (есть, только если X - дата)
+    pub annotations: Vec<Annotation>,
+}
+
+/// Результат загрузки датасетов (ошибка передаётся из потока загрузки, поэтому должна быть `Send`)
+type LoadResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// Описывает загрузку датасетов, которая происходит в отдельном потоке
+pub struct DatasetsLoader {
+    /// Поток, в котором происходит загрузка
+    thread: Option<JoinHandle<LoadResult<Option<Datasets>>>>,
+
+    /// Позволяет прервать запрос, который сейчас выполняется в потоке
+    interrupt: InterruptHandle,
+
+    /// Флаг отмены загрузки
+    cancelled: Arc<AtomicBool>,
+
+    /// Количество уже загруженных графиков
+    progress: Arc<AtomicUsize>,
+
+    /// Общее количество графиков
+    total: usize,
+
+    /// Время начала загрузки
+    started: Instant,
+}
+
+impl std::fmt::Debug for DatasetsLoader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // У прерывателя запросов нет отладочного вывода
+        f.debug_struct("DatasetsLoader")
+            .field("thread", &self.thread)
+            .field("cancelled", &self.cancelled)
+            .field("progress", &self.progress)
+            .field("total", &self.total)
+            .field("started", &self.started)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DatasetsLoader {
+    /// Запускает загрузку датасетов в отдельном потоке. Поток читает базу данных через своё соединение,
+    /// чтобы основное соединение оставалось свободным на всё время загрузки
+    pub fn spawn(
+        database: &Mutex<rusqlite::Connection>,
+        request: DatasetsRequest,
+    ) -> rusqlite::Result<Self> {
+        let connection = open_read_connection(&database.lock().unwrap())?;
+        let interrupt = connection.get_interrupt_handle();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let progress = Arc::new(AtomicUsize::new(0));
+        let total = request.series.len();
+
+        // Делаем "копии" флагов для потока
+        let thread_cancelled = cancelled.clone();
+        let thread_progress = progress.clone();
+        let thread = std::thread::spawn(move || {
+            let result = request.execute(&connection, &thread_cancelled, &thread_progress);
+            // Прерванный при отмене запрос завершается ошибкой, но это не ошибка загрузки
+            match thread_cancelled.load(Ordering::Relaxed) {
+                true => Ok(None),
+                false => result,
+            }
+        });
+
+        Ok(Self {
+            thread: Some(thread),
+            interrupt,
+            cancelled,
+            progress,
+            total,
+            started: Instant::now(),
+        })
+    }
+
+    /// Отменяет загрузку, прерывая текущий запрос
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+        self.interrupt.interrupt();
+    }
+
+    /// Возвращает, завершилась ли загрузка
+    pub fn is_finished(&self) -> bool {
+        self.thread.as_ref().is_none_or(JoinHandle::is_finished)
+    }
+
+    /// Дожидается окончания загрузки и возвращает данные (`None`, если она была отменена)
+    /// или текст ошибки, с которой она завершилась
+    pub fn join(mut self) -> Result<Option<Datasets>, String> {
+        let Some(thread) = self.thread.take() else {
+            return Ok(None);
+        };
+
+        match thread.join() {
+            Ok(result) => result.map_err(|err| err.to_string()),
+            Err(_) => Err(tr("Поток загрузки данных завершился аварийно").to_owned()),
+        }
+    }
+
+    /// Возвращает строку о прогрессе загрузки для отображения
+    pub fn status(&self) -> String {
+        const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+        // Кадр анимации меняется каждые 100 мс
+        let frame = (self.started.elapsed().as_millis() / 100) as usize % SPINNER.len();
+        let done = self.progress.load(Ordering::Relaxed).min(self.total);
+        let status = trf("Загрузка данных: {} из {}", &[&done, &self.total]);
+        format!("{} {status}", SPINNER[frame])
+    }
+}
+
+impl Drop for DatasetsLoader {
+    fn drop(&mut self) {
+        // Если загрузка больше не нужна, то и поток не должен зря держать базу данных
+        self.cancel();
+    }
+}
+
+impl DatasetsRequest {
+    /// Выполняет запрос к базе данных. Возвращает `None`, если загрузка была отменена
+    pub fn execute(
+        &self,
+        database: &rusqlite::Connection,
+        cancelled: &AtomicBool,
+        progress: &AtomicUsize,
+    ) -> LoadResult<Option<Datasets>> {
+        // Обрабатываем Y данные (границы считаем отдельно для левой и правой оси)
+        let mut y_ranges = [(f64::MAX, f64::MIN); 2];
+        let mut datasets = vec![];
+        for series in &self.series {
+            // Данные всех приборов одной функции собираем в один датасет
+            let mut dataset = GraphDataset::default();
+            if series.is_empty() {
+                // Ненастроенная функция, как и раньше, просто прижимает границы к нулю
+                y_ranges[0] = (y_ranges[0].0.min(0.), y_ranges[0].1.max(0.));
+            }
+            for series in series {
+                for table in &series.temp_tables {
+                    table.create(database)?;
+                }
+                let Some((part, y_min, y_max)) =
+                    self.generate_dataset(database, series, cancelled)?
+                else {
+                    return Ok(None);
+                };
+                dataset.labels.extend(part.labels);
+                dataset.data.extend(part.data);
+                dataset.regressions.extend(part.regressions);
+                dataset.gaps.extend(part.gaps);
+                dataset.differences.extend(part.differences);
+                dataset.outliers.extend(part.outliers);
+                for threshold in part.thresholds {
+                    if !dataset.thresholds.contains(&threshold) {
+                        dataset.thresholds.push(threshold);
+                    }
+                }
+                dataset.axis = series.axis;
+
+                // Статистику считаем по тем же данным, что и график, но без прореживания
+                if self.statistics {
+                    let (source, y_value, sql_filtering) = self.get_series_source(series);
+                    let statistics = SeriesStatistics::query(
+                        database,
+                        dataset.labels.last().cloned().unwrap_or_default(),
+                        (&source, &y_value, &sql_filtering),
+                        &self.get_devices_source(series),
+                    )?;
+                    dataset.statistics.push(statistics);
+                }
+
+                // Устанавливаем новые минимум и максимум для оси графика
+                let range = &mut y_ranges[series.axis as usize];
+                *range = (range.0.min(y_min), range.1.max(y_max));
+            }
+
+            datasets.push(dataset);
+            progress.fetch_add(1, Ordering::Relaxed);
+        }
+
+        // Записываем диапазоны значений
+        let x_min = self.x_bounds.0.unwrap_or_else(|| {
+            datasets
+                .iter()
+                .flat_map(|dataset| &dataset.data)
+                .map(|dataset| {
+                    dataset
+                        .iter()
+                        .map(|&(x, _)| x)
+                        .min_by(|a, b| a.partial_cmp(b).unwrap())
+                        .unwrap_or(0.)
+                })
+                .min_by(|a, b| a.partial_cmp(b).unwrap())
+                .unwrap_or(0.)
+        });
+
+        let x_max = self.x_bounds.1.unwrap_or_else(|| {
+            datasets
+                .iter()
+                .flat_map(|dataset| &dataset.data)
+                .map(|dataset| {
+                    dataset
+                        .iter()
+                        .map(|&(x, _)| x)
+                        .max_by(|a, b| a.partial_cmp(b).unwrap())
+                        .unwrap_or(0.)
+                })
+                .max_by(|a, b| a.partial_cmp(b).unwrap())
+                .unwrap_or(0.)
+        });
+
+        // Если все графики на правой оси, то она становится единственной
+        let [mut left_range, right_range] = y_ranges;
+        let has_left = datasets
+            .iter()
+            .any(|dataset| dataset.axis == YAxis::Left && !dataset.data.is_empty());
+        let right_range = match (has_left, right_range.0 <= right_range.1) {
+            (true, true) => Some(right_range),
+            (false, true) => {
+                left_range = right_range;
+                None
+            }
+            (_, false) => None,
+        };
+        if right_range.is_none() {
+            for dataset in &mut datasets {
+                dataset.axis = YAxis::Left;
+            }
+        }
+
+        // Метки событий ставятся только на шкалу времени. Метка прибора попадает на график,
+        // только если на нём есть записи этого прибора
+        let annotations = match self.x_field == "date" && !self.is_scatter() {
+            true => {
+                let range = (x_min.floor() as i64, x_max.ceil() as i64);
+                let serials: HashSet<_> = self
+                    .series
+                    .iter()
+                    .flatten()
+                    .flat_map(|series| &series.serials)
+                    .collect();
+                annotations::load_annotations(database, Some(range))?
+                    .into_iter()
+                    .filter(|annotation| {
+                        annotation
+                            .serial
+                            .as_ref()
+                            .is_none_or(|serial| serials.contains(serial))
+                    })
+                    .collect()
+            }
+            false => vec![],
+        };
+
+        Ok(Some(Datasets {
+            datasets,
+            ranges: ((x_min, x_max), left_range),
+            right_range,
+            x_is_date: self.x_field == "date",
+            scatter: self.is_scatter(),
+            annotations,
+        }))
+    }
+
+    /// Возвращает, строятся ли графики в точечном режиме (для даты он не имеет смысла)
+    fn is_scatter(&self) -> bool {
+        self.scatter && self.x_field != "date"
+    }
+
+    /// Возвращает источник данных графика, значение Y в нём и часть SQL запроса с фильтрами
+    fn get_series_source(&self, series: &SeriesRequest) -> (String, String, String) {
+        let y_sensor = &series.sensor;
+        let y_value = &series.value;
+
+        // Составляем часть SQL запроса со всеми фильтрами. Записи без значения (например, производной
+        // величины, у которой нет одного из входов) пропускаем, чтобы они не ломали загрузку
+        let not_null = format!("{} IS NOT NULL AND {y_value} IS NOT NULL", self.x_field);
+        let sql_filtering = where_clause(&[&self.x_filtering, &series.serial_filtering, &not_null]);
+
+        // Если данные группируются по времени, то берём их из подзапроса с группировкой.
+        // Статистика промежутка тоже может не посчитаться (например, СКО по одной записи)
+        match self.aggregation {
+            Some((bucket, statistic)) => {
+                let source = format!(
+                    "(SELECT {} AS date, {}({y_value}) AS value FROM {y_sensor} {sql_filtering} GROUP BY 1)",
+                    bucket.sql(),
+                    statistic.sql_function(),
+                );
+                (
+                    source,
+                    "value".to_owned(),
+                    "WHERE value IS NOT NULL".to_owned(),
+                )
+            }
+            None => (y_sensor.clone(), y_value.clone(), sql_filtering),
+        }
+    }
+
+    /// Возвращает подзапрос (date, serial) с записями приборов графика, по которым ищутся пропуски
+    fn get_devices_source(&self, series: &SeriesRequest) -> String {
+        match (&series.devices, self.aggregation) {
+            // У значения по всем приборам пропуски ищутся у каждого прибора отдельно
+            (Some(devices), None) => {
+                let sql_filtering = match self.x_filtering.is_empty() {
+                    true => String::new(),
+                    false => format!("WHERE {}", self.x_filtering),
+                };
+                format!("(SELECT date, serial FROM {devices} {sql_filtering})")
+            }
+            // Иначе записи графика принадлежат одному прибору (или это промежутки группировки)
+            _ => {
+                let (source, _, sql_filtering) = self.get_series_source(series);
+                format!("(SELECT date, '' AS serial FROM {source} {sql_filtering})")
+            }
+        }
+    }
+
+    /// Генерирует датасеты графика и получает в них минимальное и максимальное значение
+    fn generate_dataset(
+        &self,
+        database: &rusqlite::Connection,
+        series: &SeriesRequest,
+        cancelled: &AtomicBool,
+    ) -> LoadResult<Option<(GraphDataset, f64, f64)>> {
+        let x_field = &self.x_field;
+        let mut label = series.label.clone();
+
+        // В точечном режиме точки и регрессия получаются отдельно
+        if self.is_scatter() {
+            return self.generate_scatter_dataset(database, series, cancelled);
+        }
+
+        // Дописываем группировку в подпись графика
+        if let Some((bucket, statistic)) = self.aggregation {
+            let name = format!(
+                "{} {}",
+                tr(statistic.name()),
+                tr(bucket.name()).to_lowercase()
+            );
+            label = format!("{label} [{name}]");
+        }
+        let (source, y_value, sql_filtering) = self.get_series_source(series);
+
+        // Узнаём количество точек и их границы по X, чтобы понять, нужно ли их прореживать
+        let sql = format!(
+            "SELECT COUNT(*), MIN({x_field}), MAX({x_field}) FROM {source} {sql_filtering}"
+        );
+        let (count, data_x_min, data_x_max): (usize, Option<f64>, Option<f64>) =
+            database.query_row(&sql, (), |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+
+        // Если точек больше, чем может отобразить график, группируем их по корзинам
+        let bucket_width = match (data_x_min, data_x_max) {
+            (Some(min), Some(max)) if count > self.resolution && max > min => {
+                Some((max - min) / self.resolution as f64)
+            }
+            _ => None,
+        };
+        // Сглаживать прореженные точки (минимумы и максимумы корзин) нельзя, поэтому у сглаженного графика
+        // по дате в SQL берутся все записи и прореживаются уже после фильтра, а по полю датчика не прореживаются
+        let sql_bucket_width = bucket_width.filter(|_| series.smoothing.is_none());
+        let group_key = match sql_bucket_width {
+            Some(width) => {
+                let start = data_x_min.unwrap();
+                format!("CAST(({x_field} - {start}) / {width} AS INTEGER)")
+            }
+            None => x_field.to_owned(),
+        };
+
+        // Собираем финальный SQL запрос
+        let from = format!("FROM {source} {sql_filtering}");
+        let sql = match (x_field == "date", sql_bucket_width.is_some()) {
+            // Если X - дата, то берём точки как есть
+            (true, false) => format!("SELECT {x_field}, {y_value} {from} ORDER BY {x_field}"),
+            // Если точек слишком много, то в каждой корзине оставляем записи с минимумом и максимумом,
+            // чтобы не потерять пики (вместе с их собственными датами, а не границами корзины)
+            (true, true) => format!(
+                "SELECT x, y FROM (\
+                     SELECT x, y, \
+                     ROW_NUMBER() OVER (PARTITION BY bucket ORDER BY y, x) AS low, \
+                     ROW_NUMBER() OVER (PARTITION BY bucket ORDER BY y DESC, x) AS high \
+                     FROM (SELECT {x_field} AS x, {y_value} AS y, {group_key} AS bucket {from}) \
+                     WHERE y IS NOT NULL\
+                 ) WHERE low = 1 OR high = 1 ORDER BY x"
+            ),
+            // Если X - какое-то поле датчика, то для каждого X считаем минимум, среднее и максимум
+            (false, _) => format!(
+                "SELECT AVG({x_field}), MIN({y_value}), AVG({y_value}), MAX({y_value}) \
+                 {from} GROUP BY {group_key} ORDER BY 1"
+            ),
+        };
+        let mut statement = database.prepare(&sql)?;
+
+        // Проверяем, нужно ли нам график разделять на несколько
+        let mut dataset = if x_field == "date" {
+            // Если X - дата, то не нужно
+            vec![vec![]]
+        } else {
+            // Если X - какое-то поле датчика - нужно (минимум, среднее, максимум)
+            vec![vec![], vec![], vec![]]
+        };
+
+        // Выполняем запрос SQL и сохраняем данные
+        let mut rows = statement.query(())?;
+        while let Some(row) = rows.next()? {
+            // Проверяем, не была ли отменена загрузка
+            if cancelled.load(Ordering::Relaxed) {
+                return Ok(None);
+            }
+
+            let x: f64 = row.get(0)?;
+            match x_field == "date" {
+                // Минимум и максимум корзины приходят отдельными строками, уже по порядку времени
+                true => dataset[0].push((x, row.get(1)?)),
+                // Группа без значений Y не даёт точек
+                false => {
+                    let values: [Option<f64>; 3] = [row.get(1)?, row.get(2)?, row.get(3)?];
+                    if let [Some(min), Some(average), Some(max)] = values {
+                        for (points, y) in dataset.iter_mut().zip([min, average, max]) {
+                            points.push((x, y));
+                        }
+                    }
+                }
+            }
+        }
+
+        // Пропуски ищем по всем записям, а не по прореженным точкам. Они нужны и для сглаживания
+        let data_gaps = match (self.gap_breaks || series.smoothing.is_some()) && x_field == "date" {
+            true => gaps::find_gaps(database, &self.get_devices_source(series))?,
+            false => vec![],
+        };
+
+        // Сглаживаем значения до нормализации, чтобы она считалась уже по сглаженному графику.
+        // По дате участки между пропусками сглаживаются отдельно
+        if let Some((smoothing, window)) = series.smoothing {
+            let breaks: Vec<_> = data_gaps.iter().map(|&(_, end)| end as f64).collect();
+            for points in &mut dataset {
+                smoothing.apply_split(points, window, &breaks);
+            }
+        }
+
+        // Нормализуем значения, если это необходимо (по всем записям графика, а у сглаженного графика
+        // по дате - по его сглаженным значениям)
+        if let Some(normalization) = series.normalization {
+            let parameters = match series.smoothing.is_some() && x_field == "date" {
+                true => normalization.compute(&dataset[0]),
+                false => normalization.query(database, &y_value, &from, x_field)?,
+            };
+            match parameters {
+                Some(parameters) => {
+                    for points in &mut dataset {
+                        Normalization::apply(points, parameters);
+                    }
+                }
+                // Процент от первого значения не определён, если все значения нулевые
+                None if dataset.iter().any(|points| !points.is_empty()) => {
+                    return Err(trf(
+                        "Нельзя нормализовать \"{}\": нет ненулевых значений",
+                        &[&label],
+                    )
+                    .into())
+                }
+                None => (),
+            }
+        }
+
+        // Сглаженный график по дате прореживаем так же, как это делает запрос SQL
+        if let (Some(width), None, true) = (bucket_width, sql_bucket_width, x_field == "date") {
+            dataset[0] = downsample(&dataset[0], data_x_min.unwrap(), width);
+        }
+
+        // Получаем минимальное и максимальное значение Y
+        let (mut y_min, mut y_max) = (f64::MAX, f64::MIN);
+        for &(_, y) in dataset.iter().flatten() {
+            y_min = y_min.min(y);
+            y_max = y_max.max(y);
+        }
+
+        // Разрывы линий рисуем, только если они включены
+        let series_gaps: Vec<_> = match self.gap_breaks {
+            true => data_gaps
+                .into_iter()
+                .map(|(start, end)| (start as f64, end as f64))
+                .collect(),
+            false => vec![],
+        };
+
+        // Выбросы подсвечиваем только у исходных значений, т.к. после группировки и нормализации
+        // помеченных записей уже не узнать
+        let outliers = match (&series.outliers, self.aggregation, series.normalization) {
+            (Some(flagged), None, None) if x_field == "date" => {
+                // У помеченных записей свои столбцы, поэтому фильтр по значению Y здесь другой
+                let sql_filtering = where_clause(&[
+                    &self.x_filtering,
+                    &series.serial_filtering,
+                    "value IS NOT NULL",
+                ]);
+                let sql =
+                    format!("SELECT date, value FROM {flagged} {sql_filtering} ORDER BY date");
+                let mut statement = database.prepare(&sql)?;
+                let points = statement.query_map((), |row| Ok((row.get(0)?, row.get(1)?)))?;
+                points.collect::<rusqlite::Result<_>>()?
+            }
+            _ => vec![],
+        };
+
+        // Смещение и СКО разности считаем по исходным записям в границах X, до группировки и сглаживания
+        let difference = match series.difference {
+            true => {
+                let sql_filtering = match self.x_filtering.is_empty() {
+                    true => String::new(),
+                    false => format!("WHERE {}", self.x_filtering),
+                };
+                Difference::query(database, &series.sensor, &series.value, &sql_filtering)?
+            }
+            false => None,
+        };
+
+        // Пороги поля тоже должны попасть в границы графика, чтобы их линии было видно
+        let thresholds = match &series.thresholds {
+            Some((sensor, field)) => alerts::load_threshold(database, sensor, field)
+                .map_err(|err| err.to_string())?
+                .map(|threshold| threshold.values())
+                .unwrap_or_default(),
+            None => vec![],
+        };
+        for &threshold in &thresholds {
+            y_min = y_min.min(threshold);
+            y_max = y_max.max(threshold);
+        }
+
+        let dataset = GraphDataset {
+            labels: vec![label; dataset.len()],
+            regressions: vec![None; dataset.len()],
+            gaps: vec![series_gaps; dataset.len()],
+            differences: vec![difference; dataset.len()],
+            outliers,
+            thresholds,
+            data: dataset,
+            axis: series.axis,
+            statistics: vec![],
+        };
+
+        Ok(Some((dataset, y_min, y_max)))
+    }
+
+    /// Генерирует точки Y от X с линией регрессии и получает в них минимальное и максимальное значение
+    fn generate_scatter_dataset(
+        &self,
+        database: &rusqlite::Connection,
+        series: &SeriesRequest,
+        cancelled: &AtomicBool,
+    ) -> LoadResult<Option<(GraphDataset, f64, f64)>> {
+        let (x, y) = (&self.x_field, &series.value);
+        let source = &series.sensor;
+
+        // Точки без одного из значений в регрессии не участвуют
+        let not_null = format!("{x} IS NOT NULL AND {y} IS NOT NULL");
+        let filters = [&self.x_filtering, &series.serial_filtering, &not_null]
+            .into_iter()
+            .filter(|filter| !filter.is_empty())
+            .join(" AND ");
+        let from = format!("FROM {source} WHERE {filters}");
+
+        // Регрессию считаем по всем точкам в SQL в два прохода: сначала средние, затем суммы
+        // отклонений от них, т.к. суммы квадратов больших значений (например, давления ~1000 гПа)
+        // теряют точность при вычитании
+        let sql = format!(
+            "SELECT COUNT(*), AVG({x}), AVG({y}), MIN({x}), MAX({x}), MIN({y}), MAX({y}) {from}"
+        );
+        let (count, means, bounds): (usize, [Option<f64>; 2], [Option<f64>; 4]) = database
+            .query_row(&sql, (), |row| {
+                let means = [row.get(1)?, row.get(2)?];
+                let bounds = [row.get(3)?, row.get(4)?, row.get(5)?, row.get(6)?];
+                Ok((row.get(0)?, means, bounds))
+            })?;
+        let regression = match means {
+            [Some(mean_x), Some(mean_y)] => {
+                let (dx, dy) = (format!("({x} - {mean_x:?})"), format!("({y} - {mean_y:?})"));
+                let sql =
+                    format!("SELECT SUM({dx} * {dx}), SUM({dy} * {dy}), SUM({dx} * {dy}) {from}");
+                let sums = database
+                    .query_row(&sql, (), |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+                Regression::from_centered_sums(count, (mean_x, mean_y), sums)
+            }
+            _ => None,
+        };
+
+        // Если точек слишком много, то объединяем близкие точки в одну по сетке графика
+        let sql = match bounds {
+            [Some(x_min), Some(x_max), Some(y_min), Some(y_max)] if count > self.resolution => {
+                let x_width = ((x_max - x_min) / self.resolution as f64).max(f64::EPSILON);
+                let y_width = ((y_max - y_min) / self.resolution as f64).max(f64::EPSILON);
+                format!(
+                    "SELECT AVG({x}), AVG({y}) {from} \
+                     GROUP BY CAST(({x} - {x_min}) / {x_width} AS INTEGER), \
+                     CAST(({y} - {y_min}) / {y_width} AS INTEGER) ORDER BY 1"
+                )
+            }
+            _ => format!("SELECT {x}, {y} {from} ORDER BY 1"),
+        };
+        let mut statement = database.prepare(&sql)?;
+
+        // Выполняем запрос SQL и сохраняем точки
+        let mut points = vec![];
+        let mut rows = statement.query(())?;
+        while let Some(row) = rows.next()? {
+            // Проверяем, не была ли отменена загрузка
+            if cancelled.load(Ordering::Relaxed) {
+                return Ok(None);
+            }
+
+            points.push((row.get(0)?, row.get(1)?));
+        }
+
+        let dataset = GraphDataset {
+            labels: vec![series.label.clone()],
+            data: vec![points],
+            regressions: vec![regression],
+            gaps: vec![vec![]],
+            differences: vec![None],
+            outliers: vec![],
+            thresholds: vec![],
+            axis: series.axis,
+            statistics: vec![],
+        };
+
+        // Границы Y уже известны из первого запроса
+        let y_min = bounds[2].unwrap_or(f64::MAX);
+        let y_max = bounds[3].unwrap_or(f64::MIN);
+        Ok(Some((dataset, y_min, y_max)))
+    }
+}
+
+/// Собирает часть SQL запроса `WHERE` из непустых фильтров
+fn where_clause(filters: &[&str]) -> String {
+    let filters = filters
+        .iter()
+        .filter(|filter| !filter.is_empty())
+        .join(" AND ");
+    match filters.is_empty() {
+        true => String::new(),
+        false => format!("WHERE {filters}"),
+    }
+}
+
+/// Прореживает точки, отсортированные по X: в каждой корзине шириной `width` от `start` оставляет только
+/// точки с минимумом и максимумом, по порядку X
+fn downsample(points: &[(f64, f64)], start: f64, width: f64) -> Vec<(f64, f64)> {
+    let bucket = |x: f64| ((x - start) / width) as i64;
+
+    let mut result = vec![];
+    let mut i = 0;
+    while i < points.len() {
+        // Ищем конец корзины и записи с минимумом и максимумом в ней
+        let end = i + points[i..]
+            .iter()
+            .take_while(|&&(x, _)| bucket(x) == bucket(points[i].0))
+            .count();
+        let (mut low, mut high) = (i, i);
+        for j in i..end {
+            if points[j].1 < points[low].1 {
+                low = j;
+            }
+            if points[j].1 > points[high].1 {
+                high = j;
+            }
+        }
+
+        result.push(points[low.min(high)]);
+        if low != high {
+            result.push(points[low.max(high)]);
+        }
+        i = end;
+    }
+
+    result
+}
@@ -0,0 +1,143 @@
+/// Максимальное количество входных полей у производной величины (под них отведены два столбца)
+pub const MAX_INPUTS: usize = 2;
+
+/// Описывает производную величину, которая считается по одному или нескольким полям датчика
+#[derive(Debug)]
+pub struct DerivedField {
+    /// Название величины для отображения в поле данных Y
+    pub name: &'static str,
+
+    /// Название функции SQL, которая считает величину
+    pub function: &'static str,
+
+    /// Названия входных полей в том порядке, в котором их принимает формула
+    pub inputs: &'static [&'static str],
+
+    /// Формула величины (принимает значения входных полей)
+    pub formula: fn(&[f64]) -> f64,
+}
+
+impl DerivedField {
+    /// Возвращает выражение SQL, считающее величину по переданным полям датчика
+    pub fn sql(&self, fields: &[&str]) -> String {
+        format!("{}({})", self.function, fields.join(", "))
+    }
+}
+
+/// Все производные величины в порядке отображения в меню
+pub const DERIVED_FIELDS: [DerivedField; 8] = [
+    DerivedField {
+        name: "Эфф. темп.",
+        function: "effective_temperature",
+        inputs: &["Температура, °C", "Влажность, %"],
+        formula: effective_temperature,
+    },
+    DerivedField {
+        name: "Точка росы",
+        function: "dew_point",
+        inputs: &["Температура, °C", "Влажность, %"],
+        formula: dew_point,
+    },
+    DerivedField {
+        name: "Индекс жары",
+        function: "heat_index",
+        inputs: &["Температура, °C", "Влажность, %"],
+        formula: heat_index,
+    },
+    DerivedField {
+        name: "Абс. влажн.",
+        function: "absolute_humidity",
+        inputs: &["Температура, °C", "Влажность, %"],
+        formula: absolute_humidity,
+    },
+    DerivedField {
+        name: "Ветр. охлажд.",
+        function: "wind_chill",
+        inputs: &["Температура, °C", "Скорость ветра, м/с"],
+        formula: wind_chill,
+    },
+    DerivedField {
+        name: "Темп. в °F",
+        function: "celsius_to_fahrenheit",
+        inputs: &["Температура, °C"],
+        formula: |values| values[0] * 9. / 5. + 32.,
+    },
+    DerivedField {
+        name: "Темп. в K",
+        function: "celsius_to_kelvin",
+        inputs: &["Температура, °C"],
+        formula: |values| values[0] + 273.15,
+    },
+    DerivedField {
+        name: "Давл. в мм рт. ст.",
+        function: "hpa_to_mmhg",
+        inputs: &["Давление, гПа"],
+        formula: |values| values[0] * 0.750062,
+    },
+];
+
+// Под входные поля отведено только `MAX_INPUTS` столбцов, поэтому проверяем это при сборке
+const _: () = {
+    let mut i = 0;
+    while i < DERIVED_FIELDS.len() {
+        assert!(DERIVED_FIELDS[i].inputs.len() <= MAX_INPUTS);
+        i += 1;
+    }
+};
+
+/// Ищет производную величину по её названию
+pub fn find_derived_field(name: &str) -> Option<&'static DerivedField> {
+    DERIVED_FIELDS.iter().find(|field| field.name == name)
+}
+
+/// Эффективная температура (по температуре и относительной влажности)
+fn effective_temperature(values: &[f64]) -> f64 {
+    let (t, h) = (values[0], values[1]);
+    t - 0.4 * (t - 10.) * (1. - h / 100.)
+}
+
+/// Точка росы по формуле Магнуса
+fn dew_point(values: &[f64]) -> f64 {
+    const A: f64 = 17.62;
+    const B: f64 = 243.12;
+
+    let (t, h) = (values[0], values[1]);
+    let gamma = (h / 100.).ln() + A * t / (B + t);
+    B * gamma / (A - gamma)
+}
+
+/// Индекс жары по формуле Ротфуса (считается в градусах Фаренгейта)
+fn heat_index(values: &[f64]) -> f64 {
+    let (t, h) = (values[0] * 9. / 5. + 32., values[1]);
+
+    // При невысоких температурах достаточно упрощённой формулы
+    let simple = 0.5 * (t + 61. + (t - 68.) * 1.2 + h * 0.094);
+    let index = if (simple + t) / 2. < 80. {
+        simple
+    } else {
+        -42.379 + 2.04901523 * t + 10.14333127 * h
+            - 0.22475541 * t * h
+            - 0.00683783 * t * t
+            - 0.05481717 * h * h
+            + 0.00122874 * t * t * h
+            + 0.00085282 * t * h * h
+            - 0.00000199 * t * t * h * h
+    };
+
+    (index - 32.) * 5. / 9.
+}
+
+/// Абсолютная влажность в г/м³
+fn absolute_humidity(values: &[f64]) -> f64 {
+    let (t, h) = (values[0], values[1]);
+    // Давление насыщенного пара в гПа
+    let saturation = 6.112 * (17.67 * t / (t + 243.5)).exp();
+    saturation * h * 2.1674 / (273.15 + t)
+}
+
+/// Температура ветрового охлаждения (формула для скорости ветра в км/ч)
+fn wind_chill(values: &[f64]) -> f64 {
+    let (t, v) = (values[0], values[1] * 3.6);
+    let v = v.powf(0.16);
+    13.12 + 0.6215 * t - 11.37 * v + 0.3965 * t * v
+}
@@ -0,0 +1,65 @@
+use super::{datasets::TempTable, join::DEFAULT_JOIN_SECONDS};
+
+/// Смещение и СКО разности графика с эталоном
+#[derive(Clone, Copy, Debug)]
+pub struct Difference {
+    /// Среднее значение разности (систематическое смещение прибора)
+    pub bias: f64,
+
+    /// Среднеквадратичная разность
+    pub rmse: f64,
+
+    /// Количество совмещённых записей
+    pub count: usize,
+}
+
+impl Difference {
+    /// Считает смещение и СКО по значениям `value` источника разности с фильтрами `sql_filtering`
+    pub fn query(
+        database: &rusqlite::Connection,
+        source: &str,
+        value: &str,
+        sql_filtering: &str,
+    ) -> rusqlite::Result<Option<Self>> {
+        let sql = format!(
+            "SELECT COUNT({value}), AVG({value}), AVG({value} * {value}) FROM {source} {sql_filtering}"
+        );
+        database.query_row(&sql, (), |row| {
+            let count: usize = row.get(0)?;
+            let (bias, square): (Option<f64>, Option<f64>) = (row.get(1)?, row.get(2)?);
+            Ok(match (bias, square) {
+                (Some(bias), Some(square)) => Some(Self {
+                    bias,
+                    rmse: square.sqrt(),
+                    count,
+                }),
+                _ => None,
+            })
+        })
+    }
+}
+
+/// Возвращает временную таблицу (date, value) со значениями эталона из подзапроса `reference`.
+/// Разность ищет по ней ближайшие записи через индекс по дате
+pub fn reference_table(reference: String) -> TempTable {
+    TempTable::new(reference, "date")
+}
+
+/// Возвращает подзапрос (date, value) с разностью значений `value` подзапроса `minuend` (date, value)
+/// и ближайших по времени значений эталона из таблицы `reference` (date, value) в пределах `DEFAULT_JOIN_SECONDS`.
+/// Записи, для которых у эталона нет значения, отбрасываются
+pub fn difference_source(minuend: &str, reference: &TempTable) -> String {
+    let seconds = DEFAULT_JOIN_SECONDS;
+    let reference = &reference.name;
+
+    // Ищем ближайшие записи эталона до и после записи, а затем берём ближайшую из них
+    format!(
+        "(SELECT n.date AS date, n.value - r.value AS value \
+         FROM (SELECT d.date AS date, d.value AS value, \
+         (SELECT MAX(r.date) FROM {reference} AS r WHERE r.date BETWEEN d.date - {seconds} AND d.date) AS prev, \
+         (SELECT MIN(r.date) FROM {reference} AS r WHERE r.date BETWEEN d.date AND d.date + {seconds}) AS next \
+         FROM {minuend} AS d WHERE d.value IS NOT NULL) AS n \
+         JOIN {reference} AS r ON r.date = \
+         CASE WHEN n.next IS NULL OR n.date - n.prev <= n.next - n.date THEN n.prev ELSE n.next END)"
+    )
+}
@@ -0,0 +1,569 @@
+use itertools::Itertools;
+
+use crate::{
+    database::SensorsFields,
+    locale::{tr, trf},
+};
+
+use super::derived::{DerivedField, DERIVED_FIELDS};
+
+/// Название поля данных Y, значение которого задаётся выражением
+pub const EXPRESSION_FIELD: &str = "Выражение";
+
+/// Поля датчиков, которые нельзя использовать в выражениях
+const IGNORE_FIELDS: [&str; 3] = ["id", "serial", "date"];
+
+/// Арифметическая операция над двумя значениями
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl Operator {
+    /// Возвращает операцию по её символу
+    fn from_char(ch: char) -> Option<Self> {
+        match ch {
+            '+' => Some(Self::Add),
+            '-' => Some(Self::Sub),
+            '*' => Some(Self::Mul),
+            '/' => Some(Self::Div),
+            _ => None,
+        }
+    }
+
+    /// Возвращает символ операции в SQL
+    fn sql(&self) -> &'static str {
+        match self {
+            Self::Add => "+",
+            Self::Sub => "-",
+            Self::Mul => "*",
+            Self::Div => "/",
+        }
+    }
+}
+
+/// Разобранное выражение над полями датчиков
+#[derive(Debug)]
+pub enum Expression {
+    /// Числовая константа
+    Number(f64),
+
+    /// Поле датчика
+    Field { sensor: String, field: String },
+
+    /// Смена знака
+    Negate(Box<Expression>),
+
+    /// Арифметическая операция
+    Binary(Box<Expression>, Operator, Box<Expression>),
+
+    /// Вызов функции производной величины
+    Call(&'static DerivedField, Vec<Expression>),
+}
+
+impl Expression {
+    /// Разбирает выражение, проверяя существование всех полей датчиков
+    pub fn parse(text: &str, sensor_fields: &SensorsFields) -> Result<Self, String> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+            end: text.chars().count(),
+            sensor_fields,
+        };
+
+        // Разбираем всё выражение и проверяем, что после него ничего не осталось
+        let expression = parser.sum()?;
+        if let Some((position, _)) = parser.peek() {
+            return Err(trf("Лишний символ (позиция {})", &[&(position + 1)]));
+        }
+
+        // Без полей датчиков непонятно, откуда брать даты и серийники
+        if expression.sensors().is_empty() {
+            return Err(tr("Выражение должно содержать поле датчика").to_owned());
+        }
+
+        Ok(expression)
+    }
+
+    /// Возвращает все датчики, которые используются в выражении (в порядке появления)
+    pub fn sensors(&self) -> Vec<&str> {
+        let mut sensors = vec![];
+        self.collect_sensors(&mut sensors);
+        sensors.into_iter().unique().collect()
+    }
+
+    /// Дописывает в массив датчики, которые используются в выражении
+    fn collect_sensors<'a>(&'a self, sensors: &mut Vec<&'a str>) {
+        match self {
+            Self::Number(_) => (),
+            Self::Field { sensor, .. } => sensors.push(sensor),
+            Self::Negate(value) => value.collect_sensors(sensors),
+            Self::Binary(a, _, b) => {
+                a.collect_sensors(sensors);
+                b.collect_sensors(sensors);
+            }
+            Self::Call(_, arguments) => {
+                for argument in arguments {
+                    argument.collect_sensors(sensors);
+                }
+            }
+        }
+    }
+
+    /// Возвращает подзапрос SQL, в котором данные всех датчиков объединены по дате и серийнику.
+    /// Значение выражения находится в столбце `value`
+    pub fn sql_source(&self) -> String {
+        let sensors = self.sensors();
+
+        // Первый датчик даёт все остальные столбцы (дату, серийник и свои поля)
+        let mut sql = format!(
+            "SELECT t0.*, {} AS value FROM {} AS t0",
+            self.sql_value(&sensors),
+            sensors[0]
+        );
+        for (i, sensor) in sensors.iter().enumerate().skip(1) {
+            sql += &format!(
+                " JOIN {sensor} AS t{i} ON t{i}.date = t0.date AND t{i}.serial = t0.serial"
+            );
+        }
+
+        format!("({sql})")
+    }
+
+    /// Возвращает значение выражения в SQL (датчики заменяются на их псевдонимы в подзапросе)
+    fn sql_value(&self, sensors: &[&str]) -> String {
+        match self {
+            // Формат отладки всегда оставляет точку, поэтому деление не станет целочисленным
+            Self::Number(number) => format!("{number:?}"),
+            Self::Field { sensor, field } => {
+                let i = sensors.iter().position(|s| s == sensor).unwrap();
+                format!("t{i}.{field}")
+            }
+            Self::Negate(value) => format!("(-{})", value.sql_value(sensors)),
+            Self::Binary(a, operator, b) => format!(
+                "({} {} {})",
+                a.sql_value(sensors),
+                operator.sql(),
+                b.sql_value(sensors)
+            ),
+            Self::Call(function, arguments) => {
+                let arguments: Vec<_> = arguments.iter().map(|a| a.sql_value(sensors)).collect();
+                let arguments: Vec<_> = arguments.iter().map(String::as_str).collect();
+                function.sql(&arguments)
+            }
+        }
+    }
+}
+
+/// Лексема выражения
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Operator(Operator),
+    Open,
+    Close,
+    Comma,
+}
+
+/// Разбивает текст выражения на лексемы вместе с их позициями
+fn tokenize(text: &str) -> Result<Vec<(usize, Token)>, String> {
+    let chars: Vec<_> = text.chars().collect();
+    let mut tokens = vec![];
+
+    let mut i = 0;
+    while i < chars.len() {
+        let ch = chars[i];
+        let start = i;
+
+        let token = match ch {
+            ch if ch.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => Token::Open,
+            ')' => Token::Close,
+            ',' => Token::Comma,
+            // Число может быть в экспоненциальной записи
+            ch if ch.is_ascii_digit() || ch == '.' => {
+                while i + 1 < chars.len() {
+                    let next = chars[i + 1];
+                    let exponent = matches!(next, 'e' | 'E');
+                    let exponent_sign = matches!(next, '+' | '-') && matches!(chars[i], 'e' | 'E');
+                    if !(next.is_ascii_digit() || next == '.' || exponent || exponent_sign) {
+                        break;
+                    }
+                    i += 1;
+                }
+
+                let number: String = chars[start..=i].iter().collect();
+                match number.parse() {
+                    Ok(number) => Token::Number(number),
+                    Err(_) => {
+                        return Err(trf(
+                            "Неверное число \"{}\" (позиция {})",
+                            &[&number, &(start + 1)],
+                        ))
+                    }
+                }
+            }
+            // Имя поля может быть как "поле", так и "датчик/поле"
+            ch if is_name_char(ch) => {
+                while i + 1 < chars.len() && is_name_char(chars[i + 1]) {
+                    i += 1;
+                }
+                // Слэш сразу после имени и перед следующим именем означает поле датчика
+                if chars.get(i + 1) == Some(&'/')
+                    && chars.get(i + 2).is_some_and(|&c| is_name_char(c))
+                {
+                    i += 2;
+                    while i + 1 < chars.len() && is_name_char(chars[i + 1]) {
+                        i += 1;
+                    }
+                }
+
+                Token::Name(chars[start..=i].iter().collect())
+            }
+            ch => match Operator::from_char(ch) {
+                Some(operator) => Token::Operator(operator),
+                None => {
+                    return Err(trf(
+                        "Неизвестный символ '{}' (позиция {})",
+                        &[&ch, &(i + 1)],
+                    ))
+                }
+            },
+        };
+
+        tokens.push((start, token));
+        i += 1;
+    }
+
+    Ok(tokens)
+}
+
+/// Возвращает, может ли символ быть частью имени поля или функции
+fn is_name_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
+
+/// Разбирает лексемы выражения рекурсивным спуском
+struct Parser<'a> {
+    /// Лексемы выражения
+    tokens: Vec<(usize, Token)>,
+
+    /// Индекс следующей лексемы
+    position: usize,
+
+    /// Позиция конца выражения (для сообщений об ошибках)
+    end: usize,
+
+    /// Поля каждого датчика
+    sensor_fields: &'a SensorsFields,
+}
+
+impl Parser<'_> {
+    /// Возвращает следующую лексему, не забирая её
+    fn peek(&self) -> Option<&(usize, Token)> {
+        self.tokens.get(self.position)
+    }
+
+    /// Забирает следующую лексему
+    fn next(&mut self) -> Result<(usize, Token), String> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token.ok_or_else(|| {
+            trf(
+                "Неожиданный конец выражения (позиция {})",
+                &[&(self.end + 1)],
+            )
+        })
+    }
+
+    /// Забирает следующую лексему, если она является ожидаемой
+    fn expect(&mut self, expected: Token, name: &str) -> Result<(), String> {
+        match self.next()? {
+            (_, token) if token == expected => Ok(()),
+            (position, _) => Err(trf("Ожидалось {} (позиция {})", &[&name, &(position + 1)])),
+        }
+    }
+
+    /// Разбирает сумму или разность
+    fn sum(&mut self) -> Result<Expression, String> {
+        let mut expression = self.product()?;
+        while let Some((_, Token::Operator(operator @ (Operator::Add | Operator::Sub)))) =
+            self.peek().cloned()
+        {
+            self.position += 1;
+            let right = self.product()?;
+            expression = Expression::Binary(Box::new(expression), operator, Box::new(right));
+        }
+
+        Ok(expression)
+    }
+
+    /// Разбирает произведение или частное
+    fn product(&mut self) -> Result<Expression, String> {
+        let mut expression = self.factor()?;
+        while let Some((_, Token::Operator(operator @ (Operator::Mul | Operator::Div)))) =
+            self.peek().cloned()
+        {
+            self.position += 1;
+            let right = self.factor()?;
+            expression = Expression::Binary(Box::new(expression), operator, Box::new(right));
+        }
+
+        Ok(expression)
+    }
+
+    /// Разбирает число, поле, вызов функции, выражение в скобках или смену знака
+    fn factor(&mut self) -> Result<Expression, String> {
+        let (position, token) = self.next()?;
+        match token {
+            Token::Number(number) => Ok(Expression::Number(number)),
+            Token::Operator(Operator::Sub) => Ok(Expression::Negate(Box::new(self.factor()?))),
+            Token::Operator(Operator::Add) => self.factor(),
+            Token::Open => {
+                let expression = self.sum()?;
+                self.expect(Token::Close, "')'")?;
+                Ok(expression)
+            }
+            // Если после имени идёт скобка, то это функция
+            Token::Name(name) if self.peek().is_some_and(|(_, token)| *token == Token::Open) => {
+                self.position += 1;
+                self.call(&name, position)
+            }
+            Token::Name(name) => self.field(&name, position),
+            _ => Err(trf("Ожидалось значение (позиция {})", &[&(position + 1)])),
+        }
+    }
+
+    /// Разбирает аргументы функции производной величины
+    fn call(&mut self, name: &str, position: usize) -> Result<Expression, String> {
+        let function = DERIVED_FIELDS
+            .iter()
+            .find(|field| field.function == name)
+            .ok_or_else(|| {
+                trf(
+                    "Неизвестная функция \"{}\" (позиция {})",
+                    &[&name, &(position + 1)],
+                )
+            })?;
+
+        // Аргументы перечисляются через запятую
+        let mut arguments = vec![self.sum()?];
+        while let Some((_, Token::Comma)) = self.peek() {
+            self.position += 1;
+            arguments.push(self.sum()?);
+        }
+        self.expect(Token::Close, "')'")?;
+
+        if arguments.len() != function.inputs.len() {
+            return Err(trf(
+                "Функция \"{}\" принимает аргументов: {} (позиция {})",
+                &[&name, &function.inputs.len(), &(position + 1)],
+            ));
+        }
+
+        Ok(Expression::Call(function, arguments))
+    }
+
+    /// Разбирает поле датчика, проверяя его существование
+    fn field(&self, name: &str, position: usize) -> Result<Expression, String> {
+        let has_field = |fields: &Vec<String>, field: &str| {
+            !IGNORE_FIELDS.contains(&field) && fields.iter().any(|f| f == field)
+        };
+
+        // Если датчик указан, то проверяем только его
+        if let Some((sensor, field)) = name.split_once('/') {
+            return match self.sensor_fields.get(sensor) {
+                Some(fields) if has_field(fields, field) => Ok(Expression::Field {
+                    sensor: sensor.to_owned(),
+                    field: field.to_owned(),
+                }),
+                Some(_) => Err(trf(
+                    "Неизвестное поле \"{}\" (позиция {})",
+                    &[&name, &(position + 1)],
+                )),
+                None => Err(trf(
+                    "Неизвестный датчик \"{}\" (позиция {})",
+                    &[&sensor, &(position + 1)],
+                )),
+            };
+        }
+
+        // Иначе ищем датчик, у которого есть такое поле
+        let sensors: Vec<_> = self
+            .sensor_fields
+            .iter()
+            .filter(|(_, fields)| has_field(fields, name))
+            .map(|(sensor, _)| sensor)
+            .sorted()
+            .collect();
+        match sensors[..] {
+            [sensor] => Ok(Expression::Field {
+                sensor: sensor.clone(),
+                field: name.to_owned(),
+            }),
+            [] => Err(trf(
+                "Неизвестное поле \"{}\" (позиция {})",
+                &[&name, &(position + 1)],
+            )),
+            _ => Err(trf(
+                "Поле \"{}\" есть у нескольких датчиков, укажите \"{}/{}\" (позиция {})",
+                &[&name, &sensors[0], &name, &(position + 1)],
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Поля двух датчиков, у которых есть одинаковое поле
+    fn sensor_fields() -> SensorsFields {
+        let fields = |fields: &[&str]| fields.iter().map(|f| f.to_string()).collect();
+        SensorsFields::from([
+            (
+                "meteo".to_owned(),
+                fields(&["id", "serial", "date", "t", "h", "p"]),
+            ),
+            (
+                "air".to_owned(),
+                fields(&["id", "serial", "date", "t", "co2"]),
+            ),
+        ])
+    }
+
+    /// Разбирает выражение и возвращает его значение в SQL
+    fn parse_sql(text: &str) -> Result<String, String> {
+        let expression = Expression::parse(text, &sensor_fields())?;
+        Ok(expression.sql_value(&expression.sensors()))
+    }
+
+    #[test]
+    fn multiplication_binds_tighter_than_addition() {
+        assert_eq!(parse_sql("h + p * 2").unwrap(), "(t0.h + (t0.p * 2.0))");
+        assert_eq!(parse_sql("(h + p) * 2").unwrap(), "((t0.h + t0.p) * 2.0)");
+    }
+
+    #[test]
+    fn operators_of_same_precedence_are_left_associative() {
+        assert_eq!(parse_sql("h - p - 1").unwrap(), "((t0.h - t0.p) - 1.0)");
+        assert_eq!(parse_sql("h / p / 2").unwrap(), "((t0.h / t0.p) / 2.0)");
+    }
+
+    #[test]
+    fn unary_minus_applies_to_factor() {
+        assert_eq!(parse_sql("-h * 2").unwrap(), "((-t0.h) * 2.0)");
+        assert_eq!(parse_sql("p - -1e3").unwrap(), "(t0.p - (-1000.0))");
+    }
+
+    #[test]
+    fn sensors_are_numbered_in_order_of_appearance() {
+        assert_eq!(parse_sql("air/co2 / meteo/t").unwrap(), "(t0.co2 / t1.t)");
+        let expression = Expression::parse("air/co2 / meteo/t + co2", &sensor_fields()).unwrap();
+        assert_eq!(expression.sensors(), ["air", "meteo"]);
+    }
+
+    #[test]
+    fn division_by_zero_gives_skipped_null() {
+        let database = rusqlite::Connection::open_in_memory().unwrap();
+        crate::database::functions::register_functions(&database).unwrap();
+        database
+            .execute_batch(
+                "CREATE TABLE meteo (id INTEGER PRIMARY KEY, serial TEXT, date INTEGER, t REAL, h REAL, p REAL);
+                 INSERT INTO meteo (serial, date, h, p) VALUES ('a', 1, 1, 0), ('a', 2, 2, NULL), ('a', 3, 4, 2);",
+            )
+            .unwrap();
+
+        // SQLite делит на ноль без ошибки, а такие записи график пропускает вместе с пустыми значениями
+        let expression = Expression::parse("h / p", &sensor_fields()).unwrap();
+        let sql = format!(
+            "SELECT value FROM {} WHERE value IS NOT NULL ORDER BY date",
+            expression.sql_source()
+        );
+        let values: Vec<f64> = database
+            .prepare(&sql)
+            .unwrap()
+            .query_map((), |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(values, [2.]);
+    }
+
+    #[test]
+    fn function_call_checks_arguments() {
+        assert_eq!(
+            parse_sql("dew_point(meteo/t, h)").unwrap(),
+            "dew_point(t0.t, t0.h)"
+        );
+        assert_eq!(
+            parse_sql("dew_point(h)").unwrap_err(),
+            "Функция \"dew_point\" принимает аргументов: 2 (позиция 1)"
+        );
+        assert_eq!(
+            parse_sql("foo(h)").unwrap_err(),
+            "Неизвестная функция \"foo\" (позиция 1)"
+        );
+    }
+
+    #[test]
+    fn unknown_and_ambiguous_fields_are_errors() {
+        assert_eq!(
+            parse_sql("h + x").unwrap_err(),
+            "Неизвестное поле \"x\" (позиция 5)"
+        );
+        assert_eq!(
+            parse_sql("date").unwrap_err(),
+            "Неизвестное поле \"date\" (позиция 1)"
+        );
+        assert_eq!(
+            parse_sql("foo/t").unwrap_err(),
+            "Неизвестный датчик \"foo\" (позиция 1)"
+        );
+        assert_eq!(
+            parse_sql("t").unwrap_err(),
+            "Поле \"t\" есть у нескольких датчиков, укажите \"air/t\" (позиция 1)"
+        );
+    }
+
+    #[test]
+    fn syntax_errors_report_position() {
+        assert_eq!(
+            parse_sql("h +").unwrap_err(),
+            "Неожиданный конец выражения (позиция 4)"
+        );
+        assert_eq!(
+            parse_sql("(h + p").unwrap_err(),
+            "Неожиданный конец выражения (позиция 7)"
+        );
+        assert_eq!(parse_sql("h p").unwrap_err(), "Лишний символ (позиция 3)");
+        assert_eq!(
+            parse_sql("h % p").unwrap_err(),
+            "Неизвестный символ '%' (позиция 3)"
+        );
+        assert_eq!(
+            parse_sql("h * )").unwrap_err(),
+            "Ожидалось значение (позиция 5)"
+        );
+        assert_eq!(
+            parse_sql("1.2.3 + h").unwrap_err(),
+            "Неверное число \"1.2.3\" (позиция 1)"
+        );
+    }
+
+    #[test]
+    fn expression_without_fields_is_error() {
+        assert_eq!(
+            parse_sql("1 + 2").unwrap_err(),
+            "Выражение должно содержать поле датчика"
+        );
+    }
+}
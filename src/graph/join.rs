@@ -0,0 +1,74 @@
+/// Допуск или шаг сетки по умолчанию (в секундах), если он не введён
+pub const DEFAULT_JOIN_SECONDS: i64 = 60;
+
+/// Перечисляемый тип, определяющий то, как совмещаются по времени записи X и Y разных датчиков
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimestampJoin {
+    /// Записи с одинаковой датой
+    Exact,
+    /// Ближайшая по времени запись X в пределах допуска
+    Nearest,
+    /// Средние значения X и Y на общей сетке времени
+    Grid,
+}
+
+impl TimestampJoin {
+    /// Все возможные способы в порядке отображения в меню
+    pub const ALL: [Self; 3] = [Self::Exact, Self::Nearest, Self::Grid];
+
+    /// Возвращает название способа для отображения
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Exact => "Точное",
+            Self::Nearest => "Ближайшее",
+            Self::Grid => "Сетка",
+        }
+    }
+
+    /// Возвращает, нужен ли способу допуск или шаг сетки
+    pub fn has_seconds(&self) -> bool {
+        !matches!(self, Self::Exact)
+    }
+
+    /// Возвращает подзапрос SQL, в котором к значениям Y (столбец `value`) добавлен столбец X.
+    /// `seconds` - допуск для ближайшей записи или шаг сетки. `x_sensor` должен быть таблицей датчика,
+    /// а не подзапросом, иначе поиск ближайших записей X не сможет использовать индекс
+    pub fn sql_source(
+        &self,
+        seconds: i64,
+        (x_sensor, x_field): (&str, &str),
+        (y_source, y_value): (&str, &str),
+    ) -> String {
+        // Сначала считаем значение Y, чтобы поля датчиков X и Y не пересекались
+        let y = format!("(SELECT date, serial, {y_value} AS value FROM {y_source})");
+
+        let sql = match self {
+            Self::Exact => format!(
+                "SELECT y.date AS date, y.serial AS serial, x.{x_field} AS {x_field}, y.value AS value \
+                 FROM {y} AS y JOIN {x_sensor} AS x ON x.serial = y.serial AND x.date = y.date"
+            ),
+            // Ищем ближайшие записи X до и после записи Y, а затем берём ближайшую из них.
+            // X всегда берётся из самой таблицы датчика, поэтому поиск идёт по её индексу (serial, date),
+            // а записи Y, какими бы подзапросами они ни были обёрнуты, просматриваются один раз
+            Self::Nearest => format!(
+                "SELECT n.date AS date, n.serial AS serial, x.{x_field} AS {x_field}, n.value AS value \
+                 FROM (SELECT y.date AS date, y.serial AS serial, y.value AS value, \
+                 (SELECT MAX(x.date) FROM {x_sensor} AS x WHERE x.serial = y.serial \
+                 AND x.date BETWEEN y.date - {seconds} AND y.date) AS prev, \
+                 (SELECT MIN(x.date) FROM {x_sensor} AS x WHERE x.serial = y.serial \
+                 AND x.date BETWEEN y.date AND y.date + {seconds}) AS next FROM {y} AS y) AS n \
+                 JOIN {x_sensor} AS x ON x.serial = n.serial AND x.date = \
+                 CASE WHEN n.next IS NULL OR n.date - n.prev <= n.next - n.date THEN n.prev ELSE n.next END"
+            ),
+            Self::Grid => format!(
+                "SELECT x.date AS date, x.serial AS serial, x.value AS {x_field}, y.value AS value \
+                 FROM (SELECT date - date % {seconds} AS date, serial, AVG({x_field}) AS value \
+                 FROM {x_sensor} GROUP BY 1, 2) AS x \
+                 JOIN (SELECT date - date % {seconds} AS date, serial, AVG(value) AS value \
+                 FROM {y} GROUP BY 1, 2) AS y ON x.serial = y.serial AND x.date = y.date"
+            ),
+        };
+
+        format!("({sql})")
+    }
+}
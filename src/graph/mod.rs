@@ -1,22 +1,58 @@
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
-
-use crate::app::state::App;
-
-use self::state::{GraphFieldState, GraphState};
-
+use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
+
+use tui::layout::Rect;
+
+use crate::{
+    app::{
+        keymap::{Action, KeyContext},
+        state::App,
+    },
+    locale::tr,
+    ui::{
+        menu::{MenuState, MENU_HEIGHT},
+        utils::area_contains,
+    },
+};
+
+use self::{
+    derived::{find_derived_field, MAX_INPUTS},
+    expression::EXPRESSION_FIELD,
+    join::TimestampJoin,
+    smoothing::SmoothingWindow,
+    state::{GraphFieldState, GraphState, ROW_LENGTH},
+    view::GraphView,
+};
+
+pub mod aggregation;
+pub mod datasets;
+pub mod derived;
+pub mod difference;
+pub mod expression;
+pub mod join;
+pub mod scaling;
+pub mod smoothing;
 pub mod state;
+pub mod statistics;
 pub mod ui;
+pub mod view;
 
 impl App<'_> {
     /// Выполняет один тик обновления во вкладке графика
-    pub fn tick_graph(&mut self) {}
+    pub fn tick_graph(&mut self) {
+        // Проверяем, не загрузились ли новые датасеты
+        self.graph_state_mut().poll_datasets();
+    }
 
     /// Обрабатывает все события, связанные с нажатием клавиш во вкладке графика
     pub fn on_key_event_graph(&mut self, event: KeyEvent) {
         // Получаем состояние вкладки графика
         let state = self.graph_state();
 
-        if state.selected.is_some() {
+        if state.annotation_state.is_some() {
+            self.on_key_event_annotation(event);
+        } else if state.views_state.is_some() {
+            self.on_key_event_views(event);
+        } else if state.selected.is_some() {
             match state.selected_field_state() {
                 GraphFieldState::Input(state) if state.opened() => {
                     self.on_key_event_graph_input(event);
@@ -35,32 +71,220 @@ impl App<'_> {
 
     /// Обрабатывает все события, связанные с нажатием клавиш в обычном режиме
     fn on_key_event_graph_default(&mut self, event: KeyEvent) {
+        // Действие, назначенное на клавишу в обычном режиме вкладки
+        let action = self.keymap.action(KeyContext::Graph, event);
+
         // Получаем состояние вкладки графика
         let state = self.graph_state_mut();
 
-        match event.code {
+        match action {
             // Выход из приложения
-            KeyCode::Char('q') if event.modifiers == KeyModifiers::CONTROL => self.running = false,
+            Some(Action::Quit) => self.running = false,
             // Управление вкладками
-            KeyCode::BackTab => self.tabs.prev(),
-            KeyCode::Tab => self.tabs.next(),
-            KeyCode::Char('N') => self.open_new_tab(),
-            KeyCode::Char('q') => self.tabs.close(),
+            Some(Action::PrevTab) => self.tabs.prev(),
+            Some(Action::NextTab) => self.tabs.next(),
+            Some(Action::NewTab) => self.open_new_tab(),
+            Some(Action::CloseTab) => self.tabs.close(),
             // Открытие режима редактирования (первое поле всегда не пустое)
-            KeyCode::Char('e') => state.selected = Some(0),
+            Some(Action::Edit) => state.selected = Some(0),
+            // Переключение точечного режима
+            Some(Action::Scatter) => {
+                state.scatter = !state.scatter;
+                state.was_edited = true;
+                state.update_datasets();
+            }
+            // Переключение панели статистики
+            Some(Action::Statistics) => {
+                state.show_statistics = !state.show_statistics;
+                state.was_edited = true;
+                state.update_datasets();
+            }
+            // Переключение разрывов линий на пропусках данных
+            Some(Action::GapBreaks) => {
+                state.gap_breaks = !state.gap_breaks;
+                state.was_edited = true;
+                state.update_datasets();
+            }
+            // Переключение исключения выбросов из графиков
+            Some(Action::ExcludeOutliers) => {
+                state.exclude_outliers = !state.exclude_outliers;
+                state.was_edited = true;
+                state.update_datasets();
+            }
+            // Переключение между значениями с поправками приборов и исходными
+            Some(Action::Calibrated) => {
+                state.calibrated = !state.calibrated;
+                state.was_edited = true;
+                state.update_datasets();
+            }
+            // Сохранение и открытие видов
+            Some(Action::SaveView) => self.open_views(true),
+            Some(Action::OpenViews) => self.open_views(false),
+            // Добавление или изменение метки события в точке курсора
+            Some(Action::Annotation) => self.open_annotation(),
+            // Управление курсором на графике
+            Some(Action::FastLeft) => state.cursor_prev(10),
+            Some(Action::FastRight) => state.cursor_next(10),
+            Some(Action::Left) => state.cursor_prev(1),
+            Some(Action::Right) => state.cursor_next(1),
+            Some(Action::Home) => state.cursor_prev(usize::MAX),
+            Some(Action::End) => state.cursor_next(usize::MAX),
+            Some(Action::ClearCursor) => state.cursor = None,
+            // Справка по клавишам
+            Some(Action::Help) => self.open_help(KeyContext::Graph),
 
             _ => (),
         }
     }
 
+    /// Обрабатывает все события, связанные с мышкой во вкладке графика
+    pub fn on_mouse_event_graph(&mut self, event: MouseEvent) {
+        // Получаем состояние вкладки графика
+        let state = self.graph_state_mut();
+
+        // Окна видов и меток перекрывают вкладку
+        if state.views_state.is_some() || state.annotation_state.is_some() {
+            return;
+        }
+
+        // Если открыто меню, сначала мышкой управляется оно
+        let menu_opened = match state.selected.map(|_| state.selected_field_state()) {
+            Some(GraphFieldState::Menu(menu_state)) => menu_state.opened(),
+            _ => false,
+        };
+        if menu_opened && self.on_mouse_event_graph_menu(event) {
+            return;
+        }
+
+        // Нажатие на поле графика открывает его редактирование
+        let state = self.graph_state_mut();
+        let field = state.field_at(event.column, event.row);
+        if let (MouseEventKind::Down(MouseButton::Left), Some(index)) = (event.kind, field) {
+            self.open_graph_field(index);
+            return;
+        }
+
+        match event.kind {
+            // Ставим курсор на место нажатия (в режиме редактирования график не отображается)
+            MouseEventKind::Down(MouseButton::Left) | MouseEventKind::Drag(MouseButton::Left)
+                if state.selected.is_none() =>
+            {
+                // Проверяем, что нажатие было внутри графика
+                let area = state.chart_area;
+                if !area_contains(area, event.column, event.row) {
+                    return;
+                }
+
+                // Переводим положение мыши в значение X
+                let ((x_min, x_max), _) = state.dataset_ranges;
+                let ratio = (event.column - area.x) as f64 / area.width.max(1) as f64;
+                state.move_cursor_to(x_min + ratio * (x_max - x_min));
+            }
+            // Убираем курсор
+            MouseEventKind::Down(MouseButton::Right) if state.selected.is_none() => {
+                state.cursor = None
+            }
+
+            _ => (),
+        }
+    }
+
+    /// Обрабатывает все события, связанные с мышкой в открытом меню.
+    /// Возвращает `false`, если нажатие было вне меню (тогда меню закрывается)
+    fn on_mouse_event_graph_menu(&mut self, event: MouseEvent) -> bool {
+        // Получаем состояние вкладки графика
+        let state = self.graph_state_mut();
+        let selected = state.selected.unwrap();
+
+        // Элементы открытого меню и область, в которой оно отрисовано
+        let items = state.get_menu_labels(selected);
+        let field_area = state
+            .fields_areas
+            .iter()
+            .find(|&&(i, _)| i == selected)
+            .map(|&(_, area)| area)
+            .unwrap_or_default();
+        let list_area = Rect {
+            y: field_area.y + 1,
+            height: MENU_HEIGHT,
+            ..field_area
+        };
+
+        // Получаем состояние открытого меню
+        let menu_state = state.selected_menu_state_mut();
+        let length = menu_state.matches(&items).len();
+
+        match event.kind {
+            // Прокрутка вариантов колёсиком
+            MouseEventKind::ScrollUp => menu_state.prev(length),
+            MouseEventKind::ScrollDown => menu_state.next(length),
+            // Выбор элемента нажатием на него
+            MouseEventKind::Down(MouseButton::Left)
+                if area_contains(list_area, event.column, event.row) =>
+            {
+                let row = (event.row - list_area.y) as usize;
+                if menu_state.click(row, &items) {
+                    self.apply_graph_field();
+                }
+            }
+            // Нажатие на само поле меню закрывает его
+            MouseEventKind::Down(MouseButton::Left)
+                if area_contains(field_area, event.column, event.row) =>
+            {
+                menu_state.close()
+            }
+            // Нажатие вне меню тоже его закрывает, но дальше обрабатывается как обычно
+            MouseEventKind::Down(_) => {
+                menu_state.close();
+                return false;
+            }
+
+            _ => (),
+        }
+
+        true
+    }
+
+    /// Выделяет поле графика с данным индексом и открывает его редактирование
+    fn open_graph_field(&mut self, index: usize) {
+        // Открытое поле ввода закрываем так же, как по Enter, чтобы не потерять изменения
+        let input_opened = match self.graph_state().selected {
+            Some(selected) => matches!(
+                self.graph_state().field_state(selected),
+                GraphFieldState::Input(input_state) if input_state.opened()
+            ),
+            None => false,
+        };
+        if input_opened {
+            // В уже открытом поле ввода ничего не делаем
+            if self.graph_state().selected == Some(index) {
+                return;
+            }
+            self.graph_state_mut().selected_input_state_mut().close();
+            self.apply_graph_field();
+        }
+
+        // Выделяем поле и открываем его
+        let state = self.graph_state_mut();
+        state.selected = Some(index);
+        match state.selected_field_state_mut() {
+            GraphFieldState::Input(input_state) => input_state.open(),
+            GraphFieldState::Menu(menu_state) => menu_state.open(),
+            GraphFieldState::Hidden => panic!("Скрытое поле не должно быть выделено"),
+        }
+    }
+
     /// Обрабатывает все события, связанные с нажатием клавиш в режиме редактирования
     fn on_key_event_graph_edit(&mut self, event: KeyEvent) {
+        // Действие, назначенное на клавишу в режиме редактирования
+        let action = self.keymap.action(KeyContext::GraphEdit, event);
+
         // Получаем состояние вкладки графика
         let state = self.graph_state_mut();
 
-        match event.code {
+        match action {
             // Выход из режима редактирования
-            KeyCode::Esc | KeyCode::Char('q') => {
+            Some(Action::Close) => {
                 // Выключаем режим редактирования
                 state.selected = None;
                 // Обновляем датасеты
@@ -68,21 +292,21 @@ impl App<'_> {
             }
 
             // Управление графиками
-            KeyCode::Char('a') => self.add_graph(),
-            KeyCode::Char('d') => self.remove_graph(),
+            Some(Action::AddGraph) => self.add_graph(),
+            Some(Action::RemoveGraph) => self.remove_graph(),
             // Переключение между возможными полями
-            KeyCode::BackTab => state.select_prev(1),
-            KeyCode::Tab => state.select_next(1),
-            KeyCode::Up => state.select_prev(4),
-            KeyCode::Down => state.select_next(4),
-            KeyCode::Left => state.select_prev(1),
-            KeyCode::Right => state.select_next(1),
+            Some(Action::Up) => state.select_prev(ROW_LENGTH),
+            Some(Action::Down) => state.select_next(ROW_LENGTH),
+            Some(Action::Left) => state.select_prev(1),
+            Some(Action::Right) => state.select_next(1),
             // Открытие редактирование поля
-            KeyCode::Enter => match state.selected_field_state_mut() {
+            Some(Action::Open) => match state.selected_field_state_mut() {
                 GraphFieldState::Input(input_state) => input_state.open(),
                 GraphFieldState::Menu(menu_state) => menu_state.open(),
                 GraphFieldState::Hidden => panic!("Скрытое поле не должно быть выделено"),
             },
+            // Справка по клавишам
+            Some(Action::Help) => self.open_help(KeyContext::GraphEdit),
 
             _ => (),
         }
@@ -90,26 +314,28 @@ impl App<'_> {
 
     /// Обрабатывает все события, связанные с нажатием клавиш в режиме редактирования текста
     fn on_key_event_graph_input(&mut self, event: KeyEvent) {
+        // Действие, назначенное на клавишу в поле ввода (символы без действия вводятся в поле)
+        let action = self.keymap.action(KeyContext::GraphInput, event);
+
         // Получаем состояние открытого поля ввода
         let state = self.graph_state_mut().selected_input_state_mut();
 
-        match event.code {
+        match (action, event.code) {
             // Выход из поля ввода
-            KeyCode::Esc | KeyCode::Enter => {
+            (Some(Action::Close), _) => {
                 state.close();
-                // Обновляем поля графиков
-                self.update_graph_field(self.graph_state().selected.unwrap());
-                // Обновляем датасеты
-                self.graph_state_mut().was_edited = true;
+                self.apply_graph_field();
             }
             // Навигация в поле ввода
-            KeyCode::Home => state.goto_start(),
-            KeyCode::End => state.goto_end(),
-            KeyCode::Left => state.goto_prev(),
-            KeyCode::Right => state.goto_next(),
+            (Some(Action::Home), _) => state.goto_start(),
+            (Some(Action::End), _) => state.goto_end(),
+            (Some(Action::Left), _) => state.goto_prev(),
+            (Some(Action::Right), _) => state.goto_next(),
             // Ввод в поле
-            KeyCode::Char(ch) => state.insert(ch),
-            KeyCode::Backspace => state.remove(),
+            (None, KeyCode::Char(ch)) => state.insert(ch),
+            (Some(Action::Erase), _) => state.remove(),
+            // Справка по клавишам
+            (Some(Action::Help), _) => self.open_help(KeyContext::GraphInput),
 
             _ => (),
         }
@@ -117,51 +343,51 @@ impl App<'_> {
 
     /// Обрабатывает все события, связанные с нажатием клавиш в режиме редактирования меню
     fn on_key_event_graph_menu(&mut self, event: KeyEvent) {
+        // Действие, назначенное на клавишу в меню (символы без действия идут в строку поиска)
+        let action = self.keymap.action(KeyContext::GraphMenu, event);
+
         // Получаем состояние вкладки графика
         let state = self.graph_state_mut();
 
-        // Количество элементов в открытом меню
-        let length = match state.selected.unwrap() {
-            // Поля данных X
-            0 => state.x_data_fields.len(),
-
-            // Поля данных Y
-            v if v % 4 == 0 => state.y_data_fields.len(),
-            // Поля серийников
-            v if v % 4 == 1 => state.get_serial_fields_for_sensor(v).len(),
-
-            // Поля дополнительных данных Y (основное поле гарантированно установлено)
-            v => {
-                // Получаем основное поле данных Y
-                let data_field = state.ys_states[v / 4 - 1][0].menu().unwrap();
-                let selected = data_field.selected().unwrap();
-                let (sensor, _) = state.y_data_fields[selected].split_once('/').unwrap();
-                state.y_data_fields_without_extra[sensor].len()
-            }
-        };
+        // Элементы открытого меню и количество тех, что подходят под строку поиска
+        let items = state.get_menu_labels(state.selected.unwrap());
 
         // Получаем состояние открытого меню
         let menu_state = state.selected_menu_state_mut();
+        let length = menu_state.matches(&items).len();
 
-        match event.code {
+        match (action, event.code) {
             // Выход из меню без сохранения
-            KeyCode::Esc | KeyCode::Char('q') => menu_state.close(),
+            (Some(Action::Close), _) => menu_state.close(),
             // Переключение вариантов
-            KeyCode::Up => menu_state.prev(length),
-            KeyCode::Down => menu_state.next(length),
-            // Выбор элемента в меню
-            KeyCode::Enter => {
-                menu_state.select();
-                // Обновляем поля графиков
-                self.update_graph_field(self.graph_state().selected.unwrap());
-                // Обновляем датасеты
-                self.graph_state_mut().was_edited = true;
+            (Some(Action::Up), _) => menu_state.prev(length),
+            (Some(Action::Down), _) => menu_state.next(length),
+            // Поиск по элементам меню
+            (None, KeyCode::Char(ch)) => menu_state.insert(ch),
+            (Some(Action::Erase), _) => menu_state.remove(),
+            // Справка по клавишам
+            (Some(Action::Help), _) => self.open_help(KeyContext::GraphMenu),
+            // Выбор элемента в меню (если под поиск ничего не подошло, то выбирать нечего)
+            (Some(Action::Select), _) => {
+                if !menu_state.select(&items) {
+                    return;
+                }
+                self.apply_graph_field();
             }
 
             _ => (),
         }
     }
 
+    /// Применяет изменение выделенного поля графика
+    fn apply_graph_field(&mut self) {
+        // Обновляем поля графиков
+        self.update_graph_field(self.graph_state().selected.unwrap());
+        // Обновляем датасеты, а устаревшую загрузку отменяем
+        self.graph_state_mut().was_edited = true;
+        self.graph_state_mut().cancel_loading();
+    }
+
     /// Добавляет новую функцию Y(x) на график
     pub fn add_graph(&mut self) {
         // Добавляем пустые параметры к графику
@@ -183,6 +409,63 @@ impl App<'_> {
         }
     }
 
+    /// Заменяет конфигурацию вкладки графика на сохранённый вид
+    pub fn apply_graph_view(&mut self, view: &GraphView) {
+        // Начинаем с пустой вкладки с нужным количеством графиков
+        let state = self.graph_state_mut();
+        state.x_states = GraphState::default_graph();
+        state.ys_states = (0..view.ys.len().max(1))
+            .map(|_| GraphState::default_graph())
+            .collect();
+        state.scatter = view.scatter;
+        state.show_statistics = view.statistics;
+        state.gap_breaks = view.gap_breaks;
+        state.exclude_outliers = view.exclude_outliers;
+        state.calibrated = view.calibrated;
+        state.selected = None;
+        state.cursor = None;
+
+        // Заполняем поля по порядку, как если бы их выбирал пользователь,
+        // т.к. от выбора одних полей зависит появление других
+        let rows = std::iter::once(&view.x).chain(&view.ys);
+        for (row, values) in rows.enumerate() {
+            for (col, value) in values.iter().enumerate().take(ROW_LENGTH) {
+                let Some(value) = value else {
+                    continue;
+                };
+
+                let index = row * ROW_LENGTH + col;
+                let state = self.graph_state_mut();
+                match state.field_state(index) {
+                    GraphFieldState::Menu(_) => {
+                        // Поля, которых больше нет в базе данных, пропускаем
+                        let items = state.get_menu_items(index);
+                        let Some(i) = items.iter().position(|item| item == value) else {
+                            continue;
+                        };
+                        state
+                            .field_state_mut(index)
+                            .menu_mut()
+                            .unwrap()
+                            .set_select(Some(i));
+                    }
+                    GraphFieldState::Input(_) => {
+                        let input_state = state.field_state_mut(index).input_mut().unwrap();
+                        input_state.set_content(value.clone());
+                    }
+                    GraphFieldState::Hidden => continue,
+                }
+                self.update_graph_field(index);
+            }
+        }
+
+        // Загружаем данные нового вида
+        let state = self.graph_state_mut();
+        state.was_edited = true;
+        state.cancel_loading();
+        state.update_datasets();
+    }
+
     /// Обрабатывает поля графика
     pub fn update_graph_field(&mut self, selected: usize) {
         // Получаем состояние вкладки графика
@@ -198,41 +481,37 @@ impl App<'_> {
                 // Получаем значение поля
                 let value = &state.x_data_fields[field_state.selected().unwrap()];
                 // Проверяем, является ли это полем датчика
-                if let Some((sensor, _)) = value.split_once('/') {
+                if value.contains('/') {
                     // Включаем поле серийника, заодно сбрасываем
                     state.x_states[1] = GraphFieldState::new_menu();
                     to_update.push(1);
 
-                    // Если является полем датчика, проверяем, что все графики тоже используют поле этого тачика
-                    for (i, y_fields) in state.ys_states.iter_mut().enumerate() {
-                        // Получаем состояние поля
-                        let y_sensor_field_state = y_fields[0].menu_mut().unwrap();
-                        // Если в поле что-то выбрано, отсматриваем что
-                        if let Some(selection_index) = y_sensor_field_state.selected() {
-                            // Если поле не начинается на название сенсора, сбрасываем значение
-                            let value = &state.y_data_fields[selection_index];
-                            if !value.starts_with(sensor) {
-                                // Обновляем поле графика (чуть позже оно будет сброшено)
-                                to_update.push((i + 1) * 4);
-                            } else {
-                                // Необходимо убрать серийник у поля, если уж не обновляем поле
-                                y_fields[1] = GraphFieldState::Hidden;
-                            }
-                        }
+                    // Все графики берут серийник у X, поэтому убираем серийники и эталоны у Y
+                    for y_fields in state.ys_states.iter_mut() {
+                        y_fields[1] = GraphFieldState::Hidden;
+                        y_fields[8] = GraphFieldState::Hidden;
                     }
+
+                    // Записи Y других датчиков по умолчанию совмещаются по точной дате
+                    state.x_states[4] = GraphFieldState::new_menu_selected(0);
                 } else {
                     // Выключаем поле серийника
                     state.x_states[1] = GraphFieldState::Hidden;
                     to_update.push(1);
 
-                    // Включаем поля серийника на всех установленных Y
+                    // Включаем поля серийника и эталона на всех установленных Y
                     for y_fields in state.ys_states.iter_mut() {
                         if y_fields[0].menu().unwrap().selected().is_some() {
-                            // Устанавливаем поле. Обновлять его не нужно
+                            // Устанавливаем поля. Обновлять их не нужно
                             y_fields[1] = GraphFieldState::new_menu();
+                            y_fields[8] = GraphFieldState::new_menu_selected(0);
                         }
                     }
+
+                    // Включаем группировку по времени (по умолчанию данные не группируются)
+                    state.x_states[4] = GraphFieldState::new_menu_selected(0);
                 }
+                to_update.push(4);
 
                 // Также мы должны обновить поля мин/макс значения
                 state.x_states[2] = GraphFieldState::new_input();
@@ -252,10 +531,50 @@ impl App<'_> {
             // Обновилось максимальное значение X
             3 => {}
 
+            // Обновилась группировка по времени или способ совмещения записей
+            4 => {
+                let selection = state.x_states[4].menu().and_then(MenuState::selected);
+                if state.is_x_date() {
+                    // Значение в промежутке выбирается, только если данные группируются
+                    match (&state.x_states[5], selection) {
+                        (GraphFieldState::Hidden, Some(i)) if i > 0 => {
+                            state.x_states[5] = GraphFieldState::new_menu_selected(0);
+                        }
+                        (_, Some(0) | None) => state.x_states[5] = GraphFieldState::Hidden,
+                        _ => (),
+                    }
+                } else {
+                    // Допуск или шаг сетки вводится, только если он нужен способу совмещения
+                    let join = TimestampJoin::ALL[selection.unwrap_or(0)];
+                    match (&state.x_states[5], join.has_seconds()) {
+                        (GraphFieldState::Input(_), true) => (),
+                        (_, true) => state.x_states[5] = GraphFieldState::new_input(),
+                        (_, false) => state.x_states[5] = GraphFieldState::Hidden,
+                    }
+                }
+                to_update.push(5);
+            }
+
+            // Обновилось значение, которое берётся в промежутке, или допуск совмещения
+            5 => {
+                // Проверяем допуск, чтобы сразу показать ошибку в поле
+                if let Some(input_state) = state.x_states[5].input_mut() {
+                    let error = match input_state.content().parse::<i64>() {
+                        _ if input_state.content().is_empty() => None,
+                        Ok(seconds) if seconds > 0 => None,
+                        _ => Some(tr("Ожидалось целое положительное число секунд").to_owned()),
+                    };
+                    input_state.set_error(error);
+                }
+            }
+
+            // У X нет фильтра сглаживания и эталона, эти поля всегда скрыты
+            6..=8 => {}
+
             // Обновилось поле данных Y
-            v if v % 4 == 0 => {
+            v if v % ROW_LENGTH == 0 => {
                 // Получаем индекс графика
-                let y_index = v / 4 - 1;
+                let y_index = v / ROW_LENGTH - 1;
                 // Получаем изменяемую ссылку на этот график
                 let y_fields = &mut state.ys_states[y_index];
                 // Получаем состояние поля этого графика
@@ -264,50 +583,111 @@ impl App<'_> {
                 if let Some(selection_index) = field_state.selected() {
                     // Получаем значение поля
                     let value = &state.y_data_fields[selection_index];
-                    // Получаем название датчика и поле
-                    let (_, field) = value.split_once('/').unwrap();
+                    // Получаем название датчика и поле (у выражения их нет)
+                    let (_, field) = value.split_once('/').unwrap_or_default();
 
                     // Проверяем, нужно ли отобразить серийник. Для этого смотрим, есть ли серийник у X
                     to_update.push(v + 1);
                     if let GraphFieldState::Hidden = state.x_states[1] {
-                        // Ставим второе поле серийником
+                        // Ставим второе поле серийником, а эталон сбрасываем, т.к. у датчика свои приборы
                         y_fields[1] = GraphFieldState::new_menu();
+                        y_fields[8] = GraphFieldState::new_menu_selected(0);
                     } else {
-                        // Ставим второе поле пустым
+                        // Ставим второе поле и эталон пустыми
                         y_fields[1] = GraphFieldState::Hidden;
+                        y_fields[8] = GraphFieldState::Hidden;
                     }
 
-                    // Проверяем на "особые поля"
-                    match field {
-                        "Эфф. темп." => {
-                            // Для эффективной температуры, последние два поля - датчики
-                            y_fields[2] = GraphFieldState::new_menu();
-                            y_fields[3] = GraphFieldState::new_menu();
-                            to_update.extend([v + 2, v + 3]);
+                    if value == EXPRESSION_FIELD {
+                        // Для выражения третье поле - ввод текста самого выражения
+                        y_fields[2] = GraphFieldState::new_input();
+                        y_fields[3] = GraphFieldState::Hidden;
+                    } else {
+                        // Для производных величин показываем столько полей, сколько у них входных данных
+                        let inputs =
+                            find_derived_field(field).map_or(0, |field| field.inputs.len());
+                        for k in 0..MAX_INPUTS {
+                            y_fields[2 + k] = match k < inputs {
+                                true => GraphFieldState::new_menu(),
+                                false => GraphFieldState::Hidden,
+                            };
                         }
-                        _ => {
-                            // Для всех других полей последние два поля - пустые
-                            y_fields[2] = GraphFieldState::Hidden;
-                            y_fields[3] = GraphFieldState::Hidden;
-                            to_update.extend([v + 2, v + 3]);
+                    }
+                    to_update.extend([v + 2, v + 3]);
+
+                    // Ось, нормализацию и фильтр оставляем прежними, если они уже были выбраны
+                    for k in [4, 5, 6] {
+                        if let GraphFieldState::Hidden = y_fields[k] {
+                            y_fields[k] = GraphFieldState::new_menu_selected(0);
                         }
                     }
                 } else {
                     // Если не выбрано, скрываем все возможные поля
-                    y_fields[1] = GraphFieldState::Hidden;
-                    y_fields[2] = GraphFieldState::Hidden;
-                    y_fields[3] = GraphFieldState::Hidden;
+                    for y_field in &mut y_fields[1..] {
+                        *y_field = GraphFieldState::Hidden;
+                    }
                     to_update.extend([v + 1, v + 2, v + 3]);
                 }
+                to_update.push(v + 6);
             }
 
             // Обновился серийник Y
-            v if v % 4 == 1 => {}
+            v if v % ROW_LENGTH == 1 => {}
+
+            // Обновилось входное поле производной величины или выражение
+            v if v % ROW_LENGTH == 2 => {
+                // Проверяем выражение, чтобы сразу показать ошибку в поле
+                let y_fields = &state.ys_states[v / ROW_LENGTH - 1];
+                if let Some(input_state) = y_fields[2].input() {
+                    let error = match input_state.content() {
+                        "" => None,
+                        text => state.parse_expression(text).err(),
+                    };
+                    let input_state = state.ys_states[v / ROW_LENGTH - 1][2].input_mut();
+                    input_state.unwrap().set_error(error);
+                }
+            }
+
+            // Обновилось второе входное поле производной величины
+            v if v % ROW_LENGTH == 3 => {}
+
+            // Обновилась ось Y графика
+            v if v % ROW_LENGTH == 4 => {}
+
+            // Обновилась нормализация графика
+            v if v % ROW_LENGTH == 5 => {}
 
-            // Обновился температурный датчик
-            v if v % 4 == 2 => {}
+            // Обновился фильтр сглаживания графика
+            v if v % ROW_LENGTH == 6 => {
+                // Окно вводится, только если фильтр выбран (первый вариант - без фильтра)
+                let y_fields = &mut state.ys_states[v / ROW_LENGTH - 1];
+                let selection = y_fields[6].menu().and_then(MenuState::selected);
+                match (&y_fields[7], selection) {
+                    (GraphFieldState::Input(_), Some(i)) if i > 0 => (),
+                    (_, Some(i)) if i > 0 => y_fields[7] = GraphFieldState::new_input(),
+                    _ => y_fields[7] = GraphFieldState::Hidden,
+                }
+                to_update.push(v + 1);
+            }
+
+            // Обновилось окно фильтра
+            v if v % ROW_LENGTH == 7 => {
+                // Проверяем окно, чтобы сразу показать ошибку в поле
+                let is_x_date = state.is_x_date();
+                if let Some(input_state) = state.ys_states[v / ROW_LENGTH - 1][7].input_mut() {
+                    let error = match SmoothingWindow::parse(input_state.content()) {
+                        _ if input_state.content().trim().is_empty() => None,
+                        Ok(SmoothingWindow::Seconds(_)) if !is_x_date => {
+                            Some(tr("Окно во времени доступно, только если X - дата").to_owned())
+                        }
+                        Ok(_) => None,
+                        Err(error) => Some(error),
+                    };
+                    input_state.set_error(error);
+                }
+            }
 
-            // Обновился датчик давления
+            // Обновился эталонный прибор графика
             _v => {}
         }
 
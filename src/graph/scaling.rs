@@ -0,0 +1,122 @@
+use rusqlite::OptionalExtension;
+
+/// Перечисляемый тип, определяющий ось Y, по которой отображается график
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum YAxis {
+    #[default]
+    Left,
+    Right,
+}
+
+impl YAxis {
+    /// Все возможные оси в порядке отображения в меню
+    pub const ALL: [Self; 2] = [Self::Left, Self::Right];
+
+    /// Возвращает название оси для отображения
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Left => "Левая",
+            Self::Right => "Правая",
+        }
+    }
+}
+
+/// Перечисляемый тип, определяющий то, как нормализуются значения графика
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Normalization {
+    /// Стандартизация: (y - среднее) / станд. откл.
+    ZScore,
+    /// Приведение к промежутку от 0 до 1
+    MinMax,
+    /// Процент от первого значения
+    PercentOfFirst,
+}
+
+impl Normalization {
+    /// Все возможные нормализации в порядке отображения в меню
+    pub const ALL: [Self; 3] = [Self::ZScore, Self::MinMax, Self::PercentOfFirst];
+
+    /// Возвращает название нормализации для отображения
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ZScore => "Z-оценка",
+            Self::MinMax => "От 0 до 1",
+            Self::PercentOfFirst => "% от первого",
+        }
+    }
+
+    /// Возвращает сдвиг и масштаб, после которых значения будут нормализованы. Считается в SQL
+    /// по всем записям графика (`from` - часть запроса с источником и фильтрами), а не по прореженным точкам.
+    /// Первое значение ищется по порядку `order`, причём нулевые значения пропускаются, т.к. процент от них
+    /// не определён. Возвращает `None`, если подходящих значений нет
+    pub fn query(
+        &self,
+        database: &rusqlite::Connection,
+        value: &str,
+        from: &str,
+        order: &str,
+    ) -> rusqlite::Result<Option<(f64, f64)>> {
+        let values = format!("(SELECT {order} AS x, {value} AS y {from}) WHERE y IS NOT NULL");
+        let sql = match self {
+            Self::ZScore => format!("SELECT AVG(y), stddev(y) FROM {values}"),
+            Self::MinMax => format!("SELECT MIN(y), MAX(y) - MIN(y) FROM {values}"),
+            Self::PercentOfFirst => {
+                format!("SELECT 0., y / 100. FROM {values} AND y != 0 ORDER BY x LIMIT 1")
+            }
+        };
+
+        let row: Option<(Option<f64>, Option<f64>)> = database
+            .query_row(&sql, (), |row| Ok((row.get(0)?, row.get(1)?)))
+            .optional()?;
+        Ok(match row {
+            // Если масштаб нулевой (или его нет у единственного значения), то значения только сдвигаем
+            Some((Some(shift), scale)) => {
+                Some((shift, scale.filter(|&scale| scale != 0.).unwrap_or(1.)))
+            }
+            _ => None,
+        })
+    }
+
+    /// Возвращает сдвиг и масштаб так же, как `query`, но по уже загруженным точкам, отсортированным по X
+    /// (например, по сглаженным значениям всех записей)
+    pub fn compute(&self, points: &[(f64, f64)]) -> Option<(f64, f64)> {
+        let mut values = points.iter().map(|&(_, y)| y);
+        let (shift, scale) = match self {
+            Self::ZScore if !points.is_empty() => {
+                let count = points.len() as f64;
+                let mean = values.clone().sum::<f64>() / count;
+                let squares: f64 = values.map(|y| (y - mean).powi(2)).sum();
+                (mean, (squares / (count - 1.)).sqrt())
+            }
+            Self::MinMax if !points.is_empty() => {
+                let min = values.clone().fold(f64::MAX, f64::min);
+                (min, values.fold(f64::MIN, f64::max) - min)
+            }
+            Self::PercentOfFirst => (0., values.find(|&y| y != 0.)? / 100.),
+            _ => return None,
+        };
+
+        // Как и в `query`, нулевой или неопределённый масштаб заменяется на единицу
+        Some((
+            shift,
+            Some(scale).filter(|scale| scale.is_normal()).unwrap_or(1.),
+        ))
+    }
+
+    /// Нормализует значения Y набора точек по сдвигу и масштабу
+    pub fn apply(points: &mut [(f64, f64)], (shift, scale): (f64, f64)) {
+        for (_, y) in points {
+            *y = (*y - shift) / scale;
+        }
+    }
+}
+
+/// Переводит значение из одного диапазона в другой (например, с правой оси Y на левую)
+pub fn rescale(value: f64, from: (f64, f64), to: (f64, f64)) -> f64 {
+    let span = from.1 - from.0;
+    if span == 0. {
+        return (to.0 + to.1) / 2.;
+    }
+
+    to.0 + (value - from.0) / span * (to.1 - to.0)
+}
@@ -0,0 +1,207 @@
+use crate::locale::{tr, trf};
+
+/// Окно фильтра по умолчанию (в точках), если оно не введено
+pub const DEFAULT_SMOOTHING_WINDOW: SmoothingWindow = SmoothingWindow::Samples(5);
+
+/// Перечисляемый тип, определяющий то, как сглаживается график
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Smoothing {
+    /// Скользящее среднее
+    MovingAverage,
+    /// Скользящая медиана
+    RollingMedian,
+    /// Экспоненциальное скользящее среднее
+    Exponential,
+}
+
+/// Длина окна фильтра: в точках графика или во времени (в секундах)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SmoothingWindow {
+    Samples(usize),
+    Seconds(f64),
+}
+
+impl Smoothing {
+    /// Все возможные фильтры в порядке отображения в меню
+    pub const ALL: [Self; 3] = [Self::MovingAverage, Self::RollingMedian, Self::Exponential];
+
+    /// Возвращает название фильтра для отображения
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::MovingAverage => "Скольз. среднее",
+            Self::RollingMedian => "Скольз. медиана",
+            Self::Exponential => "Эксп. среднее",
+        }
+    }
+
+    /// Сглаживает значения Y набора точек, отсортированных по X
+    pub fn apply(&self, points: &mut [(f64, f64)], window: SmoothingWindow) {
+        if points.len() < 2 {
+            return;
+        }
+
+        let smoothed: Vec<f64> = match self {
+            // Экспоненциальное среднее учитывает только прошлые точки
+            Self::Exponential => {
+                let mut average = points[0].1;
+                points
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &(x, y))| {
+                        // Вес новой точки зависит от окна: в точках - постоянный, во времени - от шага по X
+                        let alpha = match window {
+                            SmoothingWindow::Samples(samples) => 2. / (samples as f64 + 1.),
+                            SmoothingWindow::Seconds(seconds) => {
+                                let step = x - points[i.saturating_sub(1)].0;
+                                1. - (-step / seconds).exp()
+                            }
+                        };
+                        average += alpha * (y - average);
+                        average
+                    })
+                    .collect()
+            }
+            // Скользящие фильтры берут окно с центром в точке, чтобы график не сдвигался
+            Self::MovingAverage | Self::RollingMedian => (0..points.len())
+                .map(|i| {
+                    let (start, end) = window_bounds(points, i, window);
+                    let mut values: Vec<_> = points[start..end].iter().map(|&(_, y)| y).collect();
+                    match self {
+                        Self::MovingAverage => values.iter().sum::<f64>() / values.len() as f64,
+                        _ => {
+                            values.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+                            let middle = values.len() / 2;
+                            match values.len() % 2 {
+                                0 => (values[middle - 1] + values[middle]) / 2.,
+                                _ => values[middle],
+                            }
+                        }
+                    }
+                })
+                .collect(),
+        };
+
+        for ((_, y), smoothed) in points.iter_mut().zip(smoothed) {
+            *y = smoothed;
+        }
+    }
+
+    /// Сглаживает каждый участок между разрывами отдельно, чтобы фильтр не тянул значения через пропуск.
+    /// Разрывы `breaks` - значения X, с которых начинается новый участок (например, концы пропусков)
+    pub fn apply_split(&self, points: &mut [(f64, f64)], window: SmoothingWindow, breaks: &[f64]) {
+        let mut splits: Vec<_> = breaks
+            .iter()
+            .map(|&x| points.partition_point(|&(px, _)| px < x))
+            .collect();
+        splits.sort_unstable();
+        splits.dedup();
+
+        let (mut rest, mut offset) = (points, 0);
+        for split in splits {
+            let (segment, tail) = rest.split_at_mut(split - offset);
+            self.apply(segment, window);
+            (rest, offset) = (tail, split);
+        }
+        self.apply(rest, window);
+    }
+}
+
+impl SmoothingWindow {
+    /// Разбирает окно из строки: число точек (`15`) или время с единицей (`30s`, `10m`, `2h`, `1d`)
+    pub fn parse(text: &str) -> Result<Self, String> {
+        const ERROR: &str = "Ожидалось число точек или время (например, 30s, 10m, 2h, 1d)";
+
+        let text = text.trim();
+        let unit = match text.chars().last() {
+            Some('s') => 1.,
+            Some('m') => 60.,
+            Some('h') => 3600.,
+            Some('d') => 86400.,
+            _ => {
+                return match text.parse::<usize>() {
+                    Ok(samples) if samples > 0 => Ok(Self::Samples(samples)),
+                    _ => Err(tr(ERROR).to_owned()),
+                }
+            }
+        };
+
+        match text[..text.len() - 1].parse::<f64>() {
+            Ok(value) if value > 0. => Ok(Self::Seconds(value * unit)),
+            _ => Err(tr(ERROR).to_owned()),
+        }
+    }
+}
+
+impl std::fmt::Display for SmoothingWindow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Samples(samples) => f.write_str(&trf("{} т.", &[samples])),
+            Self::Seconds(seconds) => f.write_str(&trf("{} с", &[seconds])),
+        }
+    }
+}
+
+/// Возвращает границы окна [начало, конец) с центром в точке с индексом `i`
+fn window_bounds(points: &[(f64, f64)], i: usize, window: SmoothingWindow) -> (usize, usize) {
+    match window {
+        SmoothingWindow::Samples(samples) => {
+            let start = i.saturating_sub((samples - 1) / 2);
+            let end = (i + samples / 2 + 1).min(points.len());
+            (start, end)
+        }
+        SmoothingWindow::Seconds(seconds) => {
+            // Точки отсортированы по X, поэтому границы можно найти двоичным поиском
+            let x = points[i].0;
+            let start = points.partition_point(|&(px, _)| px < x - seconds / 2.);
+            let end = points.partition_point(|&(px, _)| px <= x + seconds / 2.);
+            (start, end)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_windows() {
+        assert_eq!(
+            SmoothingWindow::parse("15"),
+            Ok(SmoothingWindow::Samples(15))
+        );
+        assert_eq!(
+            SmoothingWindow::parse("30s"),
+            Ok(SmoothingWindow::Seconds(30.))
+        );
+        assert_eq!(
+            SmoothingWindow::parse(" 10m "),
+            Ok(SmoothingWindow::Seconds(600.))
+        );
+        assert_eq!(
+            SmoothingWindow::parse("1.5h"),
+            Ok(SmoothingWindow::Seconds(5400.))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_windows() {
+        for text in ["0", "0s", "m", "", "-5", "5x", "abc"] {
+            assert!(SmoothingWindow::parse(text).is_err(), "{text:?}");
+        }
+    }
+
+    #[test]
+    fn smooths_segments_separately() {
+        let mut points = [
+            (0., 0.),
+            (1., 0.),
+            (2., 0.),
+            (10., 9.),
+            (11., 9.),
+            (12., 9.),
+        ];
+        Smoothing::MovingAverage.apply_split(&mut points, SmoothingWindow::Samples(3), &[10.]);
+        let values: Vec<_> = points.iter().map(|&(_, y)| y).collect();
+        assert_eq!(values, [0., 0., 0., 9., 9., 9.]);
+    }
+}
@@ -7,12 +7,36 @@ use std::{
 
 use chrono::NaiveDateTime;
 use itertools::Itertools;
+use tui::layout::Rect;
 
 use crate::{
-    database::{SensorsFields, SensorsSerials},
-    ui::{input::InputState, menu::MenuState},
+    annotations::state::AnnotationState,
+    database::{annotations::Annotation, calibration, outliers, SensorsFields, SensorsSerials},
+    locale::{tr, tr_item},
+    ui::{input::InputState, menu::MenuState, utils::area_contains},
+    views::state::ViewsState,
 };
 
+use super::{
+    aggregation::{Statistic, TimeBucket},
+    datasets::{DatasetsLoader, DatasetsRequest, GraphDataset, SeriesRequest},
+    derived::{find_derived_field, DerivedField, DERIVED_FIELDS, MAX_INPUTS},
+    difference::{difference_source, reference_table},
+    expression::{Expression, EXPRESSION_FIELD},
+    join::{TimestampJoin, DEFAULT_JOIN_SECONDS},
+    scaling::{Normalization, YAxis},
+    smoothing::{Smoothing, SmoothingWindow, DEFAULT_SMOOTHING_WINDOW},
+};
+
+/// Псевдо-серийники, которые можно выбрать у Y вместо конкретного прибора
+pub const PSEUDO_SERIALS: [&str; 5] = ["Все", "Разброс", "Средн.", "Мин.", "Макс."];
+
+/// Количество полей в строке параметров X или Y
+pub const ROW_LENGTH: usize = 9;
+
+/// Количество точек по X, до которого прореживаются данные, если размер графика ещё неизвестен
+const DEFAULT_CHART_RESOLUTION: usize = 512;
+
 /// Сохраняет состояние вкладки графика
 #[derive(Debug)]
 pub struct GraphState {
@@ -38,22 +62,73 @@ pub struct GraphState {
     pub serial_fields: SensorsSerials,
 
     /// Содержит параметры для X
-    pub x_states: [GraphFieldState; 4],
+    pub x_states: [GraphFieldState; ROW_LENGTH],
 
     /// Содержит параметры для всех Y
-    pub ys_states: Vec<[GraphFieldState; 4]>,
+    pub ys_states: Vec<[GraphFieldState; ROW_LENGTH]>,
 
     /// Содержит все данные графиков для построения
-    pub datasets: Vec<Vec<Vec<(f64, f64)>>>,
+    pub datasets: Vec<GraphDataset>,
 
     /// Содержит границы данных ((x_min, x_max), (y_min, y_max))
     pub dataset_ranges: ((f64, f64), (f64, f64)),
 
+    /// Содержит флаг того, что X у датасетов является датой
+    pub dataset_x_is_date: bool,
+
+    /// Содержит границы данных (y_min, y_max) по правой оси Y, если на ней есть графики
+    pub dataset_right_range: Option<(f64, f64)>,
+
+    /// Содержит флаг того, что датасеты построены в точечном режиме
+    pub dataset_scatter: bool,
+
+    /// Содержит все различные значения X в датасетах (отсортированы)
+    pub dataset_xs: Vec<f64>,
+
+    /// Содержит метки событий, которые попали на график
+    pub dataset_annotations: Vec<Annotation>,
+
+    /// Содержит индекс значения X, на котором стоит курсор
+    pub cursor: Option<usize>,
+
+    /// Содержит область, в которой был отрисован сам график (без осей)
+    pub chart_area: Rect,
+
+    /// Содержит индексы видимых полей и области, в которых они были отрисованы
+    pub fields_areas: Vec<(usize, Rect)>,
+
+    /// Содержит загрузку датасетов, если она происходит
+    pub loader: Option<DatasetsLoader>,
+
+    /// Содержит ошибку последней загрузки датасетов, если она была
+    pub load_error: Option<String>,
+
     /// Содержит флаг того, что поля обновлялись
     pub was_edited: bool,
 
+    /// Содержит флаг точечного режима (точки Y от X с линией регрессии)
+    pub scatter: bool,
+
+    /// Содержит флаг того, что под графиком отображается панель статистики
+    pub show_statistics: bool,
+
+    /// Содержит флаг того, что линии графиков разрываются на пропусках данных
+    pub gap_breaks: bool,
+
+    /// Содержит флаг того, что помеченные выбросами записи исключаются из графиков
+    pub exclude_outliers: bool,
+
+    /// Содержит флаг того, что к значениям Y применяются поправки приборов
+    pub calibrated: bool,
+
     /// Содержит индекс выделенного виджета
     pub selected: Option<usize>,
+
+    /// Содержит состояние окна сохранённых видов, если оно открыто
+    pub views_state: Option<ViewsState>,
+
+    /// Содержит состояние окна метки события, если оно открыто
+    pub annotation_state: Option<AnnotationState>,
 }
 
 impl GraphState {
@@ -76,11 +151,30 @@ impl GraphState {
             y_data_fields_without_extra: HashMap::new(),
             serial_fields: SensorsSerials::new(),
 
-            datasets: vec![vec![]],
+            datasets: vec![],
             dataset_ranges: Default::default(),
+            dataset_x_is_date: false,
+            dataset_right_range: None,
+            dataset_scatter: false,
+            dataset_xs: vec![],
+            dataset_annotations: vec![],
+            loader: None,
+            load_error: None,
             was_edited: false,
+            scatter: false,
+            show_statistics: false,
+            gap_breaks: false,
+            exclude_outliers: false,
+            calibrated: false,
+
+            cursor: None,
+            chart_area: Rect::default(),
+            fields_areas: vec![],
 
             selected: None,
+
+            views_state: None,
+            annotation_state: None,
         }
     }
 
@@ -143,30 +237,17 @@ impl GraphState {
     }
 
     pub fn update_y_data_fields(&mut self) {
-        const EXTRA_FIELDS: [&str; 1] = ["Эфф. темп."];
-
         // Генерируем поля данных для Y на основе полей X
         let mut new_y_data_fields = self.x_data_fields.clone();
         new_y_data_fields.retain(|field| field.contains('/'));
 
-        // Если X специализирует единственный датчик - убираем все другие датчики
-        if let Some(i) = self.x_states[0].menu().unwrap().selected() {
-            // Получаем данное значение поля данных X
-            let value = &self.x_data_fields[i];
-            // Если поле представляет из себя поле датчика, то убираем лишние из Y
-            if let Some((sensor, _)) = value.split_once('/') {
-                let start = format!("{sensor}/");
-                new_y_data_fields.retain(|field| field.starts_with(&start));
-            }
-        }
-
         // Генерируем поля с дополнительными вариантами
         let mut new_y_data_fields = new_y_data_fields.clone();
         // Дополняем поля к последнему датчику
         let last_field = new_y_data_fields.last().cloned().unwrap();
         let (last_sensor, _) = last_field.split_once('/').unwrap();
-        for extra_field in EXTRA_FIELDS {
-            new_y_data_fields.push(format!("{last_sensor}/{extra_field}"));
+        for extra_field in &DERIVED_FIELDS {
+            new_y_data_fields.push(format!("{last_sensor}/{}", extra_field.name));
         }
 
         // Дополняем новые поля к каждому датчику
@@ -175,12 +256,16 @@ impl GraphState {
             let (sensor_b, _) = window[1].split_once('/').unwrap();
             // Когда два соседних сенсора не равны - это место для вставки дополнительных полей
             if sensor_a != sensor_b {
-                for extra_field in EXTRA_FIELDS {
-                    new_y_data_fields.insert(i + 1, format!("{sensor_a}/{extra_field}"));
+                // Вставляем с конца, чтобы сохранить порядок величин
+                for extra_field in DERIVED_FIELDS.iter().rev() {
+                    new_y_data_fields.insert(i + 1, format!("{sensor_a}/{}", extra_field.name));
                 }
             }
         }
 
+        // Последним идёт поле, значение которого задаётся выражением
+        new_y_data_fields.push(EXPRESSION_FIELD.to_owned());
+
         // Конвертируем все выбранные Y поля на новые индексы
         let index_conversion_map: HashMap<usize, usize> = new_y_data_fields
             .iter()
@@ -209,20 +294,20 @@ impl GraphState {
 
     /// Обновляет серийники датчиков и связанное с ними в графике
     pub fn update_sensor_serials(&mut self) {
-        // TODO: в будущем обдумать это. Вероятнее всего просто убрать
-        // const EXTRA_FIELDS: [&str; 3] = ["Средн.", "Макс.", "Мин."];
-
         // Получаем серийники датчиков
         let sensor_serials_ref = self.sensor_serials.borrow();
-        // Делаем копию, чтобы добавить новые "псевдо-серийники"
-        let serial_fields = sensor_serials_ref.clone();
-
-        // Добавляем дополнительные поля в начало
-        // for (_, fields) in serial_fields.iter_mut() {
-        //     for extra_field in EXTRA_FIELDS.into_iter().rev() {
-        //         fields.insert(0, extra_field.to_owned());
-        //     }
-        // }
+        // Делаем копию, чтобы добавить серийники выражений
+        let mut serial_fields = sensor_serials_ref.clone();
+
+        // У выражения могут быть поля разных датчиков, поэтому предлагаем серийники всех датчиков
+        let all_serials = sensor_serials_ref
+            .values()
+            .flatten()
+            .cloned()
+            .sorted()
+            .dedup()
+            .collect();
+        serial_fields.insert(EXPRESSION_FIELD.to_owned(), all_serials);
 
         // Теперь можно сохранить серийники датчиков
         self.serial_fields = serial_fields;
@@ -243,7 +328,7 @@ impl GraphState {
         let x_data = &self.x_data_fields[x_data_index];
 
         // Получаем сенсор, поле и серийник, если есть
-        let (_x_sensor, x_field, x_serial) = {
+        let (x_sensor, x_field, x_serial) = {
             // Если поле X состоит из {sensor}/{field}, разбираем его
             if let Some((sensor, field)) = x_data.split_once('/') {
                 // Получаем серийник датчика. Если он не установлен, выходим из обновления
@@ -306,242 +391,510 @@ impl GraphState {
             (_, true, _) => format!("{x_field} > {}", x_min_float.unwrap()),
             _ => String::new(),
         };
-        let x_ordering = format!("ORDER BY {x_field}");
 
-        // Открываем соединение с базой данных
-        let database = self.database.lock().unwrap();
+        // Получаем диапазон X, заданный пользователем
+        let x_bounds = (
+            x_min_date
+                .map(|date| date.timestamp() as f64)
+                .or(x_min_float),
+            x_max_date
+                .map(|date| date.timestamp() as f64)
+                .or(x_max_float),
+        );
+
+        // Узнаём, сколько точек может отобразить график по ширине
+        let resolution = self.chart_resolution();
+
+        // Получаем группировку данных по времени, если она выбрана (только если X - дата)
+        let aggregation = match (self.x_states[4].menu(), self.x_states[5].menu()) {
+            (Some(bucket), Some(statistic)) if x_sensor.is_none() => {
+                match (bucket.selected(), statistic.selected()) {
+                    (Some(i), Some(j)) if i > 0 => {
+                        Some((TimeBucket::ALL[i - 1], Statistic::ALL[j]))
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+
+        // Получаем способ совмещения по времени записей Y других датчиков (только если X - поле датчика)
+        let x_join = match x_sensor {
+            Some(x_sensor) => {
+                let join_index = self.x_states[4].menu().unwrap().selected().unwrap_or(0);
+                let seconds = match self.x_states[5].input().map(InputState::content) {
+                    Some("") | None => DEFAULT_JOIN_SECONDS,
+                    Some(seconds) => match seconds.parse::<i64>() {
+                        Ok(seconds) if seconds > 0 => seconds,
+                        _ => return,
+                    },
+                };
+                Some((TimestampJoin::ALL[join_index], seconds, (x_sensor, x_field)))
+            }
+            None => None,
+        };
 
-        // Обрабатываем Y данные
-        let (mut y_all_min, mut y_all_max) = (std::f64::MAX, std::f64::MIN);
-        let new_datasets = self
+        // Собираем запрос для каждого Y
+        let series = self
             .ys_states
             .iter()
             .map(|y_states| {
-                let (data, y_min, y_max) = self.generate_datasets_for_y_states(
-                    &database,
-                    x_field,
-                    x_serial,
-                    &x_filtering,
-                    &x_ordering,
-                    y_states,
-                );
-
-                // Устанавливаем новые минимум и максимум для графика
-                y_all_min = y_all_min.min(y_min);
-                y_all_max = y_all_max.max(y_max);
-
-                data
+                self.get_series_requests(x_serial, x_join, y_states)
+                    .unwrap_or_default()
             })
             .collect();
-        self.datasets = new_datasets;
-
-        // Записываем диапазоны значений
-        let x_min = match (x_min_date, x_min_float) {
-            (Some(date), _) => date.timestamp() as f64,
-            (_, Some(float)) => float,
-            _ => self
-                .datasets
-                .iter()
-                .flatten()
-                .map(|dataset| {
-                    dataset
-                        .iter()
-                        .map(|&(x, _)| x)
-                        .min_by(|a, b| a.partial_cmp(b).unwrap())
-                        .unwrap_or(0.)
-                })
-                .min_by(|a, b| a.partial_cmp(b).unwrap())
-                .unwrap(),
-        };
-
-        let x_max = match (x_max_date, x_max_float) {
-            (Some(date), _) => date.timestamp() as f64,
-            (_, Some(float)) => float,
-            _ => self
-                .datasets
-                .iter()
-                .flatten()
-                .map(|dataset| {
-                    dataset
-                        .iter()
-                        .map(|&(x, _)| x)
-                        .max_by(|a, b| a.partial_cmp(b).unwrap())
-                        .unwrap_or(0.)
-                })
-                .max_by(|a, b| a.partial_cmp(b).unwrap())
-                .unwrap(),
-        };
-        self.dataset_ranges = ((x_min, x_max), (y_all_min, y_all_max));
+
+        // Отменяем прошлую загрузку и запускаем новую в отдельном потоке
+        let request = DatasetsRequest {
+            x_field: x_field.to_owned(),
+            x_filtering,
+            x_bounds,
+            resolution,
+            aggregation,
+            series,
+            scatter: self.scatter,
+            statistics: self.show_statistics,
+            gap_breaks: self.gap_breaks,
+        };
+        self.loader = None;
+        match DatasetsLoader::spawn(&self.database, request) {
+            Ok(loader) => {
+                self.loader = Some(loader);
+                self.load_error = None;
+            }
+            Err(err) => self.load_error = Some(err.to_string()),
+        }
 
         // Ставим флаг того, что изменений нет
         self.was_edited = false;
     }
 
-    /// Генерирует датасеты графиков и получает в них минимальное и максимальное значение
-    fn generate_datasets_for_y_states(
+    /// Возвращает запросы на получение данных функции Y(x), если она полностью настроена.
+    /// Запросов несколько, если выбраны все серийники или разброс между ними
+    fn get_series_requests(
         &self,
-        database: &rusqlite::Connection,
-        x_field: &str,
         x_serial: Option<&str>,
-
-        x_filtering: &str,
-        x_ordering: &str,
-
-        y_states: &[GraphFieldState; 4],
-    ) -> (Vec<Vec<(f64, f64)>>, f64, f64) {
+        x_join: Option<(TimestampJoin, i64, (&str, &str))>,
+        y_states: &[GraphFieldState; ROW_LENGTH],
+    ) -> Option<Vec<SeriesRequest>> {
         // Получаем какой Y мы хотим. Если он не установлен, пропускаем эту функцию
-        let y_data_index = match y_states[0].menu().unwrap().selected() {
-            Some(i) => i,
-            None => return (vec![vec![]], 0., 0.),
-        };
+        let y_data_index = y_states[0].menu().unwrap().selected()?;
         let y_data = &self.y_data_fields[y_data_index];
 
         // Получаем сенсор, поле и серийник, если есть
         let (y_sensor, y_field, y_serial) = {
-            // Поле Y гарантированно состоит из {sensor}/{field}, разбираем его
-            let (sensor, field) = y_data.split_once('/').unwrap();
+            // Поле Y состоит из {sensor}/{field}, если это не выражение
+            let (sensor, field) = y_data.split_once('/').unwrap_or((y_data, y_data));
 
             // Получаем серийник датчика. Если он не установлен, пропускаем
             let serial = if let Some(serial) = x_serial {
                 // Если установлен серийник у X, используем его
                 serial
             } else {
-                // Иначе пытаемся получить серийник у Y
-                let serial_index = match y_states[1].menu().unwrap().selected() {
-                    Some(i) => i,
-                    None => return (vec![vec![]], 0., 0.),
-                };
-                &self.serial_fields[sensor][serial_index]
+                // Иначе пытаемся получить серийник у Y (сначала в меню идут псевдо-серийники)
+                let serial_index = y_states[1].menu().unwrap().selected()?;
+                match serial_index.checked_sub(PSEUDO_SERIALS.len()) {
+                    Some(i) => &self.serial_fields[sensor][i],
+                    None => PSEUDO_SERIALS[serial_index],
+                }
             };
 
             (sensor, field, serial)
         };
 
-        // Проверяем дополнительные датчики, если они есть
-        let y_field_extra1 = if let Some(menu_state) = y_states[2].menu() {
-            // Пытаемся получить первое дополнительное поле у Y. Если не удаётся, пропускаем
-            let extra_index = match menu_state.selected() {
-                Some(i) => i,
-                None => return (vec![vec![]], 0., 0.),
-            };
-            let (_, extra) = self.y_data_fields_without_extra[y_sensor][extra_index]
-                .split_once('/')
-                .unwrap();
-            Some(extra)
+        // Получаем источник данных, значение Y в нём (в виде выражения SQL)
+        // и поля датчика, из которых оно считается (нужны для поправок и выбросов)
+        let (source, value, fields) = if y_data == EXPRESSION_FIELD {
+            // Выражение берёт данные из подзапроса, в котором объединены все его датчики
+            let text = y_states[2].input().unwrap().content();
+            let expression = self.parse_expression(text).ok()?;
+            (expression.sql_source(), "value".to_owned(), None)
         } else {
-            None
-        };
+            // Получаем входные поля производной величины, если они есть
+            let mut inputs = vec![];
+            for input_state in &y_states[2..2 + MAX_INPUTS] {
+                if let Some(menu_state) = input_state.menu() {
+                    // Пытаемся получить входное поле у Y. Если не удаётся, пропускаем
+                    let input_index = menu_state.selected()?;
+                    let (_, input) = self.y_data_fields_without_extra[y_sensor][input_index]
+                        .split_once('/')
+                        .unwrap();
+                    inputs.push(input);
+                }
+            }
 
-        let y_field_extra2 = if let Some(menu_state) = y_states[3].menu() {
-            // Пытаемся получить первое дополнительное поле у Y. Если не удаётся, пропускаем
-            let extra_index = match menu_state.selected() {
-                Some(i) => i,
-                None => return (vec![vec![]], 0., 0.),
+            let (value, fields) = match find_derived_field(y_field) {
+                // Производная величина считается функцией по входным полям
+                Some(derived) if derived.inputs.len() == inputs.len() => {
+                    (derived.sql(&inputs), inputs)
+                }
+                Some(_) => return None,
+                None => (y_field.to_owned(), vec![y_field]),
             };
-            let (_, extra) = self.y_data_fields_without_extra[y_sensor][extra_index]
-                .split_once('/')
-                .unwrap();
-            Some(extra)
-        } else {
-            None
+
+            // Если включены поправки, то берём значения полей датчика уже с ними
+            let source = match self.calibrated {
+                true => {
+                    let columns = &self.sensor_fields.borrow()[y_sensor];
+                    calibration::corrected_source(y_sensor, columns, &fields)
+                }
+                false => y_sensor.to_owned(),
+            };
+            (source, value, Some(fields))
         };
 
-        // Составляем часть SQL запроса для отфильтровки серийников
-        let serial_filtering = match y_serial {
-            "Средн." | "Мин." | "Макс." => String::new(),
-            serial => format!("serial = '{serial}'"),
+        // Помеченные выбросами записи подсвечиваются, если они не исключены и X - дата
+        let flagged = match (&fields, self.exclude_outliers, x_join) {
+            (Some(fields), false, None) => {
+                Some(outliers::flagged_source(y_sensor, &source, fields, &value))
+            }
+            _ => None,
         };
 
-        // Составляем часть SQL запроса со всеми фильтрами
-        let sql_filtering = match (!x_filtering.is_empty(), !serial_filtering.is_empty()) {
-            (true, true) => format!("WHERE {x_filtering} AND {serial_filtering}"),
-            (false, false) => String::new(),
-            _ => format!("WHERE {x_filtering}{serial_filtering}"),
+        // Если выбросы исключаются, то берём записи датчика без них
+        let source = match (&fields, self.exclude_outliers) {
+            (Some(fields), true) => outliers::excluding_source(y_sensor, &source, fields),
+            _ => source,
         };
 
-        // Поля, которые нам необходимо собрать
-        let graph_fields = match (y_field_extra1, y_field_extra2) {
-            (Some(field1), Some(field2)) => format!("{field1},{field2}"),
-            _ => y_field.to_owned(),
+        // Если Y берётся не из датчика X, то совмещаем их записи по времени
+        let (source, value) = match x_join {
+            Some((join, seconds, x)) if y_sensor != x.0 => {
+                let source = join.sql_source(seconds, x, (&source, &value));
+                (source, "value".to_owned())
+            }
+            _ => (source, value),
         };
-        let select_fields = format!("{graph_fields},{x_field},serial");
 
-        // Собираем финальный SQL запрос
-        let sql = format!("SELECT {select_fields} FROM {y_sensor} {sql_filtering} {x_ordering}");
-        let mut statement = database.prepare(&sql).unwrap();
+        // Получаем ось и нормализацию графика (первый вариант нормализации - без неё)
+        let selection = |i: usize| y_states[i].menu().and_then(MenuState::selected);
+        let axis = selection(4).map_or(YAxis::Left, |i| YAxis::ALL[i]);
+        let normalization = match selection(5) {
+            Some(i) if i > 0 => Some(Normalization::ALL[i - 1]),
+            _ => None,
+        };
 
-        // Выполняем запрос SQL и сохраняем данные
-        let mut rows = statement.query(()).unwrap();
-        let mut data = vec![];
-        let (mut y_min, mut y_max) = (std::f64::MAX, std::f64::MIN);
+        // Получаем фильтр сглаживания (первый вариант - без фильтра). Пустое окно заменяется
+        // окном по умолчанию, а ошибочное не даёт построить график
+        let smoothing = match selection(6) {
+            Some(i) if i > 0 => {
+                let text = y_states[7].input().map_or("", InputState::content);
+                let window = match text.trim() {
+                    "" => DEFAULT_SMOOTHING_WINDOW,
+                    text => SmoothingWindow::parse(text).ok()?,
+                };
+                // Окно во времени можно задать, только если X - дата
+                if let (SmoothingWindow::Seconds(_), false) = (window, self.is_x_date()) {
+                    return None;
+                }
+                Some((Smoothing::ALL[i - 1], window))
+            }
+            _ => None,
+        };
 
-        // Читаем строки из БД
-        if y_field_extra2.is_some() {
-            let conversion = match y_field {
-                "Эфф. темп." => |y1: f64, y2: f64| {
-                    let t = y1;
-                    let h = y2;
-                    t - 0.4 * (t - 10.) * (1. - h / 100.)
-                },
-                _ => panic!("Неизвестно, что делать с данными"),
-            };
+        // Подписываем график выражением, если оно есть, чтобы его можно было узнать
+        let label = match y_data == EXPRESSION_FIELD {
+            true => y_states[2].input().unwrap().content().to_owned(),
+            false => tr_item(y_data),
+        };
+        // Дописываем нормализацию в подпись, т.к. значения уже не в исходных единицах
+        let label = match normalization {
+            Some(normalization) => format!("{label} [{}]", tr(normalization.name())),
+            None => label,
+        };
+        // Помечаем значения с поправками, чтобы их можно было отличить от исходных
+        let label = match (&fields, self.calibrated) {
+            (Some(_), true) => format!("{label} [{}]", tr("поправка")),
+            _ => label,
+        };
+        // Дописываем и фильтр, чтобы сглаженный график не путали с исходным
+        let label = match smoothing {
+            Some((smoothing, window)) => format!("{label} [{}, {window}]", tr(smoothing.name())),
+            None => label,
+        };
 
-            // Если у нас были дополнительные поля, высчитываем сразу значение по формуле
-            while let Some(row) = rows.next().unwrap() {
-                // Получаем данные со строки
-                let y1: f64 = row.get(0).unwrap();
-                let y2: f64 = row.get(1).unwrap();
-                let x: f64 = row.get(2).unwrap();
-
-                // Обрабатываем дополнительные поля и кладём в массив
-                let y = conversion(y1, y2);
-                data.push((x, y));
-                y_min = y_min.min(y);
-                y_max = y_max.max(y);
+        // Пороги задаются только у полей датчика и рисуются, пока значения в исходных единицах
+        let thresholds = match (&fields, normalization) {
+            (Some(fields), None) if fields[..] == [y_field] => {
+                Some((y_sensor.to_owned(), y_field.to_owned()))
             }
-        } else {
-            // Просто кладём полученные данные в массив
-            while let Some(row) = rows.next().unwrap() {
-                let y: f64 = row.get(0).unwrap();
-                let x: f64 = row.get(1).unwrap();
-                data.push((x, y));
-                y_min = y_min.min(y);
-                y_max = y_max.max(y);
+            _ => None,
+        };
+
+        // Запрос данных одного прибора
+        let device = |serial: &str| SeriesRequest {
+            sensor: source.clone(),
+            value: value.clone(),
+            serial_filtering: format!("serial = '{serial}'"),
+            label: format!("{label} ({serial})"),
+            axis,
+            normalization,
+            smoothing,
+            difference: false,
+            outliers: flagged.clone(),
+            thresholds: thresholds.clone(),
+            temp_tables: vec![],
+            devices: None,
+            serials: vec![serial.to_owned()],
+        };
+
+        // Запрос значения, посчитанного по всем приборам в каждом промежутке сетки времени
+        // (у разных приборов почти не бывает одинаковых дат). Сначала значения усредняются
+        // по каждому прибору, чтобы частые записи одного прибора не перевешивали остальные
+        let across_devices = |name: &str| {
+            let function = match name {
+                "Мин." => "MIN",
+                "Макс." => "MAX",
+                _ => "AVG",
+            };
+            let seconds = DEFAULT_JOIN_SECONDS;
+            SeriesRequest {
+                sensor: format!(
+                    "(SELECT date, {function}(value) AS value FROM \
+                     (SELECT date - date % {seconds} AS date, serial, AVG({value}) AS value \
+                     FROM {source} GROUP BY 1, 2) GROUP BY date)"
+                ),
+                value: "value".to_owned(),
+                serial_filtering: String::new(),
+                label: format!("{label} ({})", tr(name)),
+                axis,
+                normalization,
+                smoothing,
+                difference: false,
+                outliers: None,
+                thresholds: thresholds.clone(),
+                temp_tables: vec![],
+                devices: Some(source.clone()),
+                serials: self.serial_fields[y_sensor].clone(),
             }
-        }
+        };
 
-        // Проверяем, нужно ли нам график разделять на несколько
-        let dataset = if x_field == "date" {
-            // Если X - дата, то не нужно
-            vec![data]
-        } else {
-            // Если X - какое-то поле датчика - нужно
-            let (mut min, mut avg, mut max) = (vec![], vec![], vec![]);
-            for (x, points) in &data.into_iter().group_by(|&(x, _)| x) {
-                let ys: Vec<_> = points.map(|(_, y)| y).collect();
-                let ys_len = ys.len();
-
-                let (mut y_min, mut y_max, mut y_sum) = (std::f64::MAX, std::f64::MIN, 0.);
-                for y in ys {
-                    y_min = y_min.min(y);
-                    y_max = y_max.max(y);
-                    y_sum += y;
+        // Получаем эталонный прибор, если он выбран (первый вариант - без эталона)
+        let reference = match selection(8) {
+            Some(i) if i > 0 => Some(self.serial_fields[y_sensor][i - 1].as_str()),
+            _ => None,
+        };
+
+        // Раскрываем псевдо-серийники в запросы (эталон с самим собой не сравнивается)
+        let requests: Vec<_> = match y_serial {
+            "Все" => self.serial_fields[y_sensor]
+                .iter()
+                .filter(|&serial| Some(serial.as_str()) != reference)
+                .map(|serial| device(serial))
+                .collect(),
+            "Разброс" => ["Мин.", "Средн.", "Макс."]
+                .into_iter()
+                .map(across_devices)
+                .collect(),
+            "Средн." | "Мин." | "Макс." => vec![across_devices(y_serial)],
+            serial => vec![device(serial)],
+        };
+
+        // Если выбран эталон, то вместо значений строим их разность со значениями эталона
+        let Some(reference) = reference else {
+            return Some(requests);
+        };
+        let reference_values = reference_table(format!(
+            "SELECT date, {value} AS value FROM {source} WHERE serial = '{reference}' \
+             AND {value} IS NOT NULL"
+        ));
+        let requests = requests
+            .into_iter()
+            .map(|mut request| {
+                // Разность зависит и от эталона, поэтому его метки тоже относятся к графику
+                request.serials.push(reference.to_owned());
+                let filtering = match request.serial_filtering.is_empty() {
+                    true => String::new(),
+                    false => format!("WHERE {}", request.serial_filtering),
+                };
+                let values = format!(
+                    "(SELECT date, {} AS value FROM {} {filtering})",
+                    request.value, request.sensor
+                );
+                SeriesRequest {
+                    sensor: difference_source(&values, &reference_values),
+                    value: "value".to_owned(),
+                    serial_filtering: String::new(),
+                    label: format!("{} − {reference}", request.label),
+                    difference: true,
+                    outliers: None,
+                    thresholds: None,
+                    temp_tables: vec![reference_values.clone()],
+                    ..request
                 }
+            })
+            .collect();
+
+        Some(requests)
+    }
+
+    /// Возвращает, сколько точек может отобразить график по ширине (Braille даёт 2 точки на клетку)
+    fn chart_resolution(&self) -> usize {
+        match self.chart_area.width {
+            0 => DEFAULT_CHART_RESOLUTION,
+            width => width as usize * 2,
+        }
+    }
+
+    /// Возвращает, является ли X датой (или ещё не выбран)
+    pub fn is_x_date(&self) -> bool {
+        match self.x_states[0].menu().unwrap().selected() {
+            Some(i) => !self.x_data_fields[i].contains('/'),
+            None => true,
+        }
+    }
+
+    /// Разбирает выражение для Y по полям датчиков
+    pub fn parse_expression(&self, text: &str) -> Result<Expression, String> {
+        Expression::parse(text, &self.sensor_fields.borrow())
+    }
 
-                min.push((x, y_min));
-                max.push((x, y_max));
-                avg.push((x, y_sum / ys_len as f64));
+    /// Принимает загруженные датасеты, если загрузка завершилась
+    pub fn poll_datasets(&mut self) {
+        // Проверяем, завершилась ли загрузка
+        match &self.loader {
+            Some(loader) if loader.is_finished() => (),
+            _ => return,
+        }
+
+        // Если загрузка была отменена или завершилась с ошибкой, оставляем прошлые данные,
+        // а ошибку показываем во вкладке
+        let datasets = match self.loader.take().unwrap().join() {
+            Ok(Some(datasets)) => datasets,
+            Ok(None) => return,
+            Err(err) => {
+                self.load_error = Some(err);
+                return;
             }
+        };
+
+        self.datasets = datasets.datasets;
+        self.dataset_ranges = datasets.ranges;
+        self.dataset_x_is_date = datasets.x_is_date;
+        self.dataset_right_range = datasets.right_range;
+        self.dataset_scatter = datasets.scatter;
+        self.dataset_annotations = datasets.annotations;
+
+        // Собираем все различные значения X для курсора
+        let previous_cursor_x = self.cursor_x();
+        let mut xs: Vec<f64> = self
+            .datasets
+            .iter()
+            .flat_map(|dataset| dataset.data.iter().flatten().map(|&(x, _)| x))
+            .collect();
+        xs.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+        xs.dedup();
+        self.dataset_xs = xs;
+
+        // Оставляем курсор как можно ближе к прежнему положению
+        self.cursor = None;
+        if let Some(x) = previous_cursor_x {
+            self.move_cursor_to(x);
+        }
+    }
+
+    /// Отменяет загрузку датасетов, если она происходит
+    pub fn cancel_loading(&mut self) {
+        if let Some(loader) = self.loader.take() {
+            loader.cancel();
+        }
+    }
+
+    /// Возвращает значение X, на котором стоит курсор
+    pub fn cursor_x(&self) -> Option<f64> {
+        self.cursor.map(|i| self.dataset_xs[i])
+    }
+
+    /// Ставит курсор на ближайшее к данному значение X
+    pub fn move_cursor_to(&mut self, x: f64) {
+        // Если данных нет, то курсор ставить некуда
+        if self.dataset_xs.is_empty() {
+            return;
+        }
+
+        self.cursor = Some(nearest_point_index(&self.dataset_xs, x, |&x| x));
+    }
+
+    /// Сдвигает курсор на несколько значений X влево
+    pub fn cursor_prev(&mut self, step: usize) {
+        if self.dataset_xs.is_empty() {
+            return;
+        }
+
+        // Если курсора не было, ставим его в конец графика
+        let i = self.cursor.unwrap_or(self.dataset_xs.len());
+        self.cursor = Some(i.saturating_sub(step));
+    }
+
+    /// Сдвигает курсор на несколько значений X вправо
+    pub fn cursor_next(&mut self, step: usize) {
+        if self.dataset_xs.is_empty() {
+            return;
+        }
+
+        // Если курсора не было, ставим его в начало графика
+        let last = self.dataset_xs.len() - 1;
+        self.cursor = Some(self.cursor.map_or(0, |i| (i + step).min(last)));
+    }
+
+    /// Возвращает значения каждого графика в точке курсора
+    /// Представляет из себя набор (индекс графика, индекс подграфика, ближайшее значение Y, если оно рядом)
+    pub fn cursor_values(&self) -> Vec<(usize, usize, Option<f64>)> {
+        let x = match self.cursor_x() {
+            Some(x) => x,
+            None => return vec![],
+        };
 
-            // Возвращаем три разделённых графика
-            vec![min, avg, max]
+        // Ширина одной точки графика по X: ближе неё точка считается стоящей под курсором
+        let (x_min, x_max) = self.dataset_ranges.0;
+        let bucket_width = (x_max - x_min) / self.chart_resolution() as f64;
+
+        // Значение графика под курсором, если у графика есть точка рядом с ним
+        let value_at_cursor = move |points: &[(f64, f64)]| {
+            // Точки отсортированы по X, поэтому ищем ближайшую бинарным поиском
+            let (&(first_x, _), &(last_x, _)) = (points.first()?, points.last()?);
+            let (point_x, y) = points[nearest_point_index(points, x, |&(x, _)| x)];
+
+            // Редкие записи отстоят друг от друга дальше точки графика, поэтому допуск
+            // не меньше среднего шага самого графика. Дальше у графика данных нет
+            let spacing = (last_x - first_x) / (points.len() - 1).max(1) as f64;
+            Some(y).filter(|_| (point_x - x).abs() <= bucket_width.max(spacing))
+        };
+
+        self.datasets
+            .iter()
+            .enumerate()
+            .flat_map(|(i, dataset)| {
+                dataset
+                    .data
+                    .iter()
+                    .enumerate()
+                    .map(move |(j, points)| (i, j, value_at_cursor(points)))
+            })
+            .collect()
+    }
+
+    /// Возвращает метки, на которые указывает курсор: промежутки, в которых он стоит,
+    /// и метки, ближайшее значение X к которым - значение курсора
+    pub fn annotations_at_cursor(&self) -> Vec<&Annotation> {
+        let (Some(i), Some(x)) = (self.cursor, self.cursor_x()) else {
+            return vec![];
         };
 
-        (dataset, y_min, y_max)
+        self.dataset_annotations
+            .iter()
+            .filter(|annotation| {
+                let inside =
+                    (annotation.start as f64) <= x && x <= annotation.end_or_start() as f64;
+                let nearest =
+                    nearest_point_index(&self.dataset_xs, annotation.start as f64, |&x| x);
+                inside || nearest == i
+            })
+            .collect()
     }
 
-    /// Возвращает серийники для поля с индексом
-    pub fn get_serial_fields_for_sensor(&self, field_index: usize) -> &Vec<String> {
+    /// Возвращает серийники для поля с индексом (у Y вначале идут псевдо-серийники)
+    pub fn get_serial_fields_for_sensor(&self, field_index: usize) -> Vec<&str> {
         // Извлекаем название сенсора из выбранного поля данных
         let selection = if field_index == 1 {
             // Обрабатываем как поле X
@@ -549,7 +902,7 @@ impl GraphState {
             &self.x_data_fields[selection]
         } else {
             // Обрабатываем как поле Y
-            let selection = self.ys_states[field_index / 4 - 1][0]
+            let selection = self.ys_states[field_index / ROW_LENGTH - 1][0]
                 .menu()
                 .unwrap()
                 .selected()
@@ -557,35 +910,134 @@ impl GraphState {
             &self.y_data_fields[selection]
         };
 
-        let (sensor, _) = selection.split_once('/').unwrap();
+        // У выражения серийники хранятся под его названием
+        let (sensor, _) = selection.split_once('/').unwrap_or((selection, ""));
         // Получаем серийники датчика
-        &self.serial_fields[sensor]
+        let serials = self.serial_fields[sensor].iter().map(String::as_str);
+        match field_index {
+            1 => serials.collect(),
+            _ => PSEUDO_SERIALS.into_iter().chain(serials).collect(),
+        }
+    }
+
+    /// Возвращает элементы меню поля с индексом (поле данных Y для доп. полей гарантированно выбрано)
+    pub fn get_menu_items(&self, field_index: usize) -> Vec<String> {
+        match (field_index / ROW_LENGTH, field_index % ROW_LENGTH) {
+            // Поля значений X
+            (0, 0) => self.x_data_fields.clone(),
+            // Поля значений Y
+            (_, 0) => self.y_data_fields.clone(),
+            // Группировка по времени (первый вариант - без группировки), если X - дата
+            (0, 4) if self.is_x_date() => std::iter::once("Без агрегации")
+                .chain(TimeBucket::ALL.iter().map(TimeBucket::name))
+                .map(str::to_owned)
+                .collect(),
+            // Способ совмещения записей по времени, если X - поле датчика
+            (0, 4) => TimestampJoin::ALL
+                .iter()
+                .map(|join| join.name().to_owned())
+                .collect(),
+            // Значение, которое берётся в промежутке
+            (0, 5) => Statistic::ALL
+                .iter()
+                .map(|statistic| statistic.name().to_owned())
+                .collect(),
+            // Ось Y графика
+            (_, 4) => YAxis::ALL
+                .iter()
+                .map(|axis| axis.name().to_owned())
+                .collect(),
+            // Нормализация графика (первый вариант - без нормализации)
+            (_, 5) => std::iter::once("Без нормализации")
+                .chain(Normalization::ALL.iter().map(Normalization::name))
+                .map(str::to_owned)
+                .collect(),
+            // Фильтр сглаживания графика (первый вариант - без фильтра)
+            (_, 6) => std::iter::once("Без фильтра")
+                .chain(Smoothing::ALL.iter().map(Smoothing::name))
+                .map(str::to_owned)
+                .collect(),
+            // Эталонный прибор графика (первый вариант - без эталона)
+            (row, 8) => {
+                let data_field = self.ys_states[row - 1][0].menu().unwrap();
+                let selection = &self.y_data_fields[data_field.selected().unwrap()];
+                let (sensor, _) = selection.split_once('/').unwrap_or((selection, ""));
+                std::iter::once("Без эталона".to_owned())
+                    .chain(self.serial_fields[sensor].iter().cloned())
+                    .collect()
+            }
+            // Поля серийников
+            (_, 1) => self
+                .get_serial_fields_for_sensor(field_index)
+                .into_iter()
+                .map(str::to_owned)
+                .collect(),
+            // Поля дополнительных данных Y
+            (row, _) => {
+                // Получаем название сенсора из основного поля данных Y
+                let data_field = self.ys_states[row - 1][0].menu().unwrap();
+                let selection = &self.y_data_fields[data_field.selected().unwrap()];
+                let (sensor, _) = selection.split_once('/').unwrap();
+                self.y_data_fields_without_extra[sensor].clone()
+            }
+        }
+    }
+
+    /// Возвращает элементы меню поля с индексом в том виде, в котором они отображаются
+    /// (элементы меню служат идентификаторами в видах, поэтому переводятся только подписи)
+    pub fn get_menu_labels(&self, field_index: usize) -> Vec<String> {
+        self.get_menu_items(field_index)
+            .iter()
+            .map(|item| tr_item(item))
+            .collect()
+    }
+
+    /// Возвращает производную величину, выбранную в поле данных графика Y с номером `row` (с единицы)
+    pub fn derived_field(&self, row: usize) -> Option<&'static DerivedField> {
+        let selected = self.ys_states[row - 1][0].menu()?.selected()?;
+        let (_, field) = self.y_data_fields[selected].split_once('/')?;
+        find_derived_field(field)
     }
 
     /// Возвращает дефолтные поля пустого графика
-    pub fn default_graph() -> [GraphFieldState; 4] {
-        [
-            GraphFieldState::new_menu(),
-            GraphFieldState::Hidden,
-            GraphFieldState::Hidden,
-            GraphFieldState::Hidden,
-        ]
+    pub fn default_graph() -> [GraphFieldState; ROW_LENGTH] {
+        let mut fields = <[GraphFieldState; ROW_LENGTH]>::default();
+        fields[0] = GraphFieldState::new_menu();
+        fields
+    }
+
+    /// Возвращает ссылку на состояние поля с индексом
+    pub fn field_state(&self, field_index: usize) -> &GraphFieldState {
+        match field_index {
+            i @ 0..ROW_LENGTH => &self.x_states[i],
+            i => &self.ys_states[i / ROW_LENGTH - 1][i % ROW_LENGTH],
+        }
+    }
+
+    /// Возвращает изменяемую ссылку на состояние поля с индексом
+    pub fn field_state_mut(&mut self, field_index: usize) -> &mut GraphFieldState {
+        match field_index {
+            i @ 0..ROW_LENGTH => &mut self.x_states[i],
+            i => &mut self.ys_states[i / ROW_LENGTH - 1][i % ROW_LENGTH],
+        }
+    }
+
+    /// Возвращает индекс поля, которое было отрисовано в данной клетке
+    pub fn field_at(&self, column: u16, row: u16) -> Option<usize> {
+        self.fields_areas
+            .iter()
+            .find(|(_, area)| area_contains(*area, column, row))
+            .map(|&(i, _)| i)
     }
 
     /// Возвращает ссылку на состояние выделенного элемента меню
     pub fn selected_field_state(&self) -> &GraphFieldState {
-        match self.selected.unwrap() {
-            i @ 0..=3 => &self.x_states[i],
-            i => &self.ys_states[i / 4 - 1][i % 4],
-        }
+        self.field_state(self.selected.unwrap())
     }
 
     /// Возвращает изменяемую ссылку на состояние выделенного элемента меню
     pub fn selected_field_state_mut(&mut self) -> &mut GraphFieldState {
-        match self.selected.unwrap() {
-            i @ 0..=3 => &mut self.x_states[i],
-            i => &mut self.ys_states[i / 4 - 1][i % 4],
-        }
+        self.field_state_mut(self.selected.unwrap())
     }
 
     /// Возвращает ссылку на состояние выделенное поле ввода текста
@@ -611,7 +1063,7 @@ impl GraphState {
     /// Выбирает предыдущий элемент
     pub fn select_prev(&mut self, step: usize) {
         if let Some(i) = self.selected.as_mut() {
-            let length = (self.ys_states.len() + 1) * 4;
+            let length = (self.ys_states.len() + 1) * ROW_LENGTH;
             *i = (*i + length - step) % length;
 
             // Если попали на скрытое поле -> надо переходить к следующему
@@ -624,7 +1076,7 @@ impl GraphState {
     /// Выбирает следующий элемент
     pub fn select_next(&mut self, step: usize) {
         if let Some(i) = self.selected.as_mut() {
-            let length = (self.ys_states.len() + 1) * 4;
+            let length = (self.ys_states.len() + 1) * ROW_LENGTH;
             *i = (*i + step) % length;
 
             // Если попали на скрытое поле -> надо переходить к следующему
@@ -635,10 +1087,29 @@ impl GraphState {
     }
 }
 
+/// Возвращает индекс элемента отсортированного по X массива, ближайшего к данному X
+fn nearest_point_index<T>(items: &[T], x: f64, get_x: impl Fn(&T) -> f64) -> usize {
+    // Ищем первый элемент, который не меньше данного X
+    let i = items.partition_point(|item| get_x(item) < x);
+    if i == 0 {
+        return 0;
+    } else if i == items.len() {
+        return items.len() - 1;
+    }
+
+    // Выбираем ближайший из двух соседей
+    if x - get_x(&items[i - 1]) <= get_x(&items[i]) - x {
+        i - 1
+    } else {
+        i
+    }
+}
+
 /// Перечисляемый тип, определяющий вид поля графика
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub enum GraphFieldState {
     /// Определяет скрытое поле
+    #[default]
     Hidden,
 
     /// Определяет поле ввода текста
@@ -648,12 +1119,6 @@ pub enum GraphFieldState {
     Menu(MenuState),
 }
 
-impl Default for GraphFieldState {
-    fn default() -> Self {
-        Self::Hidden
-    }
-}
-
 impl GraphFieldState {
     /// Создаёт новое состояние поля ввода
     pub fn new_input() -> Self {
@@ -665,6 +1130,13 @@ impl GraphFieldState {
         Self::Menu(MenuState::default())
     }
 
+    /// Создаёт новое состояние меню с уже выбранным элементом
+    pub fn new_menu_selected(selection: usize) -> Self {
+        let mut state = MenuState::default();
+        state.set_select(Some(selection));
+        Self::Menu(state)
+    }
+
     /// Возвращает ссылку на состояние поля ввода
     pub fn input(&self) -> Option<&InputState> {
         match self {
@@ -0,0 +1,74 @@
+use crate::database::gaps;
+
+/// Статистика значений одного графика Y в текущем диапазоне X
+#[derive(Clone, Debug, Default)]
+pub struct SeriesStatistics {
+    /// Подпись графика
+    pub label: String,
+
+    /// Количество значений
+    pub count: usize,
+
+    /// Минимальное значение
+    pub min: Option<f64>,
+
+    /// Максимальное значение
+    pub max: Option<f64>,
+
+    /// Среднее значение
+    pub mean: Option<f64>,
+
+    /// Медиана
+    pub median: Option<f64>,
+
+    /// Стандартное отклонение (по выборке)
+    pub stddev: Option<f64>,
+
+    /// Дата первой записи
+    pub first: Option<i64>,
+
+    /// Дата последней записи
+    pub last: Option<i64>,
+
+    /// Количество пропусков (промежутков между записями больше обычного в `gaps::GAP_FACTOR` раз)
+    pub gaps: usize,
+}
+
+impl SeriesStatistics {
+    /// Считает статистику значения `value` в источнике `source` (таблице или подзапросе),
+    /// отфильтрованном частью SQL запроса `sql_filtering`. Пропуски ищутся в записях приборов `devices`
+    /// (подзапросе с полями `date` и `serial`)
+    pub fn query(
+        database: &rusqlite::Connection,
+        label: String,
+        (source, value, sql_filtering): (&str, &str, &str),
+        devices: &str,
+    ) -> rusqlite::Result<Self> {
+        // Сначала выбираем значения, чтобы фильтры применялись к полям источника
+        let values = format!("(SELECT date, {value} AS value FROM {source} {sql_filtering})");
+
+        let sql = format!(
+            "SELECT COUNT(value), MIN(value), MAX(value), AVG(value), median(value), stddev(value), \
+             MIN(date), MAX(date) FROM {values}"
+        );
+        let mut statistics = database.query_row(&sql, (), |row| {
+            Ok(Self {
+                label,
+                count: row.get(0)?,
+                min: row.get(1)?,
+                max: row.get(2)?,
+                mean: row.get(3)?,
+                median: row.get(4)?,
+                stddev: row.get(5)?,
+                first: row.get(6)?,
+                last: row.get(7)?,
+                gaps: 0,
+            })
+        })?;
+
+        // Пропуском считаем промежуток между записями, который намного больше обычного (медианного)
+        statistics.gaps = gaps::find_gaps(database, devices)?.len();
+
+        Ok(statistics)
+    }
+}
@@ -1,21 +1,29 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
-
+use chrono::NaiveDateTime;
 use tui::{
     backend::Backend,
+    buffer::Buffer,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
-    symbols::Marker,
+    symbols::{self, Marker},
     text::{Span, Spans, Text},
-    widgets::{Axis, Block, Borders, Chart, Dataset, GraphType, Paragraph},
+    widgets::{Axis, Block, Borders, Chart, Dataset, GraphType, Paragraph, Row, Table, Widget},
     Frame,
 };
 
 use crate::{
-    graph::state::GraphFieldState,
+    annotations::ui::draw_annotation,
+    database::gaps::format_date,
+    graph::{
+        datasets::Regression,
+        scaling::{rescale, YAxis},
+        state::{GraphFieldState, ROW_LENGTH},
+    },
+    locale::{tr, trf},
     ui::{
         input::Input,
         menu::{Menu, MENU_HEIGHT},
     },
+    views::ui::draw_views,
 };
 
 use super::state::GraphState;
@@ -41,8 +49,64 @@ pub fn draw_graph_tab<B: Backend>(frame: &mut Frame<B>, state: &mut GraphState,
     let fields_block = Block::default().borders(Borders::BOTTOM);
     // Рендерим нижнюю черту
     frame.render_widget(fields_block, fields_area);
+
+    // Если происходит загрузка данных, отображаем её прогресс на нижней черте
+    if let Some(loader) = &state.loader {
+        let status_area = Rect {
+            y: fields_area.bottom() - 1,
+            height: 1,
+            ..fields_area
+        };
+
+        let text = format!(" {} ", loader.status());
+        let paragraph = Paragraph::new(Text::raw(text))
+            .alignment(Alignment::Right)
+            .style(Style::default().fg(Color::Yellow));
+        frame.render_widget(paragraph, status_area);
+    } else if let Some(error) = &state.load_error {
+        // Если загрузка завершилась с ошибкой, отображаем её там же
+        let status_area = Rect {
+            y: fields_area.bottom() - 1,
+            height: 1,
+            ..fields_area
+        };
+
+        let text = format!(" {} ", trf("Ошибка загрузки данных: {}", &[error]));
+        let paragraph = Paragraph::new(Text::raw(text))
+            .alignment(Alignment::Right)
+            .style(Style::default().fg(Color::Red));
+        frame.render_widget(paragraph, status_area);
+    }
+
+    // Если в выделенном поле ввода есть ошибка, отображаем её на нижней черте
+    let error = match state.selected.map(|_| state.selected_field_state()) {
+        Some(GraphFieldState::Input(input_state)) => input_state.error(),
+        _ => None,
+    };
+    if let Some(error) = error {
+        let error_area = Rect {
+            y: fields_area.bottom() - 1,
+            height: 1,
+            ..fields_area
+        };
+
+        let paragraph =
+            Paragraph::new(Text::raw(format!(" {error} "))).style(Style::default().fg(Color::Red));
+        frame.render_widget(paragraph, error_area);
+    }
+
     // Рендерим поля
     draw_graph_fields(frame, state, fields_area);
+
+    // Если у нас открыто окно сохранённых видов -> рендерим его поверх всего
+    if let Some(views_state) = state.views_state.as_mut() {
+        draw_views(frame, views_state, area);
+    }
+
+    // Если у нас открыто окно метки -> рендерим его поверх всего
+    if let Some(annotation_state) = state.annotation_state.as_mut() {
+        draw_annotation(frame, annotation_state, area);
+    }
 }
 
 /// Рендерит поля графика
@@ -88,7 +152,7 @@ fn draw_graph_fields<B: Backend>(frame: &mut Frame<B>, state: &mut GraphState, a
         };
 
         // Рендерим надпись
-        let paragraph = Paragraph::new(Text::raw("Edit"))
+        let paragraph = Paragraph::new(Text::raw(tr("Правка")))
             .alignment(Alignment::Center)
             .style(Style::default().fg(Color::Red));
         frame.render_widget(paragraph, area);
@@ -97,46 +161,58 @@ fn draw_graph_fields<B: Backend>(frame: &mut Frame<B>, state: &mut GraphState, a
     // Подготавливаем области для каждого параметра
     let fields_areas = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints(vec![Constraint::Percentage(25); 4])
+        .constraints(vec![Constraint::Ratio(1, ROW_LENGTH as u32); ROW_LENGTH])
         .split(area_chunks[1]);
 
-    // Рендерим названия параметров
-    const NAMES: [&str; 4] = [
+    // Рендерим названия параметров (часть названий для X зависит от того, является ли он датой)
+    let x_is_date = state.is_x_date();
+    let names: [&str; ROW_LENGTH] = [
         "Поле данных",
         "Серийник",
-        "Мин.знач./Датчик",
-        "Макс.знач./Датчик",
+        "Мин.знач./Вход 1",
+        "Макс.знач./Вход 2",
+        if x_is_date {
+            "Агрегация/Ось"
+        } else {
+            "Совмещение/Ось"
+        },
+        if x_is_date {
+            "Статистика/Нормализация"
+        } else {
+            "Допуск, с/Нормализация"
+        },
+        "Фильтр",
+        "Окно",
+        "Эталон",
     ];
-    for (i, name) in NAMES.into_iter().enumerate() {
+    for (i, name) in names.into_iter().enumerate() {
         // Получаем область для рендера
         let area = fields_areas[i];
 
         // Рендерим надпись
-        let paragraph = Paragraph::new(vec![Spans::from(name)]).alignment(Alignment::Center);
+        let paragraph = Paragraph::new(vec![Spans::from(tr(name))]).alignment(Alignment::Center);
         frame.render_widget(paragraph, area);
     }
 
-    // Подготавливаем данные для рендера
-    let x_data_fields: Vec<_> = state.x_data_fields.iter().cloned().map(Text::raw).collect();
-    let y_data_fields: Vec<_> = state.y_data_fields.iter().cloned().map(Text::raw).collect();
-    let y_data_fields_without_extra: HashMap<_, Vec<_>> = state
-        .y_data_fields_without_extra
-        .clone()
-        .into_iter()
-        .map(|(sensor, fields)| {
-            let fields = fields.iter().cloned().map(Text::raw).collect();
-            (sensor.clone(), fields)
-        })
-        .collect();
-    let serial_fields: HashMap<_, Vec<_>> = state
-        .serial_fields
-        .iter()
-        .map(|(sensor, fields)| {
-            let fields = fields.iter().cloned().map(Text::raw).collect();
-            (sensor.clone(), fields)
+    // Подготавливаем элементы меню каждого поля заранее, т.к. дальше поля изменяются
+    let fields_count = (state.ys_states.len() + 1) * ROW_LENGTH;
+    let mut menus_items: Vec<Vec<String>> = (0..fields_count)
+        .map(|i| match state.field_state(i) {
+            GraphFieldState::Menu(_) => state.get_menu_labels(i),
+            _ => vec![],
         })
         .collect();
 
+    // Невыбранные входные поля производных величин подписываем их названиями
+    let mut placeholders: Vec<Option<String>> = vec![None; fields_count];
+    for row in 1..=state.ys_states.len() {
+        if let Some(derived) = state.derived_field(row) {
+            for (k, input) in derived.inputs.iter().enumerate() {
+                placeholders[row * ROW_LENGTH + 2 + k] = Some(tr(input).to_owned());
+            }
+        }
+    }
+
     // Подготавливаем итераторы
     let x_states = (0, &mut state.x_states);
     let ys_states = state
@@ -146,14 +222,14 @@ fn draw_graph_fields<B: Backend>(frame: &mut Frame<B>, state: &mut GraphState, a
         .map(|(i, state)| (i + 1, state))
         .rev();
 
+    // Запоминаем области видимых полей, чтобы выбирать их мышкой
+    state.fields_areas.clear();
+
     // Рендерим каждое поле (необходимо делать в обратном порядке, чтобы не было пересечений с меню)
     for (i, variable) in ys_states.chain(std::iter::once(x_states)) {
-        // Сохранем выбор поля данных для будущих действий
-        let data_selection = variable[0].menu().unwrap().selected();
-
         for (j, field) in variable.iter_mut().enumerate() {
             // Делаем выбранный элемент выделенным, если меню "выделено"
-            let style = if state.selected == Some(i * 4 + j) {
+            let style = if state.selected == Some(i * ROW_LENGTH + j) {
                 Style::default().fg(Color::Green)
             } else {
                 Style::default()
@@ -174,52 +250,25 @@ fn draw_graph_fields<B: Backend>(frame: &mut Frame<B>, state: &mut GraphState, a
                         ..area
                     };
 
-                    // Получаем элементы меню
-                    let items = match j {
-                        // Поля значений X
-                        0 if i == 0 => x_data_fields.clone(),
-                        // Поля значений Y
-                        0 => y_data_fields.clone(),
-                        // Поля серийников (поле значений гарантированно выбрано)
-                        1 => {
-                            // Получаем выбор с поля данных
-                            let data_selection = data_selection.unwrap();
-                            // В зависимости от переменной, сенсор надо получать из разных массивов
-                            if i == 0 {
-                                // Обрабатываем как поле X
-                                let selection = &state.x_data_fields[data_selection];
-                                let (sensor, _) = selection.split_once('/').unwrap();
-                                // Получаем серийники датчика
-                                serial_fields[sensor].clone()
-                            } else {
-                                // Обрабатываем как поле Y
-                                let selection = &state.y_data_fields[data_selection];
-                                let (sensor, _) = selection.split_once('/').unwrap();
-                                // Получаем серийники датчика
-                                serial_fields[sensor].clone()
-                            }
-                        }
-
-                        // Поля дополнительных данных Y
-                        _ => {
-                            // Получаем выбор с поля данных
-                            let data_selection = data_selection.unwrap();
-                            // Получаем название сенсора
-                            let selection = &state.y_data_fields[data_selection];
-                            let (sensor, _) = selection.split_once('/').unwrap();
-                            // Получаем поля необходимые для отображения
-                            y_data_fields_without_extra[sensor].clone()
-                        }
-                    };
+                    // Получаем элементы меню и подсказку
+                    let items = std::mem::take(&mut menus_items[i * ROW_LENGTH + j]);
+                    let placeholder = placeholders[i * ROW_LENGTH + j].take();
 
                     // Подготавливаем меню
                     let menu = Menu::new(items)
                         .list_style(Style::default().bg(Color::White).fg(Color::Black))
                         .list_highlight_style(Style::default().bg(Color::Green).fg(Color::Black))
+                        .match_style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
+                        .placeholder(placeholder)
                         .style(style);
 
                     // Рендерим меню
                     frame.render_stateful_widget(menu, field_area, menu_state);
+                    let field_area = Rect {
+                        height: 1,
+                        ..field_area
+                    };
+                    state.fields_areas.push((i * ROW_LENGTH + j, field_area));
                 }
                 // Рендерим поле ввода текста
                 GraphFieldState::Input(input_state) => {
@@ -233,6 +282,7 @@ fn draw_graph_fields<B: Backend>(frame: &mut Frame<B>, state: &mut GraphState, a
                     // Рендерим поле
                     let input = Input::new().style(style);
                     frame.render_stateful_widget(input, area, input_state);
+                    state.fields_areas.push((i * ROW_LENGTH + j, area));
                 }
                 // Пустые поля не надо рендерить
                 GraphFieldState::Hidden => (),
@@ -241,6 +291,17 @@ fn draw_graph_fields<B: Backend>(frame: &mut Frame<B>, state: &mut GraphState, a
     }
 }
 
+/// Цвета, которыми по очереди раскрашиваются графики
+const GRAPH_COLORS: [Color; 7] = [
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::Gray,
+];
+
 /// Рендерит общий чарт со всеми графиками по датасетам в состоянии
 fn draw_graph_chart<B: Backend>(frame: &mut Frame<B>, state: &mut GraphState, area: Rect) {
     // Перерисовываем чарт только когда не редактируем его, иначе это невыносимые лаги
@@ -248,37 +309,327 @@ fn draw_graph_chart<B: Backend>(frame: &mut Frame<B>, state: &mut GraphState, ar
         return;
     }
 
-    const GRAPH_COLORS: [Color; 7] = [
-        Color::Red,
-        Color::Green,
-        Color::Yellow,
-        Color::Blue,
-        Color::Magenta,
-        Color::Cyan,
-        Color::Gray,
+    // Если включена панель статистики, выделяем под неё место снизу (не больше трети высоты)
+    let area = if state.show_statistics {
+        let rows: usize = state
+            .datasets
+            .iter()
+            .map(|dataset| dataset.statistics.len())
+            .sum();
+        let height = (rows as u16 + 3).min(area.height / 3);
+        let area_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(height)])
+            .split(area);
+
+        draw_statistics_panel(frame, state, area_chunks[1]);
+        area_chunks[0]
+    } else {
+        area
+    };
+
+    // Собираем строки боковой панели: значения в точке курсора, регрессии и разности с эталоном
+    let mut lines = vec![];
+    let mut title = tr("Курсор");
+    if state.cursor.is_some() {
+        lines.extend(get_cursor_panel_lines(state));
+    }
+    let sections = [
+        (tr("Регрессия"), get_regression_panel_lines(state)),
+        (tr("Разность"), get_difference_panel_lines(state)),
+        (tr("Метки"), get_annotation_panel_lines(state)),
     ];
+    for (name, section) in sections {
+        if section.is_empty() {
+            continue;
+        }
+
+        // Первый раздел подписывается заголовком панели, а остальные отделяются своим заголовком
+        if lines.is_empty() {
+            title = name;
+        } else {
+            lines.push(Spans::default());
+            lines.push(Spans::from(name));
+        }
+        lines.extend(section);
+    }
+
+    // Если есть что показать, выделяем справа область под боковую панель
+    let area = if !lines.is_empty() {
+        let width = lines.iter().map(Spans::width).max().unwrap_or(0) as u16 + 2;
+        let area_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(width.min(area.width / 3)),
+            ])
+            .split(area);
+
+        // Рендерим панель со значениями
+        let block = Block::default().title(title).borders(Borders::LEFT);
+        let paragraph = Paragraph::new(lines).block(block);
+        frame.render_widget(paragraph, area_chunks[1]);
+
+        area_chunks[0]
+    } else {
+        area
+    };
+
+    // В точечном режиме точки не соединяются линиями
+    let graph_type = match state.dataset_scatter {
+        true => GraphType::Scatter,
+        false => GraphType::Line,
+    };
+
+    // Если есть правая ось, выделяем справа место под её подписи
+    let (x_range, y_range) = state.dataset_ranges;
+    let right_range = state.dataset_right_range;
+    let right_labels = right_range
+        .map(|right_range| [right_range.1.to_string(), right_range.0.to_string()])
+        .unwrap_or_default();
+    let (area, right_area) = match right_range {
+        Some(_) => {
+            let width = right_labels
+                .iter()
+                .map(|label| label.chars().count())
+                .max()
+                .unwrap_or(0) as u16
+                + 1;
+            let area_chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Min(0), Constraint::Length(width)])
+                .split(area);
+            (area_chunks[0], Some(area_chunks[1]))
+        }
+        None => (area, None),
+    };
+
+    // Графики правой оси переводим в масштаб левой, т.к. чарт поддерживает только одну ось Y,
+    // а на пропусках данных разрываем линии
+    let axis_range = |axis: YAxis| match (axis, right_range) {
+        (YAxis::Right, Some(right_range)) => Some(right_range),
+        _ => None,
+    };
+    let display_data: Vec<Vec<Vec<(f64, f64)>>> = state
+        .datasets
+        .iter()
+        .map(|dataset| {
+            let right_range = axis_range(dataset.axis);
+            let has_gaps = dataset.gaps.iter().any(|gaps| !gaps.is_empty());
+            if right_range.is_none() && !has_gaps {
+                return vec![];
+            }
+
+            dataset
+                .data
+                .iter()
+                .zip(&dataset.gaps)
+                .map(|(data, gaps)| {
+                    let data = match right_range {
+                        Some(right_range) => data
+                            .iter()
+                            .map(|&(x, y)| (x, rescale(y, right_range, y_range)))
+                            .collect(),
+                        None => data.clone(),
+                    };
+                    insert_gap_breaks(data, gaps)
+                })
+                .collect()
+        })
+        .collect();
+
+    // Выбросы правой оси тоже переводим в масштаб левой
+    let outlier_points: Vec<Vec<(f64, f64)>> = state
+        .datasets
+        .iter()
+        .map(|dataset| match axis_range(dataset.axis) {
+            Some(right_range) => dataset
+                .outliers
+                .iter()
+                .map(|&(x, y)| (x, rescale(y, right_range, y_range)))
+                .collect(),
+            None => dataset.outliers.clone(),
+        })
+        .collect();
+
+    // Пороги рисуем горизонтальными линиями во всю ширину графика (правой оси - в масштабе левой)
+    let threshold_lines: Vec<(String, [(f64, f64); 2])> = state
+        .datasets
+        .iter()
+        .enumerate()
+        .flat_map(|(i, dataset)| {
+            let right_range = axis_range(dataset.axis);
+            dataset.thresholds.iter().map(move |&threshold| {
+                let y = match right_range {
+                    Some(right_range) => rescale(threshold, right_range, y_range),
+                    None => threshold,
+                };
+                (
+                    trf("Y{} порог", &[&(i + 1)]),
+                    [(x_range.0, y), (x_range.1, y)],
+                )
+            })
+        })
+        .collect();
+
+    // Метки рисуем вертикальными пунктирами на их началах и концах (точек - по 4 на клетку высоты)
+    let annotation_xs: Vec<f64> = state
+        .dataset_annotations
+        .iter()
+        .flat_map(|annotation| {
+            let mut xs = vec![annotation.start as f64];
+            xs.extend(annotation.end.map(|end| end as f64));
+            xs
+        })
+        .filter(|x| (x_range.0..=x_range.1).contains(x))
+        .collect();
+    let steps = area.height.max(1) as usize * 4;
+    let annotation_points: Vec<(f64, f64)> = annotation_xs
+        .iter()
+        .flat_map(|&x| {
+            (0..=steps).map(move |k| {
+                (
+                    x,
+                    y_range.0 + (y_range.1 - y_range.0) * k as f64 / steps as f64,
+                )
+            })
+        })
+        .collect();
+
+    // Считаем линии регрессии заранее, т.к. датасеты только ссылаются на точки
+    let regression_lines: Vec<_> = state
+        .datasets
+        .iter()
+        .enumerate()
+        .flat_map(|(i, dataset)| {
+            dataset
+                .regressions
+                .iter()
+                .enumerate()
+                .map(move |(j, regression)| {
+                    // Линию правой оси обрезаем по её диапазону, а затем переводим в масштаб левой
+                    let line = match axis_range(dataset.axis) {
+                        Some(right_range) => regression
+                            .and_then(|regression| {
+                                get_regression_line(regression, x_range, right_range)
+                            })
+                            .map(|line| {
+                                line.into_iter()
+                                    .map(|(x, y)| (x, rescale(y, right_range, y_range)))
+                                    .collect()
+                            }),
+                        None => regression.and_then(|regression| {
+                            get_regression_line(regression, x_range, y_range)
+                        }),
+                    };
+                    (
+                        trf("Y{}.{} МНК", &[&(i + 1), &(j + 1)]),
+                        line.unwrap_or_default(),
+                    )
+                })
+        })
+        .collect();
 
     // Собираем датасеты
-    let color_iter = Rc::new(RefCell::new(GRAPH_COLORS.into_iter().cycle()));
-    let datasets = state
+    let mut datasets: Vec<_> = state
         .datasets
         .iter()
         .enumerate()
-        .flat_map(|(i, y_datasets)| {
-            let color_iter = color_iter.clone();
-            y_datasets.iter().enumerate().map(move |(j, dataset)| {
-                Dataset::default()
-                    .name(format!("Y{}.{}", i + 1, j + 1))
-                    .marker(Marker::Braille)
-                    .graph_type(GraphType::Line)
-                    .style(Style::default().fg(color_iter.borrow_mut().next().unwrap()))
-                    .data(dataset)
+        .flat_map(|(i, y_dataset)| {
+            // Графики правой оси помечаем в легенде
+            let suffix = match axis_range(y_dataset.axis) {
+                Some(_) => tr(" (пр.)"),
+                None => "",
+            };
+            let display = &display_data[i];
+            y_dataset.data.iter().enumerate().map(move |(j, dataset)| {
+                let dataset = display.get(j).unwrap_or(dataset);
+                (format!("Y{}.{}{suffix}", i + 1, j + 1), dataset)
             })
         })
+        .enumerate()
+        .map(|(k, (name, dataset))| {
+            Dataset::default()
+                .name(name)
+                .marker(Marker::Braille)
+                .graph_type(graph_type)
+                .style(Style::default().fg(GRAPH_COLORS[k % GRAPH_COLORS.len()]))
+                .data(dataset)
+        })
         .collect();
 
+    // Добавляем линии регрессии того же цвета, что и их точки
+    for (k, (name, line)) in regression_lines.iter().enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+
+        let dataset = Dataset::default()
+            .name(name.as_str())
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(GRAPH_COLORS[k % GRAPH_COLORS.len()]))
+            .data(line);
+        datasets.push(dataset);
+    }
+
+    // Подсвечиваем помеченные выбросами точки каждой функции
+    for (i, points) in outlier_points.iter().enumerate() {
+        if points.is_empty() {
+            continue;
+        }
+
+        let dataset = Dataset::default()
+            .name(trf("Y{} выбросы", &[&(i + 1)]))
+            .marker(Marker::Dot)
+            .graph_type(GraphType::Scatter)
+            .style(Style::default().fg(Color::LightRed))
+            .data(points);
+        datasets.push(dataset);
+    }
+
+    // Добавляем линии порогов
+    for (name, line) in &threshold_lines {
+        let dataset = Dataset::default()
+            .name(name.as_str())
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::Yellow))
+            .data(line);
+        datasets.push(dataset);
+    }
+
+    // Добавляем метки событий
+    if !annotation_points.is_empty() {
+        let dataset = Dataset::default()
+            .name(tr("Метки"))
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Scatter)
+            .style(Style::default().fg(Color::Magenta))
+            .data(&annotation_points);
+        datasets.push(dataset);
+    }
+
+    // Добавляем вертикальную линию курсора
+    let cursor_line = state
+        .cursor_x()
+        .map(|x| [(x, y_range.0), (x, y_range.1)])
+        .unwrap_or_default();
+    if state.cursor.is_some() {
+        let dataset = Dataset::default()
+            .name(tr("Курсор"))
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::White))
+            .data(&cursor_line);
+        datasets.push(dataset);
+    }
+
+    // Подготавливаем подписи к осям
+    let x_labels = [x_range.0.to_string(), x_range.1.to_string()];
+    let y_labels = [y_range.0.to_string(), y_range.1.to_string()];
+
     // Создаём виджет чарта
-    let (x_range, y_range) = state.dataset_ranges;
     let chart = Chart::new(datasets)
         .hidden_legend_constraints((Constraint::Ratio(1, 4), Constraint::Ratio(1, 1)))
         .x_axis(
@@ -286,28 +637,312 @@ fn draw_graph_chart<B: Backend>(frame: &mut Frame<B>, state: &mut GraphState, ar
                 .title(Span::styled("X", Style::default().fg(Color::Red)))
                 .style(Style::default().fg(Color::White))
                 .bounds(x_range.into())
-                .labels(
-                    [x_range.0.to_string(), x_range.1.to_string()]
-                        .iter()
-                        .cloned()
-                        .map(Span::from)
-                        .collect(),
-                ),
+                .labels(x_labels.iter().cloned().map(Span::from).collect()),
         )
         .y_axis(
             Axis::default()
                 .title(Span::styled("Y", Style::default().fg(Color::Red)))
                 .style(Style::default().fg(Color::White))
                 .bounds(y_range.into())
-                .labels(
-                    [y_range.0.to_string(), y_range.1.to_string()]
-                        .iter()
-                        .cloned()
-                        .map(Span::from)
-                        .collect(),
-                ),
+                .labels(y_labels.iter().cloned().map(Span::from).collect()),
         );
 
-    // Рендерим
+    // Рендерим и запоминаем область самого графика, чтобы переводить положение мыши в значения
+    let chart = MeasuredChart {
+        chart,
+        graph_area: &mut state.chart_area,
+    };
     frame.render_widget(chart, area);
+
+    // Подписываем метки их текстом внизу графика, справа от начала метки
+    let chart_area = state.chart_area;
+    for annotation in &state.dataset_annotations {
+        let x = annotation.start as f64;
+        if !(x_range.0..=x_range.1).contains(&x) || chart_area.width == 0 {
+            continue;
+        }
+
+        let ratio = (x - x_range.0) / (x_range.1 - x_range.0).max(f64::EPSILON);
+        let column = chart_area.x + (ratio * (chart_area.width - 1) as f64).round() as u16;
+        let width = chart_area.right().saturating_sub(column);
+        let y = chart_area.bottom().saturating_sub(1);
+        let paragraph =
+            Paragraph::new(annotation.label()).style(Style::default().fg(Color::Magenta));
+        frame.render_widget(paragraph, Rect::new(column, y, width, 1));
+    }
+
+    // Рендерим правую ось: линию и подписи напротив верха и низа графика
+    if let Some(right_area) = right_area {
+        let top = state.chart_area.top();
+        let bottom = state.chart_area.bottom().saturating_sub(1);
+        for y in top..=bottom {
+            let text = match y {
+                y if y == top => format!("┤{}", right_labels[0]),
+                y if y == bottom => format!("┤{}", right_labels[1]),
+                _ => "│".to_string(),
+            };
+            let paragraph = Paragraph::new(text).style(Style::default().fg(Color::White));
+            frame.render_widget(paragraph, Rect::new(right_area.x, y, right_area.width, 1));
+        }
+    }
+}
+
+/// Рендерит панель со статистикой каждого графика в текущем диапазоне X
+fn draw_statistics_panel<B: Backend>(frame: &mut Frame<B>, state: &GraphState, area: Rect) {
+    const HEADER: [&str; 10] = [
+        "График",
+        "Кол-во",
+        "Мин.",
+        "Макс.",
+        "Среднее",
+        "Медиана",
+        "Ст. откл.",
+        "Первая запись",
+        "Последняя запись",
+        "Пропуски",
+    ];
+
+    // Форматируем значения так же, как в панели курсора
+    let value = |value: Option<f64>| value.map_or("-".to_owned(), |value| format!("{value:.2}"));
+    let date = |date: Option<i64>| {
+        date.and_then(|date| NaiveDateTime::from_timestamp_opt(date, 0))
+            .map_or("-".to_owned(), |date| {
+                date.format("%Y-%m-%d %H:%M:%S").to_string()
+            })
+    };
+
+    // Собираем строки таблицы: по строке на каждый запрос каждой функции
+    let rows = state.datasets.iter().enumerate().flat_map(|(i, dataset)| {
+        dataset.statistics.iter().map(move |statistics| {
+            Row::new([
+                format!("Y{} {}", i + 1, statistics.label),
+                statistics.count.to_string(),
+                value(statistics.min),
+                value(statistics.max),
+                value(statistics.mean),
+                value(statistics.median),
+                value(statistics.stddev),
+                date(statistics.first),
+                date(statistics.last),
+                statistics.gaps.to_string(),
+            ])
+        })
+    });
+
+    // Первый столбец с подписью шире остальных
+    let widths = [Constraint::Percentage(22)]
+        .into_iter()
+        .chain([Constraint::Percentage(7); 6])
+        .chain([Constraint::Percentage(13); 2])
+        .chain([Constraint::Percentage(10)])
+        .collect::<Vec<_>>();
+
+    let table = Table::new(rows)
+        .header(Row::new(HEADER.map(tr)).style(Style::default().fg(Color::Yellow)))
+        .block(
+            Block::default()
+                .title(tr("Статистика"))
+                .borders(Borders::TOP),
+        )
+        .widths(&widths);
+    frame.render_widget(table, area);
+}
+
+/// Возвращает строки панели со значениями графиков в точке курсора
+fn get_cursor_panel_lines(state: &GraphState) -> Vec<Spans<'static>> {
+    let x = state.cursor_x().unwrap();
+    let mut lines = vec![Spans::from(format!("X: {}", format_x_value(state, x)))];
+
+    // Для каждого графика выводим ближайшее к курсору значение
+    for (k, (i, j, value)) in state.cursor_values().into_iter().enumerate() {
+        let label = &state.datasets[i].labels[j];
+        let value = match value {
+            Some(y) => format!("{y:.2}"),
+            None => "-".to_owned(),
+        };
+
+        let style = Style::default().fg(GRAPH_COLORS[k % GRAPH_COLORS.len()]);
+        let text = format!("Y{}.{} {label}: {value}", i + 1, j + 1);
+        lines.push(Spans::from(Span::styled(text, style)));
+    }
+
+    lines
+}
+
+/// Возвращает строки панели с метками, на которые указывает курсор
+fn get_annotation_panel_lines(state: &GraphState) -> Vec<Spans<'static>> {
+    let mut lines = vec![];
+    for annotation in state.annotations_at_cursor() {
+        let style = Style::default().fg(Color::Magenta);
+        lines.push(Spans::from(Span::styled(annotation.label(), style)));
+
+        let start = format_date(annotation.start);
+        let dates = match annotation.end {
+            Some(end) => format!("  {start} - {}", format_date(end)),
+            None => format!("  {start}"),
+        };
+        lines.push(Spans::from(dates));
+    }
+
+    lines
+}
+
+/// Возвращает строки панели с регрессиями графиков в точечном режиме
+fn get_regression_panel_lines(state: &GraphState) -> Vec<Spans<'static>> {
+    let mut lines = vec![];
+    let mut k = 0;
+    for (i, dataset) in state.datasets.iter().enumerate() {
+        for (j, regression) in dataset.regressions.iter().enumerate() {
+            let style = Style::default().fg(GRAPH_COLORS[k % GRAPH_COLORS.len()]);
+            k += 1;
+
+            // Регрессия может быть не определена, например, если все X одинаковые
+            let regression = match regression {
+                Some(regression) => regression,
+                None => continue,
+            };
+
+            let label = &dataset.labels[j];
+            let sign = if regression.intercept < 0. { '-' } else { '+' };
+            lines.push(Spans::from(Span::styled(
+                format!("Y{}.{} {label}", i + 1, j + 1),
+                style,
+            )));
+            lines.push(Spans::from(format!(
+                "  y = {:.4}·x {sign} {:.4}",
+                regression.slope,
+                regression.intercept.abs()
+            )));
+            lines.push(Spans::from(format!(
+                "  r² = {:.4}, n = {}",
+                regression.r2, regression.count
+            )));
+        }
+    }
+
+    lines
+}
+
+/// Возвращает строки панели со смещением и СКО графиков-разностей с эталоном
+fn get_difference_panel_lines(state: &GraphState) -> Vec<Spans<'static>> {
+    let mut lines = vec![];
+    let mut k = 0;
+    for (i, dataset) in state.datasets.iter().enumerate() {
+        for (j, difference) in dataset.differences.iter().enumerate() {
+            let style = Style::default().fg(GRAPH_COLORS[k % GRAPH_COLORS.len()]);
+            k += 1;
+
+            // Смещения нет, если график не разность или у эталона нет совпадающих записей
+            let Some(difference) = difference else {
+                continue;
+            };
+
+            let label = &dataset.labels[j];
+            lines.push(Spans::from(Span::styled(
+                format!("Y{}.{} {label}", i + 1, j + 1),
+                style,
+            )));
+            lines.push(Spans::from(trf(
+                "  смещ. = {}, СКО = {}",
+                &[
+                    &format!("{:.4}", difference.bias),
+                    &format!("{:.4}", difference.rmse),
+                ],
+            )));
+            lines.push(Spans::from(format!("  n = {}", difference.count)));
+        }
+    }
+
+    lines
+}
+
+/// Возвращает отрезок линии регрессии, который помещается в границы графика
+fn get_regression_line(
+    regression: Regression,
+    x_range: (f64, f64),
+    y_range: (f64, f64),
+) -> Option<Vec<(f64, f64)>> {
+    let y = |x: f64| regression.slope * x + regression.intercept;
+
+    // Находим промежуток X, на котором прямая не выходит за границы Y
+    let (mut x_min, mut x_max) = x_range;
+    if regression.slope != 0. {
+        let x_a = (y_range.0 - regression.intercept) / regression.slope;
+        let x_b = (y_range.1 - regression.intercept) / regression.slope;
+        x_min = x_min.max(x_a.min(x_b));
+        x_max = x_max.min(x_a.max(x_b));
+    } else if y(x_min) < y_range.0 || y(x_min) > y_range.1 {
+        return None;
+    }
+
+    match x_min <= x_max {
+        true => Some(vec![(x_min, y(x_min)), (x_max, y(x_max))]),
+        false => None,
+    }
+}
+
+/// Разрывает линию на пропусках: перед первой точкой после пропуска вставляет точку за пределами графика,
+/// т.к. чарт не рисует отрезки, один из концов которых не виден
+fn insert_gap_breaks(points: Vec<(f64, f64)>, gaps: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    if gaps.is_empty() {
+        return points;
+    }
+
+    let mut result = Vec::with_capacity(points.len() + gaps.len());
+    let mut gaps = gaps.iter().peekable();
+    for (x, y) in points {
+        // Все пропуски, закончившиеся к этой точке, уже позади
+        let mut has_break = false;
+        while gaps.next_if(|&&(_, end)| end <= x).is_some() {
+            has_break = true;
+        }
+        if has_break && !result.is_empty() {
+            result.push((x, f64::INFINITY));
+        }
+        result.push((x, y));
+    }
+
+    result
+}
+
+/// Форматирует значение X для отображения (дату переводит в читаемый вид)
+fn format_x_value(state: &GraphState, x: f64) -> String {
+    if !state.dataset_x_is_date {
+        return x.to_string();
+    }
+
+    match NaiveDateTime::from_timestamp_opt(x as i64, 0) {
+        Some(date) => date.format("%Y-%m-%d %H:%M:%S").to_string(),
+        None => x.to_string(),
+    }
+}
+
+/// Чарт, который после отрисовки запоминает область самого графика (без осей и подписей)
+struct MeasuredChart<'a, 'b> {
+    /// Сам чарт
+    chart: Chart<'a>,
+
+    /// Куда записать область графика
+    graph_area: &'b mut Rect,
+}
+
+impl Widget for MeasuredChart<'_, '_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        self.chart.render(area, buf);
+
+        // Разметку чарта не повторяем, а находим по уже нарисованному углу осей:
+        // сам график находится правее оси Y и выше оси X. Ищем снизу, т.к. у легенды тоже есть угол
+        let corner = (area.top()..area.bottom()).rev().find_map(|y| {
+            (area.left()..area.right())
+                .find(|&x| buf.get(x, y).symbol == symbols::line::BOTTOM_LEFT)
+                .map(|x| (x, y))
+        });
+
+        *self.graph_area = match corner {
+            Some((x, y)) if x + 1 < area.right() && y > area.top() => {
+                Rect::new(x + 1, area.top(), area.right() - x - 1, y - area.top())
+            }
+            _ => Rect::default(),
+        };
+    }
 }
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+
+use super::state::{GraphFieldState, GraphState, ROW_LENGTH};
+
+/// Конфигурация вкладки графика, сохранённая по названиям полей и серийников (а не по индексам в меню).
+/// Благодаря этому вид остаётся верным, даже если в базе данных появились новые датчики
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GraphView {
+    /// Значения полей X (`None` у скрытых полей и невыбранных меню)
+    pub x: Vec<Option<String>>,
+
+    /// Значения полей каждого Y
+    pub ys: Vec<Vec<Option<String>>>,
+
+    /// Флаг точечного режима
+    #[serde(default)]
+    pub scatter: bool,
+
+    /// Флаг панели статистики
+    #[serde(default)]
+    pub statistics: bool,
+
+    /// Флаг разрывов линий на пропусках данных
+    #[serde(default)]
+    pub gap_breaks: bool,
+
+    /// Флаг исключения выбросов
+    #[serde(default)]
+    pub exclude_outliers: bool,
+
+    /// Флаг применения поправок приборов
+    #[serde(default)]
+    pub calibrated: bool,
+}
+
+impl GraphView {
+    /// Переводит вид в JSON для сохранения
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Читает вид из JSON
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+}
+
+impl GraphState {
+    /// Возвращает текущую конфигурацию вкладки в виде вида
+    pub fn get_view(&self) -> GraphView {
+        let row = |row: usize| -> Vec<Option<String>> {
+            (0..ROW_LENGTH)
+                .map(|col| self.get_field_value(row * ROW_LENGTH + col))
+                .collect()
+        };
+
+        GraphView {
+            x: row(0),
+            ys: (1..=self.ys_states.len()).map(row).collect(),
+            scatter: self.scatter,
+            statistics: self.show_statistics,
+            gap_breaks: self.gap_breaks,
+            exclude_outliers: self.exclude_outliers,
+            calibrated: self.calibrated,
+        }
+    }
+
+    /// Возвращает значение поля с индексом в том виде, в каком его видит пользователь
+    pub fn get_field_value(&self, field_index: usize) -> Option<String> {
+        match self.field_state(field_index) {
+            GraphFieldState::Menu(menu_state) => {
+                let selected = menu_state.selected()?;
+                self.get_menu_items(field_index).into_iter().nth(selected)
+            }
+            GraphFieldState::Input(input_state) => match input_state.content() {
+                "" => None,
+                content => Some(content.to_owned()),
+            },
+            GraphFieldState::Hidden => None,
+        }
+    }
+}
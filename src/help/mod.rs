@@ -0,0 +1,37 @@
+use crossterm::event::{KeyCode, KeyEvent};
+
+use crate::app::{keymap::KeyContext, state::App};
+
+use self::state::HelpState;
+
+pub mod state;
+pub mod ui;
+
+/// Количество строк, на которое перемещают `PageUp` и `PageDown`
+const PAGE_LENGTH: isize = 10;
+
+impl App<'_> {
+    /// Открывает справку по клавишам данного режима
+    pub fn open_help(&mut self, context: KeyContext) {
+        self.help_state = Some(HelpState::new(context, &self.keymap));
+    }
+
+    /// Обрабатывает все события, связанные с нажатием клавиш в окне справки
+    pub fn on_key_event_help(&mut self, event: KeyEvent) {
+        // Получаем состояние окна справки
+        let state = self.help_state.as_mut().unwrap();
+
+        match event.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('?') | KeyCode::F(1) => {
+                self.help_state = None
+            }
+            KeyCode::Up => state.move_selection(-1),
+            KeyCode::Down => state.move_selection(1),
+            KeyCode::PageUp => state.move_selection(-PAGE_LENGTH),
+            KeyCode::PageDown => state.move_selection(PAGE_LENGTH),
+            KeyCode::Home => state.move_selection(-(state.rows.len() as isize)),
+            KeyCode::End => state.move_selection(state.rows.len() as isize),
+            _ => (),
+        }
+    }
+}
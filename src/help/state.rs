@@ -0,0 +1,56 @@
+use itertools::Itertools;
+use tui::widgets::TableState;
+
+use crate::{
+    app::keymap::{KeyContext, Keymap},
+    locale::tr,
+};
+
+/// Описывает состояние окна справки по клавишам
+#[derive(Debug)]
+pub struct HelpState {
+    /// Сохраняет режим, для которого открыта справка
+    pub context: KeyContext,
+
+    /// Сохраняет строки справки: клавиши и описание действия
+    pub rows: Vec<[String; 2]>,
+
+    /// Сохраняет состояние таблицы (выбранную строку и прокрутку)
+    pub table_state: TableState,
+}
+
+impl HelpState {
+    /// Создаёт справку по действующим в режиме клавишам
+    pub fn new(context: KeyContext, keymap: &Keymap) -> Self {
+        let rows: Vec<_> = keymap
+            .bindings(context)
+            .into_iter()
+            .map(|(action, bindings)| {
+                let keys = bindings.iter().map(ToString::to_string).join(", ");
+                [keys, tr(action.description(context)).to_owned()]
+            })
+            .collect();
+
+        let mut table_state = TableState::default();
+        if !rows.is_empty() {
+            table_state.select(Some(0));
+        }
+
+        Self {
+            context,
+            rows,
+            table_state,
+        }
+    }
+
+    /// Выбирает строку, отстоящую от текущей на `offset` (с ограничением по краям)
+    pub fn move_selection(&mut self, offset: isize) {
+        if self.rows.is_empty() {
+            return;
+        }
+
+        let selected = self.table_state.selected().unwrap_or(0) as isize;
+        let selected = (selected + offset).clamp(0, self.rows.len() as isize - 1);
+        self.table_state.select(Some(selected as usize));
+    }
+}
@@ -0,0 +1,54 @@
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::Text,
+    widgets::{Block, Borders, Clear, Paragraph, Row, Table},
+    Frame,
+};
+
+use crate::{
+    locale::{tr, trf},
+    ui::utils::get_popup_area,
+};
+
+use super::state::HelpState;
+
+/// Рендерит окно справки по клавишам
+pub fn draw_help<B: Backend>(frame: &mut Frame<B>, state: &mut HelpState, area: Rect) {
+    // Выделяем область под окошко и очищаем её, чтобы не видеть вкладку под ним
+    let popup_area = get_popup_area(70, 80, area);
+    frame.render_widget(Clear, popup_area);
+
+    // Делаем блок (в заголовке - режим, для которого показана справка)
+    let title = trf("Справка: {}", &[&tr(state.context.title())]);
+    let block = Block::default().title(title).borders(Borders::ALL);
+    let inner_area = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    // Снизу подсказка о символах без действия и о закрытии окна
+    let area_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(inner_area);
+
+    let rows = state.rows.iter().map(|row| Row::new(row.clone()));
+    let widths = [Constraint::Percentage(30), Constraint::Percentage(70)];
+    let table = Table::new(rows)
+        .header(Row::new([tr("Клавиши"), tr("Действие")]).style(Style::default().fg(Color::Yellow)))
+        .highlight_style(
+            Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD),
+        )
+        .widths(&widths);
+    frame.render_stateful_widget(table, area_chunks[0], &mut state.table_state);
+
+    let close_hint = tr("Esc - закрыть справку");
+    let text = match state.context.typing_hint() {
+        Some(hint) => format!("{}. {close_hint}", tr(hint)),
+        None => close_hint.to_owned(),
+    };
+    let paragraph = Paragraph::new(Text::from(text)).style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(paragraph, area_chunks[1]);
+}
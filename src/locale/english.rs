@@ -0,0 +1,611 @@
+/// Переводы строк интерфейса на английский язык: (русская строка, английская строка).
+/// Подстановки `{}` должны идти в том же порядке, что и в русской строке
+pub const MESSAGES: &[(&str, &str)] = &[
+    // Вкладки и общие надписи
+    ("Практика", "Practice"),
+    ("Датчики", "Sensors"),
+    ("График {}", "Graph {}"),
+    ("<значение не выбрано>", "<no value selected>"),
+    ("{} д {}", "{} d {}"),
+    ("Ожидалось число", "Expected a number"),
+    (
+        "Ожидалась дата формата %Y-%m-%d %H:%M:%S",
+        "Expected a date in %Y-%m-%d %H:%M:%S format",
+    ),
+    (
+        "Нет прибора с таким серийником",
+        "No device with this serial",
+    ),
+    // Дерево датчиков и импорт
+    ("Поля", "Fields"),
+    ("Серийники", "Serials"),
+    (
+        "--- Данные датчиков не импортированы ---",
+        "--- No sensor data imported ---",
+    ),
+    ("Выбор файла для импорта", "Select a file to import"),
+    (
+        "Данные импортируются, подождите...",
+        "Importing data, please wait...",
+    ),
+    ("--- Директория пуста --", "--- Directory is empty ---"),
+    (
+        "Новых превышений после импорта: {}",
+        "New threshold violations after import: {}",
+    ),
+    (
+        "Ошибка проверки новых записей: {}",
+        "Failed to check the new records: {}",
+    ),
+    // Столбцы отчётов
+    ("Датчик", "Sensor"),
+    ("Поле", "Field"),
+    ("Серийник", "Serial"),
+    ("Порог", "Threshold"),
+    ("Начало", "Start"),
+    ("Конец", "End"),
+    ("Длительность", "Duration"),
+    ("Пик", "Peak"),
+    ("Текст", "Text"),
+    ("Обычный шаг", "Usual step"),
+    // Пропуски данных
+    ("Пропуски данных ({})", "Data gaps ({})"),
+    ("--- Пропусков не найдено ---", "--- No gaps found ---"),
+    ("Не удалось найти пропуски: {}", "Failed to find gaps: {}"),
+    (
+        "Пропуск - промежуток между записями прибора, который более чем в {} раза больше обычного",
+        "A gap is an interval between device records more than {} times longer than usual",
+    ),
+    // Выбросы
+    ("Выбросы: {}/{}", "Outliers: {}/{}"),
+    ("Мин. значение", "Min value"),
+    ("Макс. значение", "Max value"),
+    ("Окно Z-оценки, записей", "Z-score window, records"),
+    ("Порог Z-оценки", "Z-score threshold"),
+    ("Макс. скорость, ед./мин", "Max rate, units/min"),
+    ("Диапазон", "Range"),
+    ("Скорость", "Rate"),
+    ("Помечено записей: {}", "Records flagged: {}"),
+    (
+        "Не удалось загрузить правило: {}",
+        "Failed to load the rule: {}",
+    ),
+    (
+        "Не удалось сохранить правило: {}",
+        "Failed to save the rule: {}",
+    ),
+    (
+        "В параметрах правила есть ошибки",
+        "The rule parameters have errors",
+    ),
+    (
+        "Окно должно быть больше 2 записей",
+        "The window must be longer than 2 records",
+    ),
+    (
+        "Порог задаётся только вместе с окном",
+        "The threshold requires a window",
+    ),
+    (
+        "Пустое поле выключает проверку. Z-оценка считается по медиане и MAD окна \
+         (порог по умолчанию - {}), а скорость - к соседним записям прибора. \
+         Enter - сохранить правило и пометить выбросы, Esc - закрыть окно.",
+        "An empty field disables the check. The Z-score uses the median and MAD of the window \
+         (default threshold is {}), and the rate is relative to neighbouring device records. \
+         Enter - save the rule and flag outliers, Esc - close the window.",
+    ),
+    // Поправки приборов
+    ("Поправки: {}/{}", "Calibrations: {}/{}"),
+    ("Коэффициенты a0 a1 …", "Coefficients a0 a1 …"),
+    ("Действует с", "Valid from"),
+    ("Действует по", "Valid to"),
+    ("Эталонный серийник", "Reference serial"),
+    ("Степень подбора", "Fit degree"),
+    ("Сохранённых поправок нет", "No saved calibrations"),
+    ("Сохранённые поправки", "Saved calibrations"),
+    ("{}: {} (с {} по {})", "{}: {} (from {} to {})"),
+    (
+        "Ожидались числа через пробел",
+        "Expected space-separated numbers",
+    ),
+    (
+        "Ожидалось целое число от 1 до {}",
+        "Expected an integer from 1 to {}",
+    ),
+    (
+        "Эталон должен быть другим прибором",
+        "The reference must be another device",
+    ),
+    (
+        "Поправка прибора {} удалена",
+        "Calibration of device {} deleted",
+    ),
+    (
+        "Поправка прибора {} сохранена",
+        "Calibration of device {} saved",
+    ),
+    (
+        "Не удалось загрузить поправки: {}",
+        "Failed to load calibrations: {}",
+    ),
+    (
+        "Не удалось сохранить поправку: {}",
+        "Failed to save the calibration: {}",
+    ),
+    (
+        "Промежуток пересекается с поправкой прибора с {} по {}",
+        "The interval overlaps the device calibration from {} to {}",
+    ),
+    (
+        "Не удалось подобрать поправку: {}",
+        "Failed to fit the calibration: {}",
+    ),
+    (
+        "В параметрах поправки есть ошибки",
+        "The calibration parameters have errors",
+    ),
+    (
+        "В параметрах подбора есть ошибки",
+        "The fit parameters have errors",
+    ),
+    (
+        "Подобрано по {} парам записей, R² = {}. Enter - сохранить",
+        "Fitted on {} record pairs, R² = {}. Enter - save",
+    ),
+    (
+        "Недостаточно совпадающих записей с эталоном",
+        "Not enough records matching the reference",
+    ),
+    (
+        "x - исходное значение прибора. Пустые даты не ограничивают промежуток, \
+         пустые коэффициенты удаляют поправку. Ctrl+F - подобрать поправку по эталону \
+         (в промежутке поправки), Enter - сохранить поправку, Esc - закрыть окно.",
+        "x is the raw device value. Empty dates leave the interval open, \
+         empty coefficients delete the calibration. Ctrl+F - fit the calibration to the reference \
+         (within the calibration interval), Enter - save the calibration, Esc - close the window.",
+    ),
+    // Пороги и превышения
+    ("Пороги: {}/{}", "Thresholds: {}/{}"),
+    ("Нижний порог", "Lower threshold"),
+    ("Верхний порог", "Upper threshold"),
+    ("Превышения порогов ({})", "Threshold violations ({})"),
+    (
+        "--- Превышений не найдено ---",
+        "--- No violations found ---",
+    ),
+    ("Превышений порогов: {}", "Threshold violations: {}"),
+    ("В порогах есть ошибки", "The thresholds have errors"),
+    (
+        "Не удалось загрузить пороги: {}",
+        "Failed to load thresholds: {}",
+    ),
+    (
+        "Не удалось сохранить пороги: {}",
+        "Failed to save thresholds: {}",
+    ),
+    (
+        "Не удалось загрузить превышения: {}",
+        "Failed to load violations: {}",
+    ),
+    (
+        "Превышение - записи прибора подряд ниже нижнего или выше верхнего порога. \
+         Пустое поле выключает порог. Enter - сохранить пороги и найти превышения, \
+         Esc - закрыть окно.",
+        "A violation is a run of device records below the lower or above the upper threshold. \
+         An empty field disables the threshold. Enter - save thresholds and find violations, \
+         Esc - close the window.",
+    ),
+    // Поля графика
+    ("Правка", "Edit"),
+    ("Поле данных", "Data field"),
+    ("Мин.знач./Вход 1", "Min/Input 1"),
+    ("Макс.знач./Вход 2", "Max/Input 2"),
+    ("Агрегация/Ось", "Aggr./Axis"),
+    ("Совмещение/Ось", "Join/Axis"),
+    ("Статистика/Нормализация", "Stat./Norm."),
+    ("Допуск, с/Нормализация", "Tol., s/Norm."),
+    ("Фильтр", "Filter"),
+    ("Окно", "Window"),
+    ("Эталон", "Reference"),
+    // Элементы меню графика
+    ("Выражение", "Expression"),
+    ("Все", "All"),
+    ("Разброс", "Spread"),
+    ("Средн.", "Mean"),
+    ("Мин.", "Min"),
+    ("Макс.", "Max"),
+    ("Без агрегации", "No aggregation"),
+    ("По минутам", "By minute"),
+    ("По часам", "By hour"),
+    ("По дням", "By day"),
+    ("По неделям", "By week"),
+    ("По месяцам", "By month"),
+    ("Среднее", "Average"),
+    ("Минимум", "Minimum"),
+    ("Максимум", "Maximum"),
+    ("Медиана", "Median"),
+    ("Сумма", "Sum"),
+    ("Количество", "Count"),
+    ("Станд. откл.", "Std. dev."),
+    ("Точное", "Exact"),
+    ("Ближайшее", "Nearest"),
+    ("Сетка", "Grid"),
+    ("Левая", "Left"),
+    ("Правая", "Right"),
+    ("Без нормализации", "No normalization"),
+    ("Z-оценка", "Z-score"),
+    ("От 0 до 1", "0 to 1"),
+    ("% от первого", "% of first"),
+    ("Без фильтра", "No filter"),
+    ("Скольз. среднее", "Moving average"),
+    ("Скольз. медиана", "Rolling median"),
+    ("Эксп. среднее", "Exp. average"),
+    ("Без эталона", "No reference"),
+    // Производные величины (их внутренние названия в видах не меняются)
+    ("Эфф. темп.", "Eff. temp."),
+    ("Точка росы", "Dew point"),
+    ("Индекс жары", "Heat index"),
+    ("Абс. влажн.", "Abs. humidity"),
+    ("Ветр. охлажд.", "Wind chill"),
+    ("Темп. в °F", "Temp. in °F"),
+    ("Темп. в K", "Temp. in K"),
+    ("Давл. в мм рт. ст.", "Press. in mmHg"),
+    // Входные поля производных величин
+    ("Температура, °C", "Temperature, °C"),
+    ("Влажность, %", "Humidity, %"),
+    ("Скорость ветра, м/с", "Wind speed, m/s"),
+    ("Давление, гПа", "Pressure, hPa"),
+    // Подписи и ошибки полей графика
+    ("поправка", "calibrated"),
+    ("{} т.", "{} pts"),
+    ("{} с", "{} s"),
+    ("Загрузка данных: {} из {}", "Loading data: {} of {}"),
+    ("Ошибка загрузки данных: {}", "Failed to load data: {}"),
+    (
+        "Нельзя нормализовать \"{}\": нет ненулевых значений",
+        "Cannot normalize \"{}\": there are no non-zero values",
+    ),
+    (
+        "Поток загрузки данных завершился аварийно",
+        "The data loading thread crashed",
+    ),
+    (
+        "Ожидалось целое положительное число секунд",
+        "Expected a positive integer number of seconds",
+    ),
+    (
+        "Окно во времени доступно, только если X - дата",
+        "A time window is only available when X is a date",
+    ),
+    (
+        "Ожидалось число точек или время (например, 30s, 10m, 2h, 1d)",
+        "Expected a number of points or a time (e.g. 30s, 10m, 2h, 1d)",
+    ),
+    (
+        "Лишний символ (позиция {})",
+        "Unexpected character (position {})",
+    ),
+    (
+        "Выражение должно содержать поле датчика",
+        "The expression must contain a sensor field",
+    ),
+    (
+        "Неверное число \"{}\" (позиция {})",
+        "Invalid number \"{}\" (position {})",
+    ),
+    (
+        "Неизвестный символ '{}' (позиция {})",
+        "Unknown character '{}' (position {})",
+    ),
+    (
+        "Неожиданный конец выражения (позиция {})",
+        "Unexpected end of expression (position {})",
+    ),
+    ("Ожидалось {} (позиция {})", "Expected {} (position {})"),
+    (
+        "Ожидалось значение (позиция {})",
+        "Expected a value (position {})",
+    ),
+    (
+        "Неизвестная функция \"{}\" (позиция {})",
+        "Unknown function \"{}\" (position {})",
+    ),
+    (
+        "Функция \"{}\" принимает аргументов: {} (позиция {})",
+        "Function \"{}\" takes {} arguments (position {})",
+    ),
+    (
+        "Неизвестное поле \"{}\" (позиция {})",
+        "Unknown field \"{}\" (position {})",
+    ),
+    (
+        "Неизвестный датчик \"{}\" (позиция {})",
+        "Unknown sensor \"{}\" (position {})",
+    ),
+    (
+        "Поле \"{}\" есть у нескольких датчиков, укажите \"{}/{}\" (позиция {})",
+        "Several sensors have field \"{}\", specify \"{}/{}\" (position {})",
+    ),
+    // Чарт и его панели
+    ("Курсор", "Cursor"),
+    ("Регрессия", "Regression"),
+    ("Разность", "Difference"),
+    ("Метки", "Annotations"),
+    ("Y{} порог", "Y{} threshold"),
+    ("Y{}.{} МНК", "Y{}.{} fit"),
+    (" (пр.)", " (right)"),
+    ("Y{} выбросы", "Y{} outliers"),
+    ("  смещ. = {}, СКО = {}", "  bias = {}, RMSE = {}"),
+    ("Статистика", "Statistics"),
+    ("График", "Graph"),
+    ("Кол-во", "Count"),
+    ("Ст. откл.", "Std. dev."),
+    ("Первая запись", "First record"),
+    ("Последняя запись", "Last record"),
+    ("Пропуски", "Gaps"),
+    // Сохранённые виды
+    ("Сохранение вида", "Save view"),
+    ("Сохранённые виды", "Saved views"),
+    ("Название:", "Name:"),
+    ("--- Сохранённых видов нет ---", "--- No saved views ---"),
+    ("Введите название вида", "Enter a view name"),
+    ("Не удалось получить виды: {}", "Failed to get views: {}"),
+    (
+        "Не удалось сохранить вид: {}",
+        "Failed to save the view: {}",
+    ),
+    ("Не удалось открыть вид: {}", "Failed to open the view: {}"),
+    (
+        "Не удалось удалить вид: {}",
+        "Failed to delete the view: {}",
+    ),
+    // Метки событий
+    ("Изменение метки", "Edit annotation"),
+    ("Новая метка", "New annotation"),
+    ("Введите текст метки", "Enter the annotation text"),
+    ("Введите дату события", "Enter the event date"),
+    (
+        "Конец должен быть не раньше начала",
+        "The end must not be before the start",
+    ),
+    ("В метке есть ошибки", "The annotation has errors"),
+    ("Метка ещё не сохранена", "The annotation is not saved yet"),
+    (
+        "Не удалось сохранить метку: {}",
+        "Failed to save the annotation: {}",
+    ),
+    (
+        "Не удалось удалить метку: {}",
+        "Failed to delete the annotation: {}",
+    ),
+    (
+        "Даты в формате %Y-%m-%d %H:%M:%S. Пустой конец - событие в один момент, \
+         пустой серийник - метка для всех приборов. PageUp/PageDown - другие метки \
+         в точке курсора, Enter - сохранить метку, Ctrl+D - удалить её, Esc - закрыть окно.",
+        "Dates in %Y-%m-%d %H:%M:%S format. An empty end means a single moment, \
+         an empty serial means all devices. PageUp/PageDown - other annotations \
+         at the cursor, Enter - save the annotation, Ctrl+D - delete it, Esc - close the window.",
+    ),
+    // Справка по клавишам
+    ("Справка: {}", "Help: {}"),
+    ("Клавиши", "Keys"),
+    ("Действие", "Action"),
+    ("Esc - закрыть справку", "Esc - close help"),
+    ("Дерево сенсоров", "Sensor tree"),
+    (
+        "Окно импорта файла/директории",
+        "File/directory import window",
+    ),
+    ("Режим редактирования графика", "Graph edit mode"),
+    ("Поле \"Ввод текста\"", "\"Text input\" field"),
+    ("Поле \"Меню\"", "\"Menu\" field"),
+    ("Окно отчёта о пропусках", "Gaps report window"),
+    ("Окно превышений порогов", "Threshold violations window"),
+    ("Окно сохранённых видов", "Saved views window"),
+    ("Окно сохранения вида", "Save view window"),
+    ("Окно метки события", "Annotation window"),
+    ("Окно правила поиска выбросов", "Outlier rule window"),
+    ("Окно поправок приборов", "Device calibrations window"),
+    ("Окно порогов поля", "Field thresholds window"),
+    (
+        "Остальные символы вводятся в поле",
+        "Other characters are typed into the field",
+    ),
+    (
+        "Остальные символы добавляются в строку поиска",
+        "Other characters are added to the search query",
+    ),
+    ("Выход из приложения", "Quit the application"),
+    ("Переход к предыдущей вкладке", "Go to the previous tab"),
+    ("Переход к следующей вкладке", "Go to the next tab"),
+    (
+        "Открытие новой вкладки \"График\"",
+        "Open a new \"Graph\" tab",
+    ),
+    ("Закрытие вкладки", "Close the tab"),
+    ("Выход из режима редактирования", "Leave edit mode"),
+    ("Сохранение поля", "Save the field"),
+    (
+        "Выход из меню без сохранения выбора",
+        "Leave the menu without saving the choice",
+    ),
+    ("Закрытие окна", "Close the window"),
+    ("Переход к пункту дерева выше", "Go to the tree item above"),
+    ("Переход к пункту дерева ниже", "Go to the tree item below"),
+    (
+        "Скрытие пункта дерева или переход к родителю",
+        "Collapse the tree item or go to its parent",
+    ),
+    ("Раскрытие пункта дерева", "Expand the tree item"),
+    ("Переход к файлу выше", "Go to the file above"),
+    ("Переход к файлу ниже", "Go to the file below"),
+    (
+        "Переход в родительскую директорию",
+        "Go to the parent directory",
+    ),
+    (
+        "Перемещение курсора на точку влево",
+        "Move the cursor one point left",
+    ),
+    (
+        "Перемещение курсора на точку вправо",
+        "Move the cursor one point right",
+    ),
+    (
+        "Перемещение курсора на первую точку",
+        "Move the cursor to the first point",
+    ),
+    (
+        "Перемещение курсора на последнюю точку",
+        "Move the cursor to the last point",
+    ),
+    ("Переход к полю выше", "Go to the field above"),
+    ("Переход к полю ниже", "Go to the field below"),
+    ("Переход к предыдущему полю", "Go to the previous field"),
+    ("Переход к следующему полю", "Go to the next field"),
+    ("Переход к пункту меню выше", "Go to the menu item above"),
+    ("Переход к пункту меню ниже", "Go to the menu item below"),
+    ("Переход к строке выше", "Go to the row above"),
+    ("Переход к строке ниже", "Go to the row below"),
+    ("Переход к первой строке", "Go to the first row"),
+    ("Переход к последней строке", "Go to the last row"),
+    ("Переход к виду выше", "Go to the view above"),
+    ("Переход к виду ниже", "Go to the view below"),
+    ("Навигация вверх", "Navigate up"),
+    ("Навигация вниз", "Navigate down"),
+    ("Перемещение курсора влево", "Move the cursor left"),
+    ("Перемещение курсора вправо", "Move the cursor right"),
+    (
+        "Перемещение курсора на 10 точек влево",
+        "Move the cursor 10 points left",
+    ),
+    (
+        "Перемещение курсора на 10 точек вправо",
+        "Move the cursor 10 points right",
+    ),
+    (
+        "Перемещение курсора в начало поля",
+        "Move the cursor to the start of the field",
+    ),
+    (
+        "Перемещение курсора в конец поля",
+        "Move the cursor to the end of the field",
+    ),
+    (
+        "Переход к предыдущей метке в точке курсора",
+        "Go to the previous annotation at the cursor",
+    ),
+    (
+        "Переход к следующей метке в точке курсора",
+        "Go to the next annotation at the cursor",
+    ),
+    ("Переход на 10 строк вверх", "Go 10 rows up"),
+    ("Переход на 10 строк вниз", "Go 10 rows down"),
+    (
+        "Раскрытие/скрытие пункта дерева",
+        "Expand/collapse the tree item",
+    ),
+    ("Открытие редактирования поля", "Start editing the field"),
+    ("Открытие выбранного вида", "Open the selected view"),
+    (
+        "Открытие файла или переход в директорию",
+        "Open the file or enter the directory",
+    ),
+    (
+        "Выход из меню с сохранением выбора",
+        "Leave the menu saving the choice",
+    ),
+    (
+        "Удаление последнего символа строки поиска",
+        "Delete the last character of the search query",
+    ),
+    (
+        "Удаление символа перед курсором",
+        "Delete the character before the cursor",
+    ),
+    (
+        "Сохранение вида под введённым названием",
+        "Save the view under the entered name",
+    ),
+    ("Сохранение метки", "Save the annotation"),
+    (
+        "Сохранение правила и пометка выбросов",
+        "Save the rule and flag outliers",
+    ),
+    ("Сохранение поправки прибора", "Save the device calibration"),
+    (
+        "Сохранение порогов и поиск превышений",
+        "Save thresholds and find violations",
+    ),
+    ("Удаление метки", "Delete the annotation"),
+    ("Удаление выбранного вида", "Delete the selected view"),
+    (
+        "Подбор поправки по эталонному прибору",
+        "Fit the calibration to the reference device",
+    ),
+    (
+        "Открытие окна импорта файла/директории",
+        "Open the file/directory import window",
+    ),
+    (
+        "Импорт всех файлов в данной директории",
+        "Import all files in this directory",
+    ),
+    (
+        "Открытие отчёта о пропусках данных",
+        "Open the data gaps report",
+    ),
+    (
+        "Открытие правила поиска выбросов выбранного поля",
+        "Open the outlier rule of the selected field",
+    ),
+    (
+        "Открытие поправок приборов выбранного поля",
+        "Open device calibrations of the selected field",
+    ),
+    (
+        "Открытие порогов выбранного поля",
+        "Open thresholds of the selected field",
+    ),
+    (
+        "Открытие окна превышений порогов",
+        "Open the threshold violations window",
+    ),
+    ("Вход в режим редактирования полей", "Enter field edit mode"),
+    ("Добавление графика Y(x)", "Add a Y(x) graph"),
+    (
+        "Удаление последнего графика Y(x)",
+        "Remove the last Y(x) graph",
+    ),
+    ("Переключение точечного режима", "Toggle scatter mode"),
+    (
+        "Переключение панели статистики",
+        "Toggle the statistics panel",
+    ),
+    (
+        "Переключение разрывов линий на пропусках данных",
+        "Toggle line breaks at data gaps",
+    ),
+    (
+        "Переключение исключения выбросов",
+        "Toggle outlier exclusion",
+    ),
+    (
+        "Переключение поправок приборов",
+        "Toggle device calibrations",
+    ),
+    ("Открытие окна сохранения вида", "Open the save view window"),
+    (
+        "Открытие окна сохранённых видов",
+        "Open the saved views window",
+    ),
+    (
+        "Открытие окна метки события в точке курсора",
+        "Open the event annotation window at the cursor",
+    ),
+    (
+        "Удаление курсора с графика",
+        "Remove the cursor from the graph",
+    ),
+    ("Открытие этой справки", "Open this help"),
+];
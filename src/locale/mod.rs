@@ -0,0 +1,118 @@
+use std::{collections::HashMap, fmt::Display, sync::OnceLock};
+
+use serde::Deserialize;
+
+pub mod english;
+
+/// Язык интерфейса
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum Language {
+    /// Русский язык (на нём написаны исходные строки)
+    #[default]
+    #[serde(rename = "ru")]
+    Russian,
+
+    /// Английский язык
+    #[serde(rename = "en")]
+    English,
+}
+
+/// Язык интерфейса, выбранный при запуске
+static LANGUAGE: OnceLock<Language> = OnceLock::new();
+
+/// Каталог переводов строк с русского на выбранный язык
+static CATALOG: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
+
+impl Language {
+    /// Определяет язык по переменным окружения `LC_ALL`, `LC_MESSAGES` и `LANG`.
+    /// Английский выбирается, только если локаль явно английская (`en_US.UTF-8`, `en`...),
+    /// а в остальных случаях остаётся русский язык
+    pub fn from_env() -> Self {
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty());
+
+        match locale {
+            Some(locale) if Self::is_english_locale(&locale) => Self::English,
+            _ => Self::Russian,
+        }
+    }
+
+    /// Проверяет, что локаль английская: язык `en` с необязательными регионом, кодировкой и модификатором
+    fn is_english_locale(locale: &str) -> bool {
+        let language = locale.split(['_', '.', '@']).next().unwrap_or_default();
+        language.eq_ignore_ascii_case("en")
+    }
+
+    /// Возвращает каталог переводов языка (у русского его нет, т.к. строки уже на нём)
+    fn messages(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::Russian => &[],
+            Self::English => english::MESSAGES,
+        }
+    }
+}
+
+/// Устанавливает язык интерфейса. Должна вызываться один раз до отрисовки интерфейса
+pub fn set_language(language: Language) {
+    LANGUAGE.set(language).unwrap();
+}
+
+/// Возвращает язык интерфейса
+pub fn language() -> Language {
+    LANGUAGE.get().copied().unwrap_or_default()
+}
+
+/// Переводит строку на язык интерфейса. Ключом служит сама русская строка,
+/// поэтому строки без перевода остаются как есть
+pub fn tr(text: &str) -> &str {
+    let catalog = CATALOG.get_or_init(|| language().messages().iter().copied().collect());
+    catalog.get(text).copied().unwrap_or(text)
+}
+
+/// Переводит строку с подстановками `{}` и подставляет в них аргументы по порядку
+pub fn trf(text: &str, args: &[&dyn Display]) -> String {
+    let mut parts = tr(text).split("{}");
+    let mut args = args.iter();
+    let mut result = parts.next().unwrap_or_default().to_owned();
+    for part in parts {
+        if let Some(arg) = args.next() {
+            result += &arg.to_string();
+        }
+        result += part;
+    }
+    result
+}
+
+/// Переводит элемент меню полей датчиков. У элементов вида "датчик/поле" переводится
+/// только поле (например, название производной величины), т.к. датчики названы данными
+pub fn tr_item(item: &str) -> String {
+    match item.split_once('/') {
+        Some((sensor, field)) if tr(item) == item => format!("{sensor}/{}", tr(field)),
+        _ => tr(item).to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_english_locales() {
+        for locale in ["en", "en_US.UTF-8", "en_GB", "en.UTF-8", "EN_us"] {
+            assert!(Language::is_english_locale(locale), "{locale}");
+        }
+        for locale in [
+            "ru_RU.UTF-8",
+            "de_DE.UTF-8",
+            "C",
+            "C.UTF-8",
+            "POSIX",
+            "eng",
+            "",
+        ] {
+            assert!(!Language::is_english_locale(locale), "{locale}");
+        }
+    }
+}
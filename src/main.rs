@@ -1,22 +1,70 @@
-use app::{run_application, state::App};
+use app::{config::Config, keymap::Keymap, run_application, state::App};
 use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use locale::Language;
 
 use tui::{backend::CrosstermBackend, Terminal};
 
+pub mod alerts;
+pub mod annotations;
 pub mod app;
+pub mod calibration;
 pub mod database;
 pub mod filepicker;
+pub mod gaps;
 pub mod graph;
+pub mod help;
+pub mod locale;
+pub mod outliers;
 pub mod sensors;
 pub mod ui;
+pub mod views;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Запускаем SQLite базу данных
     let database = rusqlite::Connection::open("db.sqlite")?;
+    // Журнал WAL позволяет писать в базу, пока графики читают её через отдельное соединение
+    database.pragma_update(None, "journal_mode", "WAL")?;
+    // Добавляем в неё функции, которых нет в SQLite
+    database::functions::register_functions(&database)?;
+    // Создаём таблицу для сохранённых видов графиков
+    database::views::create_views_table(&database)?;
+    // Создаём таблицу для сессии (открытых при выходе вкладок)
+    database::session::create_session_table(&database)?;
+    // Создаём таблицы правил и пометок выбросов
+    database::outliers::create_outliers_tables(&database)?;
+    // Создаём таблицу поправок приборов
+    database::calibration::create_calibrations_table(&database)?;
+    // Создаём таблицы порогов и их превышений
+    database::alerts::create_alerts_tables(&database)?;
+    // Создаём таблицу меток событий
+    database::annotations::create_annotations_table(&database)?;
+
+    // Загружаем настройки до всего остального, т.к. от них зависит язык вывода команд
+    let config = Config::load()?;
+    locale::set_language(config.language.unwrap_or_else(Language::from_env));
+
+    // Команды, которые выполняются без интерфейса
+    match std::env::args().nth(1).as_deref() {
+        None => (),
+        Some("gaps") => return gaps::print_gaps_report(&database),
+        Some("annotations") => return annotations::print_annotations_report(&database),
+        Some("check") => {
+            // Код выхода 2 означает, что есть новые или продолжающиеся превышения порогов,
+            // а код 1 остаётся для ошибок
+            if alerts::print_alerts_report(&database)? > 0 {
+                std::process::exit(2);
+            }
+            return Ok(());
+        }
+        Some(command) => return Err(format!("Unknown command: {command}").into()),
+    }
+
+    // Загружаем назначения клавиш до запуска интерфейса, чтобы ошибки были видны
+    let keymap = Keymap::new(&config.keys)?;
 
     // Получаем stdout для манипуляций с интерфесом
     let mut stdout = std::io::stdout();
@@ -29,8 +77,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     terminal.hide_cursor()?;
 
     // Запускаем приложение с интерфейсом
-    let mut app = App::new(database)?;
+    let mut app = App::new(database, keymap)?;
     let result = run_application(&mut terminal, &mut app);
+    // Сохраняем сессию, чтобы при следующем запуске восстановить вкладки
+    let session_result = app.save_session();
 
     // Восстанавливаем терминал до рабочего состояния
     disable_raw_mode()?;
@@ -41,6 +91,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     )?;
     terminal.show_cursor()?;
 
+    // Ошибка сохранения сессии не критична, но о ней стоит сообщить
+    if let Err(err) = session_result {
+        eprintln!("Failed to save the session: {err:?}");
+    }
+
     // Если была глобальная ошибка, мы теперь можем её вывести
     if let Err(err) = result {
         eprintln!("An error occurred: {err:?}");
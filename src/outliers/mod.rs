@@ -0,0 +1,100 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use itertools::Itertools;
+
+use crate::{
+    app::{
+        keymap::{Action, KeyContext},
+        state::App,
+    },
+    database::outliers,
+    locale::{tr, trf},
+};
+
+use self::{rule::OutlierReason, state::OutliersState};
+
+pub mod rule;
+pub mod state;
+pub mod ui;
+
+impl App<'_> {
+    /// Открывает окно правила поиска выбросов для выбранного в дереве поля датчика
+    pub fn open_outliers(&mut self) {
+        // Правило задаётся только для полей данных
+        let Some((sensor, field)) = self.sensors_state().get_selected_field() else {
+            return;
+        };
+        if ["id", "serial", "date"].contains(&field.as_str()) {
+            return;
+        }
+
+        // Загружаем сохранённое правило, если оно есть
+        let result = {
+            let database = self.database.lock().unwrap();
+            outliers::load_rule(&database, &sensor, &field)
+        };
+        let state = match result {
+            Ok(rule) => OutliersState::new(sensor, field, rule.unwrap_or_default()),
+            Err(err) => {
+                let mut state = OutliersState::new(sensor, field, Default::default());
+                state.error = Some(trf("Не удалось загрузить правило: {}", &[&err]));
+                state
+            }
+        };
+        self.sensors_state_mut().outliers_state = Some(state);
+    }
+
+    /// Обрабатывает все события, связанные с нажатием клавиш в окне правила поиска выбросов
+    pub fn on_key_event_outliers(&mut self, event: KeyEvent) {
+        // Действие, назначенное на клавишу в окне правила (символы без действия вводятся в поле)
+        let action = self.keymap.action(KeyContext::Outliers, event);
+
+        // Получаем состояние окна правила
+        let sensors_state = self.sensors_state_mut();
+        let state = sensors_state.outliers_state.as_mut().unwrap();
+
+        match (action, event.code) {
+            (Some(Action::Close), _) => sensors_state.outliers_state = None,
+            (Some(Action::Save), _) => self.save_outlier_rule(),
+            (Some(Action::Up), _) => state.prev(),
+            (Some(Action::Down), _) => state.next(),
+            (Some(Action::Left), _) => state.selected_input_mut().goto_prev(),
+            (Some(Action::Right), _) => state.selected_input_mut().goto_next(),
+            (Some(Action::Home), _) => state.selected_input_mut().goto_start(),
+            (Some(Action::End), _) => state.selected_input_mut().goto_end(),
+            (None, KeyCode::Char(ch)) => state.selected_input_mut().insert(ch),
+            (Some(Action::Erase), _) => state.selected_input_mut().remove(),
+            (Some(Action::Help), _) => self.open_help(KeyContext::Outliers),
+            _ => (),
+        }
+    }
+
+    /// Сохраняет правило из окна и заново помечает выбросы поля
+    fn save_outlier_rule(&mut self) {
+        // Получаем правило из полей ввода
+        let database = self.database.clone();
+        let state = self.sensors_state_mut().outliers_state.as_mut().unwrap();
+        state.status = None;
+        let Some(rule) = state.get_rule() else {
+            state.error = Some(tr("В параметрах правила есть ошибки").to_owned());
+            return;
+        };
+
+        // Сохраняем правило и помечаем выбросы
+        let result = {
+            let database = database.lock().unwrap();
+            outliers::save_rule(&database, &state.sensor, &state.field, &rule)
+        };
+        match result {
+            Ok(counts) => {
+                let counts = OutlierReason::ALL
+                    .iter()
+                    .zip(counts)
+                    .map(|(reason, count)| format!("{} - {count}", tr(reason.name())))
+                    .join(", ");
+                state.status = Some(trf("Помечено записей: {}", &[&counts]));
+                state.error = None;
+            }
+            Err(err) => state.error = Some(trf("Не удалось сохранить правило: {}", &[&err])),
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use serde::{Deserialize, Serialize};
+
+/// Порог модифицированной Z-оценки по умолчанию, если введено только окно
+pub const DEFAULT_MAD_THRESHOLD: f64 = 3.5;
+
+/// Правило поиска выбросов одного поля датчика. Каждая проверка включена, только если её параметры заданы
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct OutlierRule {
+    /// Минимальное допустимое значение
+    pub min: Option<f64>,
+
+    /// Максимальное допустимое значение
+    pub max: Option<f64>,
+
+    /// Длина скользящего окна (в записях) для модифицированной Z-оценки
+    pub window: Option<usize>,
+
+    /// Порог модифицированной Z-оценки (по медиане и MAD окна)
+    pub threshold: Option<f64>,
+
+    /// Максимальная скорость изменения значения (в единицах поля за минуту)
+    pub rate: Option<f64>,
+}
+
+/// Перечисляемый тип, определяющий то, какой проверкой запись признана выбросом
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutlierReason {
+    /// Значение вне допустимого диапазона
+    Range,
+    /// Значение слишком далеко от медианы окна
+    Mad,
+    /// Резкий скачок значения туда и обратно
+    Rate,
+}
+
+impl OutlierReason {
+    /// Все возможные причины в порядке проверки
+    pub const ALL: [Self; 3] = [Self::Range, Self::Mad, Self::Rate];
+
+    /// Возвращает код причины для хранения в БД
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Range => "range",
+            Self::Mad => "mad",
+            Self::Rate => "rate",
+        }
+    }
+
+    /// Возвращает название причины для отображения
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Range => "Диапазон",
+            Self::Mad => "Z-оценка",
+            Self::Rate => "Скорость",
+        }
+    }
+}
+
+impl OutlierRule {
+    /// Возвращает, не задана ли ни одна проверка
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Находит выбросы в записях (дата, значение) одного прибора, отсортированных по дате.
+    /// Возвращает индексы записей и причины
+    pub fn find(&self, points: &[(i64, f64)]) -> Vec<(usize, OutlierReason)> {
+        (0..points.len())
+            .filter_map(|i| {
+                OutlierReason::ALL
+                    .into_iter()
+                    .find(|&reason| self.check(points, i, reason))
+                    .map(|reason| (i, reason))
+            })
+            .collect()
+    }
+
+    /// Проверяет, является ли запись с индексом `i` выбросом по данной причине
+    fn check(&self, points: &[(i64, f64)], i: usize, reason: OutlierReason) -> bool {
+        let value = points[i].1;
+        match reason {
+            OutlierReason::Range => {
+                self.min.is_some_and(|min| value < min) || self.max.is_some_and(|max| value > max)
+            }
+            OutlierReason::Mad => {
+                let Some(window) = self.window.filter(|&window| window > 2) else {
+                    return false;
+                };
+                let threshold = self.threshold.unwrap_or(DEFAULT_MAD_THRESHOLD);
+
+                // Окно берём с центром в записи (у краёв оно сдвигается внутрь)
+                let start = i
+                    .saturating_sub(window / 2)
+                    .min(points.len().saturating_sub(window));
+                let end = (start + window).min(points.len());
+                let values: Vec<_> = points[start..end].iter().map(|&(_, y)| y).collect();
+
+                // Модифицированная Z-оценка: отклонение от медианы в единицах MAD
+                let center = median(values.clone());
+                let deviations: Vec<_> = values.iter().map(|y| (y - center).abs()).collect();
+                let mad = median(deviations.clone());
+                let score = match mad > 0. {
+                    true => 0.6745 * (value - center) / mad,
+                    // Если больше половины значений одинаковые, то используем среднее отклонение
+                    false => {
+                        let mean = deviations.iter().sum::<f64>() / deviations.len() as f64;
+                        match mean > 0. {
+                            true => (value - center) / (1.2533 * mean),
+                            false => 0.,
+                        }
+                    }
+                };
+                score.abs() > threshold
+            }
+            OutlierReason::Rate => {
+                let Some(rate) = self.rate else {
+                    return false;
+                };
+
+                // Скорость изменения к соседней записи (в единицах за минуту)
+                let speed = |j: usize| {
+                    let (date, y) = points[j];
+                    let minutes = (date - points[i].0).abs().max(1) as f64 / 60.;
+                    (y - value).abs() / minutes
+                };
+
+                // Скачком считаем запись, которая резко отличается от всех соседних
+                let neighbours: Vec<_> = [i.checked_sub(1), Some(i + 1)]
+                    .into_iter()
+                    .flatten()
+                    .filter(|&j| j < points.len())
+                    .collect();
+                !neighbours.is_empty() && neighbours.into_iter().all(|j| speed(j) > rate)
+            }
+        }
+    }
+}
+
+/// Возвращает медиану значений
+fn median(mut values: Vec<f64>) -> f64 {
+    values.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+    let middle = values.len() / 2;
+    match values.len() % 2 {
+        0 => (values[middle - 1] + values[middle]) / 2.,
+        _ => values[middle],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Записи с данными значениями через минуту
+    fn points(values: &[f64]) -> Vec<(i64, f64)> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, &y)| (i as i64 * 60, y))
+            .collect()
+    }
+
+    /// Правило с проверкой по медиане окна
+    fn mad_rule(window: usize) -> OutlierRule {
+        OutlierRule {
+            window: Some(window),
+            ..Default::default()
+        }
+    }
+
+    /// Правило с проверкой скорости изменения
+    fn rate_rule(rate: f64) -> OutlierRule {
+        OutlierRule {
+            rate: Some(rate),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn finds_values_out_of_range() {
+        let rule = OutlierRule {
+            min: Some(0.),
+            max: Some(10.),
+            ..Default::default()
+        };
+        let found = rule.find(&points(&[5., -1., 11., 10., 0.]));
+        assert_eq!(
+            found,
+            [(1, OutlierReason::Range), (2, OutlierReason::Range)]
+        );
+    }
+
+    #[test]
+    fn range_is_checked_before_other_reasons() {
+        let rule = OutlierRule {
+            max: Some(50.),
+            ..mad_rule(5)
+        };
+        let found = rule.find(&points(&[1., 2., 1., 2., 100., 2., 1., 2., 1.]));
+        assert_eq!(found, [(4, OutlierReason::Range)]);
+    }
+
+    #[test]
+    fn finds_values_far_from_window_median() {
+        let found = mad_rule(5).find(&points(&[1., 2., 1., 2., 100., 2., 1., 2., 1.]));
+        assert_eq!(found, [(4, OutlierReason::Mad)]);
+
+        // Высокий порог отключает пометку
+        let rule = OutlierRule {
+            threshold: Some(100.),
+            ..mad_rule(5)
+        };
+        assert!(rule
+            .find(&points(&[1., 2., 1., 2., 100., 2., 1., 2., 1.]))
+            .is_empty());
+    }
+
+    #[test]
+    fn shifts_window_inward_at_edges() {
+        let found = mad_rule(5).find(&points(&[100., 1., 2., 1., 2., 1., 2.]));
+        assert_eq!(found, [(0, OutlierReason::Mad)]);
+
+        let found = mad_rule(5).find(&points(&[1., 2., 1., 2., 1., 2., 100.]));
+        assert_eq!(found, [(6, OutlierReason::Mad)]);
+    }
+
+    #[test]
+    fn uses_all_points_if_window_is_longer() {
+        let found = mad_rule(5).find(&points(&[1., 2., 1., 100.]));
+        assert_eq!(found, [(3, OutlierReason::Mad)]);
+
+        // Одна запись не может быть выбросом относительно самой себя
+        assert!(mad_rule(5).find(&points(&[100.])).is_empty());
+        assert!(mad_rule(5).find(&[]).is_empty());
+    }
+
+    #[test]
+    fn ignores_windows_too_short_for_median() {
+        assert!(mad_rule(2).find(&points(&[1., 100., 1.])).is_empty());
+    }
+
+    #[test]
+    fn falls_back_to_mean_deviation_if_mad_is_zero() {
+        // Больше половины значений окна одинаковые, поэтому MAD равно нулю
+        let found = mad_rule(5).find(&points(&[5., 5., 5., 5., 5., 5., 50.]));
+        assert_eq!(found, [(6, OutlierReason::Mad)]);
+
+        // Если все значения одинаковые, то выбросов нет
+        assert!(mad_rule(5).find(&points(&[5.; 6])).is_empty());
+    }
+
+    #[test]
+    fn finds_spikes_faster_than_rate() {
+        let found = rate_rule(1.).find(&points(&[0., 0., 10., 0., 0.]));
+        assert_eq!(found, [(2, OutlierReason::Rate)]);
+
+        // Ступенька — не скачок: у записей есть медленно меняющийся сосед
+        assert!(rate_rule(1.).find(&points(&[0., 0., 10., 10.])).is_empty());
+
+        // Та же разница за больший промежуток времени укладывается в скорость
+        let slow = [(0, 0.), (600, 0.), (1200, 5.), (1800, 0.)];
+        assert!(rate_rule(1.).find(&slow).is_empty());
+    }
+
+    #[test]
+    fn checks_single_neighbour_at_edges() {
+        let found = rate_rule(1.).find(&points(&[10., 0., 0.]));
+        assert_eq!(found, [(0, OutlierReason::Rate)]);
+
+        let found = rate_rule(1.).find(&points(&[0., 0., 10.]));
+        assert_eq!(found, [(2, OutlierReason::Rate)]);
+
+        // У единственной записи нет соседей
+        assert!(rate_rule(1.).find(&points(&[10.])).is_empty());
+    }
+}
@@ -0,0 +1,129 @@
+use crate::{locale::tr, ui::input::InputState};
+
+use super::rule::OutlierRule;
+
+/// Названия полей правила в порядке отображения в окне
+pub const RULE_FIELDS: [&str; 5] = [
+    "Мин. значение",
+    "Макс. значение",
+    "Окно Z-оценки, записей",
+    "Порог Z-оценки",
+    "Макс. скорость, ед./мин",
+];
+
+/// Описывает состояние окна правила поиска выбросов одного поля датчика
+#[derive(Debug)]
+pub struct OutliersState {
+    /// Название датчика
+    pub sensor: String,
+
+    /// Название поля датчика
+    pub field: String,
+
+    /// Сохраняет поля ввода параметров правила (в порядке `RULE_FIELDS`)
+    pub inputs: [InputState; 5],
+
+    /// Сохраняет индекс выбранного поля ввода
+    pub selection_index: usize,
+
+    /// Сохраняет результат последнего поиска выбросов
+    pub status: Option<String>,
+
+    /// Сохраняет ошибку последнего действия, если она была
+    pub error: Option<String>,
+}
+
+impl OutliersState {
+    /// Создаёт новое состояние окна, заполняя поля сохранённым правилом
+    pub fn new(sensor: String, field: String, rule: OutlierRule) -> Self {
+        let values = [
+            rule.min.map(|min| min.to_string()),
+            rule.max.map(|max| max.to_string()),
+            rule.window.map(|window| window.to_string()),
+            rule.threshold.map(|threshold| threshold.to_string()),
+            rule.rate.map(|rate| rate.to_string()),
+        ];
+        let mut inputs: [InputState; 5] = Default::default();
+        for (input, value) in inputs.iter_mut().zip(values) {
+            input.set_content(value.unwrap_or_default());
+        }
+        inputs[0].open();
+
+        Self {
+            sensor,
+            field,
+            inputs,
+            selection_index: 0,
+            status: None,
+            error: None,
+        }
+    }
+
+    /// Возвращает изменяемую ссылку на выбранное поле ввода
+    pub fn selected_input_mut(&mut self) -> &mut InputState {
+        &mut self.inputs[self.selection_index]
+    }
+
+    /// Выбирает предыдущее поле ввода
+    pub fn prev(&mut self) {
+        self.select((self.selection_index + self.inputs.len() - 1) % self.inputs.len());
+    }
+
+    /// Выбирает следующее поле ввода
+    pub fn next(&mut self) {
+        self.select((self.selection_index + 1) % self.inputs.len());
+    }
+
+    /// Собирает правило из полей ввода. Если в каком-то поле ошибка, выделяет его и возвращает `None`
+    pub fn get_rule(&mut self) -> Option<OutlierRule> {
+        // Пустое поле означает, что параметр не задан
+        fn parse<T: std::str::FromStr>(input: &mut InputState) -> Result<Option<T>, ()> {
+            let result = match input.content().trim() {
+                "" => Ok(None),
+                content => content.parse().map(Some).map_err(|_| ()),
+            };
+            let error = result.is_err().then(|| tr("Ожидалось число").to_owned());
+            input.set_error(error);
+            result
+        }
+
+        let [min, max, window, threshold, rate] = &mut self.inputs;
+        let values = (
+            parse(min),
+            parse(max),
+            parse(window),
+            parse(threshold),
+            parse(rate),
+        );
+        let (Ok(min), Ok(max), Ok(window), Ok(threshold), Ok(rate)) = values else {
+            return None;
+        };
+
+        // Z-оценка по окну из двух записей всегда одинакова, а порог без окна не к чему применить
+        match (window, threshold) {
+            (Some(0..=2), _) => {
+                self.inputs[2].set_error(Some(tr("Окно должно быть больше 2 записей").to_owned()));
+                None
+            }
+            (None, Some(_)) => {
+                self.inputs[3]
+                    .set_error(Some(tr("Порог задаётся только вместе с окном").to_owned()));
+                None
+            }
+            _ => Some(OutlierRule {
+                min,
+                max,
+                window,
+                threshold,
+                rate,
+            }),
+        }
+    }
+
+    /// Переводит ввод на поле с данным индексом
+    fn select(&mut self, index: usize) {
+        self.selected_input_mut().close();
+        self.selection_index = index;
+        self.selected_input_mut().open();
+    }
+}
@@ -0,0 +1,95 @@
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::Text,
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::{
+    locale::{tr, trf},
+    ui::{input::Input, utils::get_popup_area},
+};
+
+use super::{
+    rule::DEFAULT_MAD_THRESHOLD,
+    state::{OutliersState, RULE_FIELDS},
+};
+
+/// Рендерит окно правила поиска выбросов
+pub fn draw_outliers<B: Backend>(frame: &mut Frame<B>, state: &mut OutliersState, area: Rect) {
+    // Выделяем область под окошко и очищаем её, чтобы не видеть дерево под ним
+    let popup_area = get_popup_area(60, 60, area);
+    frame.render_widget(Clear, popup_area);
+
+    // Делаем блок
+    let title = trf("Выбросы: {}/{}", &[&state.sensor, &state.field]);
+    let block = Block::default().title(title).borders(Borders::ALL);
+    let inner_area = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    // Сверху поля правила, снизу подсказка, результат и ошибка
+    let area_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(RULE_FIELDS.len() as u16),
+            Constraint::Min(0),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .split(inner_area);
+
+    // Рендерим поля правила: подпись слева, поле ввода справа
+    let label_width = RULE_FIELDS
+        .iter()
+        .map(|label| tr(label).chars().count())
+        .max()
+        .unwrap_or(0) as u16
+        + 2;
+    let fields_area = area_chunks[0];
+    for (i, (label, input_state)) in RULE_FIELDS.iter().zip(&mut state.inputs).enumerate() {
+        let y = fields_area.y + i as u16;
+        let style = match i == state.selection_index {
+            true => Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD),
+            false => Style::default(),
+        };
+        let paragraph = Paragraph::new(Text::from(tr(label))).style(style);
+        frame.render_widget(paragraph, Rect::new(fields_area.x, y, label_width, 1));
+
+        let input = Input::new().style(Style::default().add_modifier(Modifier::UNDERLINED));
+        let input_width = fields_area.width.saturating_sub(label_width).max(1);
+        let input_area = Rect::new(fields_area.x + label_width, y, input_width, 1);
+        frame.render_stateful_widget(input, input_area, input_state);
+    }
+
+    // Подсказка о том, как работают проверки
+    let text = trf(
+        "Пустое поле выключает проверку. Z-оценка считается по медиане и MAD окна \
+         (порог по умолчанию - {}), а скорость - к соседним записям прибора. \
+         Enter - сохранить правило и пометить выбросы, Esc - закрыть окно.",
+        &[&DEFAULT_MAD_THRESHOLD],
+    );
+    let paragraph = Paragraph::new(Text::from(text))
+        .style(Style::default().fg(Color::DarkGray))
+        .wrap(Wrap { trim: true });
+    let hint_area = Rect {
+        y: area_chunks[1].y + 1,
+        height: area_chunks[1].height.saturating_sub(1),
+        ..area_chunks[1]
+    };
+    frame.render_widget(paragraph, hint_area);
+
+    // Рендерим результат последнего поиска и ошибку
+    if let Some(status) = &state.status {
+        let paragraph = Paragraph::new(Text::from(status.as_str()));
+        frame.render_widget(paragraph, area_chunks[2]);
+    }
+    if let Some(error) = &state.error {
+        let paragraph =
+            Paragraph::new(Text::from(error.as_str())).style(Style::default().fg(Color::Red));
+        frame.render_widget(paragraph, area_chunks[3]);
+    }
+}
@@ -1,6 +1,9 @@
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{KeyEvent, MouseButton, MouseEvent, MouseEventKind};
 
-use crate::app::state::App;
+use crate::app::{
+    keymap::{Action, KeyContext},
+    state::App,
+};
 
 pub mod state;
 pub mod ui;
@@ -19,28 +22,53 @@ impl App<'_> {
 
     /// Обрабатывает все события, связанные с нажатием клавиш во вкладке дерева сенсоров
     pub fn on_key_event_sensors(&mut self, event: KeyEvent) -> std::io::Result<()> {
+        // Действие, назначенное на клавишу в обычном режиме вкладки
+        let action = self.keymap.action(KeyContext::Sensors, event);
+
         // Получаем состояние дерева сенсоров
         let state = self.sensors_state_mut();
 
         // Проверяем открыто ли окно выбора файла
         if state.file_picker_state.is_some() {
             self.on_key_event_file_picker(event)?;
+        } else if state.gaps_state.is_some() {
+            self.on_key_event_gaps(event);
+        } else if state.outliers_state.is_some() {
+            self.on_key_event_outliers(event);
+        } else if state.calibration_state.is_some() {
+            self.on_key_event_calibration(event);
+        } else if state.threshold_state.is_some() {
+            self.on_key_event_threshold(event);
+        } else if state.alerts_state.is_some() {
+            self.on_key_event_alerts(event);
         } else {
-            match event.code {
+            match action {
                 // Выход из приложения
-                KeyCode::Char('q') => self.running = false,
+                Some(Action::Quit) => self.running = false,
                 // Управление вкладками
-                KeyCode::BackTab => self.tabs.prev(),
-                KeyCode::Tab => self.tabs.next(),
-                KeyCode::Char('N') => self.open_new_tab(),
+                Some(Action::PrevTab) => self.tabs.prev(),
+                Some(Action::NextTab) => self.tabs.next(),
+                Some(Action::NewTab) => self.open_new_tab(),
                 // Управление деревом
-                KeyCode::Up => state.tree_state.key_up(&state.items),
-                KeyCode::Down => state.tree_state.key_down(&state.items),
-                KeyCode::Left => state.tree_state.key_left(),
-                KeyCode::Right => state.tree_state.key_right(),
-                KeyCode::Char(' ') | KeyCode::Enter => state.tree_state.toggle_selected(),
+                Some(Action::Up) => state.tree_state.key_up(&state.items),
+                Some(Action::Down) => state.tree_state.key_down(&state.items),
+                Some(Action::Left) => state.tree_state.key_left(),
+                Some(Action::Right) => state.tree_state.key_right(),
+                Some(Action::Toggle) => state.tree_state.toggle_selected(),
                 // Импорт данных
-                KeyCode::Char('i') => self.open_file_picker()?,
+                Some(Action::Import) => self.open_file_picker()?,
+                // Отчёт о пропусках данных
+                Some(Action::Gaps) => self.open_gaps_report(),
+                // Правило поиска выбросов выбранного поля
+                Some(Action::Outliers) => self.open_outliers(),
+                // Поправки приборов выбранного поля
+                Some(Action::Calibration) => self.open_calibration(),
+                // Пороги выбранного поля
+                Some(Action::Thresholds) => self.open_threshold(),
+                // Превышения порогов
+                Some(Action::Alerts) => self.open_alerts(None),
+                // Справка по клавишам
+                Some(Action::Help) => self.open_help(KeyContext::Sensors),
 
                 _ => (),
             }
@@ -48,4 +76,38 @@ impl App<'_> {
 
         Ok(())
     }
+
+    /// Обрабатывает все события, связанные с мышкой во вкладке дерева сенсоров
+    pub fn on_mouse_event_sensors(&mut self, event: MouseEvent) {
+        // Получаем состояние дерева сенсоров
+        let state = self.sensors_state_mut();
+
+        // Окно выбора файла управляется мышкой отдельно, а остальные окна - только клавишами
+        if state.file_picker_state.is_some() {
+            self.on_mouse_event_file_picker(event);
+            return;
+        } else if state.gaps_state.is_some()
+            || state.outliers_state.is_some()
+            || state.calibration_state.is_some()
+            || state.threshold_state.is_some()
+            || state.alerts_state.is_some()
+        {
+            return;
+        }
+
+        match event.kind {
+            // Нажатие на узел дерева выбирает его и раскрывает (или сворачивает)
+            MouseEventKind::Down(MouseButton::Left) => {
+                if let Some(node) = state.node_at(event.column, event.row) {
+                    state.tree_state.select(node);
+                    state.tree_state.toggle_selected();
+                }
+            }
+            // Прокрутка дерева колёсиком
+            MouseEventKind::ScrollUp => state.tree_state.key_up(&state.items),
+            MouseEventKind::ScrollDown => state.tree_state.key_down(&state.items),
+
+            _ => (),
+        }
+    }
 }
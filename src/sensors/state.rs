@@ -1,12 +1,24 @@
 use std::{cell::RefCell, rc::Rc};
 
-use tui_tree_widget::{TreeItem, TreeState};
+use itertools::Itertools;
+use tui::layout::Rect;
+use tui_tree_widget::{flatten, TreeItem, TreeState};
 
 use crate::{
+    alerts::state::{AlertsState, ThresholdState},
+    calibration::state::CalibrationState,
     database::{SensorsFields, SensorsSerials},
     filepicker::state::FilePickerState,
+    gaps::state::GapsState,
+    locale::tr,
+    outliers::state::OutliersState,
+    ui::utils::area_contains,
 };
 
+/// Названия групп у каждого датчика в дереве (по ним сохраняются раскрытые узлы, поэтому
+/// переводятся только при отображении)
+const TREE_GROUPS: [&str; 2] = ["Поля", "Серийники"];
+
 /// Сохраняет состояние вкладки дерева датчиков
 #[derive(Debug)]
 pub struct SensorsState<'a> {
@@ -22,8 +34,26 @@ pub struct SensorsState<'a> {
     /// Сохраняет элементы дерева датчиков
     pub items: Vec<TreeItem<'a>>,
 
+    /// Сохраняет область, в которой было отрисовано дерево датчиков
+    pub tree_area: Rect,
+
     /// Сохраняет состояние элемента выбора файлов
     pub file_picker_state: Option<FilePickerState>,
+
+    /// Сохраняет состояние окна отчёта о пропусках данных
+    pub gaps_state: Option<GapsState>,
+
+    /// Сохраняет состояние окна правила поиска выбросов
+    pub outliers_state: Option<OutliersState>,
+
+    /// Сохраняет состояние окна поправок приборов
+    pub calibration_state: Option<CalibrationState>,
+
+    /// Сохраняет состояние окна порогов поля
+    pub threshold_state: Option<ThresholdState>,
+
+    /// Сохраняет состояние окна превышений порогов
+    pub alerts_state: Option<AlertsState>,
 }
 
 impl SensorsState<'_> {
@@ -38,8 +68,14 @@ impl SensorsState<'_> {
 
             tree_state: TreeState::default(),
             items: Vec::new(),
+            tree_area: Rect::default(),
 
             file_picker_state: None,
+            gaps_state: None,
+            outliers_state: None,
+            calibration_state: None,
+            threshold_state: None,
+            alerts_state: None,
         }
     }
 
@@ -63,11 +99,80 @@ impl SensorsState<'_> {
                 let fields: Vec<_> = fields.iter().cloned().map(TreeItem::new_leaf).collect();
                 let serials: Vec<_> = serials.iter().cloned().map(TreeItem::new_leaf).collect();
 
-                let fields_tree = TreeItem::new("Поля", fields);
-                let serials_tree = TreeItem::new("Серийники", serials);
+                let fields_tree = TreeItem::new(tr(TREE_GROUPS[0]), fields);
+                let serials_tree = TreeItem::new(tr(TREE_GROUPS[1]), serials);
                 TreeItem::new(name.clone(), vec![fields_tree, serials_tree])
             })
             .collect();
         self.items = sensors_tree;
     }
+
+    /// Возвращает раскрытые узлы дерева в виде путей по названиям (индексы меняются при импорте)
+    pub fn get_opened_nodes(&self) -> Vec<Vec<String>> {
+        let sensors = self.get_sorted_sensors();
+        self.tree_state
+            .get_all_opened()
+            .into_iter()
+            .filter_map(|path| match *path.as_slice() {
+                [i] => Some(vec![sensors.get(i)?.clone()]),
+                [i, j] => Some(vec![
+                    sensors.get(i)?.clone(),
+                    TREE_GROUPS.get(j)?.to_string(),
+                ]),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Раскрывает узлы дерева по путям из названий. Узлы, которых больше нет, пропускаются
+    pub fn open_nodes(&mut self, nodes: &[Vec<String>]) {
+        let sensors = self.get_sorted_sensors();
+        for node in nodes {
+            let path: Option<Vec<usize>> = node
+                .iter()
+                .enumerate()
+                .map(|(depth, name)| match depth {
+                    0 => sensors.iter().position(|sensor| sensor == name),
+                    _ => TREE_GROUPS.iter().position(|group| group == name),
+                })
+                .collect();
+            if let Some(path) = path {
+                self.tree_state.open(path);
+            }
+        }
+    }
+
+    /// Возвращает путь до узла дерева, который был отрисован в данной клетке
+    pub fn node_at(&self, column: u16, row: u16) -> Option<Vec<usize>> {
+        if !area_contains(self.tree_area, column, row) {
+            return None;
+        }
+
+        // Каждый видимый узел занимает одну строку, начиная с отступа дерева
+        let i = self.tree_state.get_offset() + (row - self.tree_area.y) as usize;
+        let visible = flatten(&self.tree_state.get_all_opened(), &self.items);
+        visible.get(i).map(|node| node.identifier.clone())
+    }
+
+    /// Возвращает датчик и поле, если в дереве выбрано поле датчика
+    pub fn get_selected_field(&self) -> Option<(String, String)> {
+        match *self.tree_state.selected().as_slice() {
+            [i, 0, j] => {
+                let sensor = self.get_sorted_sensors().get(i)?.clone();
+                let field = self.sensor_fields.borrow().get(&sensor)?.get(j)?.clone();
+                Some((sensor, field))
+            }
+            _ => None,
+        }
+    }
+
+    /// Возвращает названия датчиков в том порядке, в котором они идут в дереве
+    fn get_sorted_sensors(&self) -> Vec<String> {
+        self.sensor_fields
+            .borrow()
+            .keys()
+            .cloned()
+            .sorted()
+            .collect()
+    }
 }
@@ -8,7 +8,14 @@ use tui::{
 };
 use tui_tree_widget::Tree;
 
-use crate::filepicker::ui::draw_file_picker;
+use crate::{
+    alerts::ui::{draw_alerts, draw_threshold},
+    calibration::ui::draw_calibration,
+    filepicker::ui::draw_file_picker,
+    gaps::ui::draw_gaps_report,
+    locale::tr,
+    outliers::ui::draw_outliers,
+};
 
 use super::state::SensorsState;
 
@@ -22,8 +29,9 @@ pub fn draw_sensors_tab<B: Backend>(frame: &mut Frame<B>, state: &mut SensorsSta
                 .add_modifier(Modifier::BOLD),
         );
         frame.render_stateful_widget(tree, area, &mut state.tree_state);
+        state.tree_area = area;
     } else {
-        let text = "--- Данные датчиков не импортированы ---";
+        let text = tr("--- Данные датчиков не импортированы ---");
         let paragraph = Paragraph::new(vec![Spans::from(text)]);
         frame.render_widget(paragraph, area);
     }
@@ -32,4 +40,29 @@ pub fn draw_sensors_tab<B: Backend>(frame: &mut Frame<B>, state: &mut SensorsSta
     if let Some(file_picker_state) = state.file_picker_state.as_mut() {
         draw_file_picker(frame, file_picker_state, area);
     }
+
+    // Если у нас открыто окно отчёта о пропусках -> рендерим его
+    if let Some(gaps_state) = state.gaps_state.as_mut() {
+        draw_gaps_report(frame, gaps_state, area);
+    }
+
+    // Если у нас открыто окно правила поиска выбросов -> рендерим его
+    if let Some(outliers_state) = state.outliers_state.as_mut() {
+        draw_outliers(frame, outliers_state, area);
+    }
+
+    // Если у нас открыто окно поправок приборов -> рендерим его
+    if let Some(calibration_state) = state.calibration_state.as_mut() {
+        draw_calibration(frame, calibration_state, area);
+    }
+
+    // Если у нас открыто окно порогов -> рендерим его
+    if let Some(threshold_state) = state.threshold_state.as_mut() {
+        draw_threshold(frame, threshold_state, area);
+    }
+
+    // Если у нас открыто окно превышений -> рендерим его
+    if let Some(alerts_state) = state.alerts_state.as_mut() {
+        draw_alerts(frame, alerts_state, area);
+    }
 }
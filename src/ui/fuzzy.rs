@@ -0,0 +1,136 @@
+/// Очки за каждый найденный символ запроса
+const MATCH_SCORE: i64 = 1;
+
+/// Очки за символ, который идёт сразу после предыдущего найденного
+const CONSECUTIVE_SCORE: i64 = 5;
+
+/// Очки за символ в начале слова (например, после `/` или пробела)
+const WORD_START_SCORE: i64 = 3;
+
+/// Наибольший штраф за пропущенные между найденными символами
+const MAX_GAP_PENALTY: i64 = 3;
+
+/// Совпадение строки с нечётким запросом
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    /// Индекс строки в исходном списке
+    pub index: usize,
+
+    /// Очки совпадения (чем больше, тем лучше строка подходит под запрос)
+    pub score: i64,
+
+    /// Номера символов строки, совпавших с символами запроса
+    pub positions: Vec<usize>,
+}
+
+/// Проверяет, что все символы запроса встречаются в строке по порядку (без учёта регистра),
+/// и возвращает очки совпадения и номера совпавших символов
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<(i64, Vec<usize>)> {
+    let candidate: Vec<char> = candidate.chars().collect();
+    let mut positions = vec![];
+    let mut score = 0;
+    let mut start = 0;
+
+    for query_char in query.chars().filter(|ch| !ch.is_whitespace()) {
+        // Ищем символ запроса среди ещё не просмотренных символов строки
+        let offset = candidate[start..]
+            .iter()
+            .position(|&ch| chars_equal(ch, query_char))?;
+        let i = start + offset;
+
+        score += MATCH_SCORE;
+        match positions.last() {
+            Some(&last) if i == last + 1 => score += CONSECUTIVE_SCORE,
+            Some(_) => score -= (offset as i64).min(MAX_GAP_PENALTY),
+            None => (),
+        }
+        if i == 0 || !candidate[i - 1].is_alphanumeric() {
+            score += WORD_START_SCORE;
+        }
+
+        positions.push(i);
+        start = i + 1;
+    }
+
+    Some((score, positions))
+}
+
+/// Оставляет строки, подходящие под запрос, и сортирует их по убыванию очков.
+/// Пустой запрос оставляет все строки в исходном порядке
+pub fn fuzzy_filter<T: AsRef<str>>(query: &str, items: &[T]) -> Vec<FuzzyMatch> {
+    let mut matches: Vec<FuzzyMatch> = items
+        .iter()
+        .enumerate()
+        .filter_map(|(index, item)| {
+            let (score, positions) = fuzzy_match(query, item.as_ref())?;
+            Some(FuzzyMatch {
+                index,
+                score,
+                positions,
+            })
+        })
+        .collect();
+
+    // Сортировка устойчивая, поэтому строки с равными очками остаются в исходном порядке
+    matches.sort_by_key(|item| std::cmp::Reverse(item.score));
+    matches
+}
+
+/// Сравнивает символы без учёта регистра (в том числе кириллицы)
+fn chars_equal(a: char, b: char) -> bool {
+    a == b || a.to_lowercase().eq(b.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn returns_char_positions() {
+        let (_, positions) = fuzzy_match("tmp", "sensor/temp").unwrap();
+        assert_eq!(positions, [7, 9, 10]);
+
+        // Берётся первое вхождение каждого символа
+        let (_, positions) = fuzzy_match("tmp", "weather/temperature").unwrap();
+        assert_eq!(positions, [3, 10, 11]);
+
+        // Позиции считаются в символах, а не в байтах
+        let (_, positions) = fuzzy_match("ДАВ", "метео/давление").unwrap();
+        assert_eq!(positions, [6, 7, 8]);
+    }
+
+    #[test]
+    fn requires_chars_in_order() {
+        assert!(fuzzy_match("pt", "temp").is_none());
+        assert!(fuzzy_match("tempx", "temp").is_none());
+        assert_eq!(fuzzy_match("", "temp"), Some((0, vec![])));
+        assert_eq!(fuzzy_match("t p", "temp").unwrap().1, [0, 3]);
+    }
+
+    #[test]
+    fn orders_by_score() {
+        let items = [
+            "pressure/temp_max",
+            "weather/pm",
+            "weather/temperature",
+            "t/e/m/p",
+        ];
+        let order: Vec<_> = fuzzy_filter("temp", &items)
+            .into_iter()
+            .map(|item| item.index)
+            .collect();
+        // Подряд идущие символы лучше начал слов, а те лучше разбросанных внутри слова символов.
+        // Строка без совпадения отбрасывается
+        assert_eq!(order, [0, 3, 2]);
+    }
+
+    #[test]
+    fn keeps_order_for_empty_query() {
+        let items = ["b", "a", "c"];
+        let order: Vec<_> = fuzzy_filter("", &items)
+            .into_iter()
+            .map(|item| item.index)
+            .collect();
+        assert_eq!(order, [0, 1, 2]);
+    }
+}
@@ -12,6 +12,7 @@ pub struct InputState {
     content: String,
     offset: usize,
     cursor: Option<usize>,
+    error: Option<String>,
 }
 
 impl InputState {
@@ -20,6 +21,23 @@ impl InputState {
         &self.content
     }
 
+    /// Заменяет содержимое поля ввода
+    pub fn set_content(&mut self, content: String) {
+        self.content = content;
+        self.offset = 0;
+        self.cursor = self.cursor.map(|_| self.content.len());
+    }
+
+    /// Возвращает ошибку в содержимом поля ввода, если она есть
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Устанавливает (или убирает) ошибку в содержимом поля ввода
+    pub fn set_error(&mut self, error: Option<String>) {
+        self.error = error;
+    }
+
     /// Открывает поле ввода
     pub fn open(&mut self) {
         self.cursor = Some(self.content.len());
@@ -157,7 +175,12 @@ impl StatefulWidget for Input {
 
         // Рендерим введённый текст
         let content = &state.content[start..end];
-        let paragraph = Paragraph::new(Text::raw(content)).style(self.style);
+        // Если в содержимом есть ошибка, выделяем его красным
+        let style = match state.error {
+            Some(_) => self.style.fg(Color::Red),
+            None => self.style,
+        };
+        let paragraph = Paragraph::new(Text::raw(content)).style(style);
         paragraph.render(area, buf);
 
         // Рендерим курсорчик, если поле редактируется
@@ -1,11 +1,15 @@
 use tui::{
     buffer::Buffer,
-    layout::Rect,
-    style::Style,
-    text::Text,
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
+    text::{Span, Spans, Text},
     widgets::{Clear, List, ListItem, ListState, Paragraph, StatefulWidget, Widget},
 };
 
+use crate::locale::tr;
+
+use super::fuzzy::{fuzzy_filter, FuzzyMatch};
+
 /// Описывает данное состояние виджета "меню"
 #[derive(Default, Debug)]
 pub struct MenuState {
@@ -14,11 +18,20 @@ pub struct MenuState {
 
     /// Хранит состояние списка элементов, когда он открыт
     list_state: ListState,
+
+    /// Хранит строку поиска, по которой отфильтрованы элементы открытого меню
+    query: String,
+
+    /// Хранит индекс первого видимого элемента открытого меню
+    offset: usize,
 }
 
 impl MenuState {
     /// Открывает меню
     pub fn open(&mut self) {
+        // Без поиска элементы идут в исходном порядке, поэтому выделяем выбранный
+        self.query.clear();
+        self.offset = 0;
         self.list_state.select(self.selected.or(Some(0)));
     }
 
@@ -54,10 +67,30 @@ impl MenuState {
         self.list_state.select(Some((i + 1) % length));
     }
 
-    /// Выбирает элемент из меню
-    pub fn select(&mut self) {
-        self.selected = self.list_state.selected();
-        self.close();
+    /// Выбирает выделенный элемент из отфильтрованных элементов меню.
+    /// Возвращает `false`, если под поиск не подошёл ни один элемент
+    pub fn select(&mut self, items: &[String]) -> bool {
+        let i = self.list_state.selected().unwrap_or(0);
+        match self.matches(items).get(i) {
+            Some(item) => {
+                self.selected = Some(item.index);
+                self.close();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Выбирает элемент в данной строке открытого меню (считая от первой видимой строки).
+    /// Возвращает `false`, если в этой строке нет элемента
+    pub fn click(&mut self, row: usize, items: &[String]) -> bool {
+        let i = self.offset + row;
+        if row >= MENU_HEIGHT as usize || i >= self.matches(items).len() {
+            return false;
+        }
+
+        self.list_state.select(Some(i));
+        self.select(items)
     }
 
     /// Устанавливает выбор у меню
@@ -74,35 +107,76 @@ impl MenuState {
     pub fn selected(&self) -> Option<usize> {
         self.selected
     }
+
+    /// Возвращает строку поиска
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Добавляет символ в строку поиска и выделяет лучший подходящий элемент
+    pub fn insert(&mut self, ch: char) {
+        self.query.push(ch);
+        self.offset = 0;
+        self.list_state.select(Some(0));
+    }
+
+    /// Удаляет последний символ строки поиска и выделяет лучший подходящий элемент
+    pub fn remove(&mut self) {
+        self.query.pop();
+        self.offset = 0;
+        self.list_state.select(Some(0));
+    }
+
+    /// Возвращает элементы меню, подходящие под строку поиска, в порядке отображения
+    pub fn matches(&self, items: &[String]) -> Vec<FuzzyMatch> {
+        fuzzy_filter(&self.query, items)
+    }
+
+    /// Сдвигает первый видимый элемент так, чтобы выделенный элемент был виден
+    fn update_offset(&mut self, height: usize) {
+        let height = height.max(1);
+        let i = self.list_state.selected().unwrap_or(0);
+        if i < self.offset {
+            self.offset = i;
+        } else if i >= self.offset + height {
+            self.offset = i + 1 - height;
+        }
+    }
 }
 
 /// Представляет из себя виджет "меню"
 #[derive(Debug)]
-pub struct Menu<'a> {
+pub struct Menu {
     /// Сохранённый список элементов для отображения
-    items: Vec<Text<'a>>,
-
-    /// Список для выбора вариантов меню
-    list: List<'a>,
+    items: Vec<String>,
 
     /// Стиль того, как выглядит выбранный элемент
     style: Style,
+
+    /// Стиль списка элементов
+    list_style: Style,
+
+    /// Стиль выделенного элемента списка
+    list_highlight_style: Style,
+
+    /// Стиль символов, совпавших со строкой поиска
+    match_style: Style,
+
+    /// Подсказка, которая отображается, пока значение не выбрано
+    placeholder: Option<String>,
 }
 
-impl<'a> Menu<'a> {
+impl Menu {
     /// Создаёт новый виджет меню
-    pub fn new<T>(items: T) -> Self
-    where
-        T: Into<Vec<Text<'a>>>,
-    {
-        let items: Vec<Text<'a>> = items.into();
-        let list_items: Vec<ListItem<'a>> = items.iter().cloned().map(ListItem::new).collect();
-
+    pub fn new(items: Vec<String>) -> Self {
         Self {
             items,
-            list: List::new(list_items),
 
             style: Style::default(),
+            list_style: Style::default(),
+            list_highlight_style: Style::default(),
+            match_style: Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            placeholder: None,
         }
     }
 
@@ -114,33 +188,77 @@ impl<'a> Menu<'a> {
 
     /// Задаёт стиль меню
     pub fn list_style(mut self, style: Style) -> Self {
-        self.list = self.list.style(style);
+        self.list_style = style;
         self
     }
 
     /// Задаёт стиль выделенному элементу меню
     pub fn list_highlight_style(mut self, style: Style) -> Self {
-        self.list = self.list.highlight_style(style);
+        self.list_highlight_style = style;
         self
     }
+
+    /// Задаёт стиль символам, совпавшим со строкой поиска
+    pub fn match_style(mut self, style: Style) -> Self {
+        self.match_style = style;
+        self
+    }
+
+    /// Задаёт подсказку вместо невыбранного значения
+    pub fn placeholder(mut self, placeholder: Option<String>) -> Self {
+        self.placeholder = placeholder;
+        self
+    }
+
+    /// Делает элемент списка, выделяя в нём совпавшие со строкой поиска символы
+    fn list_item(&self, item: &FuzzyMatch) -> ListItem<'static> {
+        let spans: Vec<Span> = self.items[item.index]
+            .chars()
+            .enumerate()
+            .map(|(i, ch)| match item.positions.contains(&i) {
+                true => Span::styled(ch.to_string(), self.match_style),
+                false => Span::raw(ch.to_string()),
+            })
+            .collect();
+        ListItem::new(Spans::from(spans))
+    }
 }
 
 /// Определяет сколько строк может максимально занимать меню
 pub const MENU_HEIGHT: u16 = 5;
 
-impl StatefulWidget for Menu<'_> {
+impl StatefulWidget for Menu {
     type State = MenuState;
 
-    fn render(mut self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
-        // Рендерим выбранную строку
-        let text = if let Some(selected) = state.selected {
-            self.items.swap_remove(selected)
-        } else {
-            Text::raw("<значение не выбрано>")
-        };
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
         let field_area = Rect { height: 1, ..area };
-        let paragraph = Paragraph::new(text).style(self.style);
-        paragraph.render(field_area, buf);
+        let matches = state.matches(&self.items);
+
+        // Рендерим выбранную строку, а в открытом меню - строку поиска
+        let text = match (state.opened(), state.selected) {
+            (true, _) => Text::styled(
+                format!("{}_", state.query),
+                self.style.add_modifier(Modifier::UNDERLINED),
+            ),
+            (false, Some(selected)) => Text::styled(self.items[selected].clone(), self.style),
+            (false, None) => match &self.placeholder {
+                Some(placeholder) => Text::styled(format!("<{placeholder}>"), self.style),
+                None => Text::styled(tr("<значение не выбрано>"), self.style),
+            },
+        };
+        Paragraph::new(text).render(field_area, buf);
+
+        // В открытом меню справа показываем, сколько элементов подходит под поиск
+        if state.opened() {
+            let count_area = Rect {
+                width: field_area.width.saturating_sub(2),
+                ..field_area
+            };
+            let text = format!(" {}/{}", matches.len(), self.items.len());
+            Paragraph::new(text)
+                .alignment(Alignment::Right)
+                .render(count_area, buf);
+        }
 
         // Рендер символа в конце выбранного элемента, для отображения состояния меню
         let opened_area = Rect {
@@ -172,8 +290,22 @@ impl StatefulWidget for Menu<'_> {
             // Вычищаем область перед рендером списка
             Widget::render(Clear, area, buf);
 
-            // Рендерим список полей (т.е. меню)
-            StatefulWidget::render(self.list, area, buf, &mut state.list_state);
+            // Видимую часть списка считаем сами, чтобы знать, какой элемент в какой строке
+            state.update_offset(area.height as usize);
+            let mut list_state = ListState::default();
+            list_state.select(state.list_state.selected().map(|i| i - state.offset));
+
+            // Рендерим видимую часть отфильтрованного списка полей (т.е. меню)
+            let list_items: Vec<ListItem> = matches
+                .iter()
+                .skip(state.offset)
+                .take(area.height as usize)
+                .map(|item| self.list_item(item))
+                .collect();
+            let list = List::new(list_items)
+                .style(self.list_style)
+                .highlight_style(self.list_highlight_style);
+            StatefulWidget::render(list, area, buf, &mut list_state);
         }
     }
 }
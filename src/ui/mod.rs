@@ -1,6 +1,6 @@
 use tui::{
     backend::Backend,
-    layout::{Alignment, Constraint, Direction, Layout},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Style},
     text::{Span, Spans},
     widgets::{Block, Borders, Tabs},
@@ -10,9 +10,12 @@ use tui::{
 use crate::{
     app::{state::App, tabs::TabState},
     graph::ui::draw_graph_tab,
+    help::ui::draw_help,
+    locale::tr,
     sensors::ui::draw_sensors_tab,
 };
 
+pub mod fuzzy;
 pub mod input;
 pub mod menu;
 pub mod utils;
@@ -26,7 +29,7 @@ pub fn draw<B: Backend>(frame: &mut Frame<B>, app: &mut App) {
         .split(frame.size());
 
     // Превращаем названия вкладок в нужный формат
-    let tabs_titles = app
+    let tabs_titles: Vec<_> = app
         .tabs
         .titles()
         .into_iter()
@@ -35,8 +38,10 @@ pub fn draw<B: Backend>(frame: &mut Frame<B>, app: &mut App) {
     // Делаем виджет вкладок и рендерим его
     let tabs_block = Block::default()
         .borders(Borders::BOTTOM)
-        .title("Практика")
+        .title(tr("Практика"))
         .title_alignment(Alignment::Center);
+    // Запоминаем, где окажутся заголовки вкладок, чтобы переключать их мышкой
+    app.tabs.areas = get_tabs_areas(&tabs_titles, tabs_block.inner(frame_chunks[0]));
     let tabs = Tabs::new(tabs_titles)
         .block(tabs_block)
         .highlight_style(Style::default().fg(Color::Yellow))
@@ -49,4 +54,27 @@ pub fn draw<B: Backend>(frame: &mut Frame<B>, app: &mut App) {
         TabState::Graph(state) => draw_graph_tab(frame, state, main_area),
         TabState::Sensors(state) => draw_sensors_tab(frame, state, main_area),
     }
+
+    // Если открыта справка по клавишам -> рендерим её поверх всего
+    if let Some(help_state) = app.help_state.as_mut() {
+        draw_help(frame, help_state, main_area);
+    }
+}
+
+/// Возвращает области заголовков вкладок так же, как их располагает виджет вкладок:
+/// отступ в 1 клетку перед заголовком и разделитель с отступом в 1 клетку после него
+fn get_tabs_areas(titles: &[Spans], area: Rect) -> Vec<Rect> {
+    let mut areas = vec![];
+    let mut x = area.left();
+    for title in titles {
+        x = x.saturating_add(1);
+        if x >= area.right() {
+            break;
+        }
+
+        let width = (title.width() as u16).min(area.right() - x);
+        areas.push(Rect::new(x, area.top(), width, 1));
+        x = x.saturating_add(width + 2);
+    }
+    areas
 }
@@ -26,3 +26,8 @@ pub fn get_popup_area(percent_width: u16, percent_height: u16, area: Rect) -> Re
         )
         .split(popup_layout[1])[1]
 }
+
+/// Проверяет, что клетка с данными столбцом и строкой находится внутри области
+pub fn area_contains(area: Rect, column: u16, row: u16) -> bool {
+    (area.left()..area.right()).contains(&column) && (area.top()..area.bottom()).contains(&row)
+}
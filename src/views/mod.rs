@@ -0,0 +1,143 @@
+use crossterm::event::{KeyCode, KeyEvent};
+
+use crate::{
+    app::{
+        keymap::{Action, KeyContext},
+        state::App,
+    },
+    locale::{tr, trf},
+};
+
+use self::state::ViewsState;
+
+pub mod state;
+pub mod ui;
+
+impl App<'_> {
+    /// Открывает окно сохранённых видов. `saving` - открыть ли его для сохранения текущего вида
+    pub fn open_views(&mut self, saving: bool) {
+        let (names, error) = match self.get_views_names() {
+            Ok(names) => (names, None),
+            Err(err) => (vec![], Some(trf("Не удалось получить виды: {}", &[&err]))),
+        };
+
+        let mut state = ViewsState::new(names, saving);
+        state.error = error;
+        self.graph_state_mut().views_state = Some(state);
+    }
+
+    /// Закрывает окно сохранённых видов
+    fn close_views(&mut self) {
+        self.graph_state_mut().views_state = None;
+    }
+
+    /// Возвращает изменяемую ссылку на состояние окна сохранённых видов
+    fn views_state_mut(&mut self) -> &mut ViewsState {
+        self.graph_state_mut().views_state.as_mut().unwrap()
+    }
+
+    /// Обрабатывает все события, связанные с нажатием клавиш в окне сохранённых видов
+    pub fn on_key_event_views(&mut self, event: KeyEvent) {
+        // У окна сохранения и окна открытия свои клавиши
+        let context = match self.views_state_mut().name_input {
+            Some(_) => KeyContext::ViewName,
+            None => KeyContext::Views,
+        };
+        let action = self.keymap.action(context, event);
+
+        // Получаем состояние окна видов
+        let state = self.views_state_mut();
+
+        if let Some(input_state) = state.name_input.as_mut() {
+            // Окно сохранения: вводим название (символы без действия вводятся в поле)
+            match (action, event.code) {
+                (Some(Action::Close), _) => self.close_views(),
+                (Some(Action::Save), _) => self.save_current_view(),
+                (Some(Action::Up), _) => state.prev(),
+                (Some(Action::Down), _) => state.next(),
+                (Some(Action::Left), _) => input_state.goto_prev(),
+                (Some(Action::Right), _) => input_state.goto_next(),
+                (Some(Action::Home), _) => input_state.goto_start(),
+                (Some(Action::End), _) => input_state.goto_end(),
+                (None, KeyCode::Char(ch)) => input_state.insert(ch),
+                (Some(Action::Erase), _) => input_state.remove(),
+                (Some(Action::Help), _) => self.open_help(context),
+                _ => (),
+            }
+        } else {
+            // Окно открытия: выбираем вид из списка
+            match action {
+                Some(Action::Close) => self.close_views(),
+                Some(Action::Up) => state.prev(),
+                Some(Action::Down) => state.next(),
+                Some(Action::Open) => self.open_selected_view(),
+                Some(Action::Delete) => self.delete_selected_view(),
+                Some(Action::Help) => self.open_help(context),
+                _ => (),
+            }
+        }
+    }
+
+    /// Сохраняет текущий вид вкладки под введённым названием
+    fn save_current_view(&mut self) {
+        // Получаем введённое название
+        let state = self.graph_state().views_state.as_ref().unwrap();
+        let name = state
+            .name_input
+            .as_ref()
+            .unwrap()
+            .content()
+            .trim()
+            .to_owned();
+        if name.is_empty() {
+            self.views_state_mut().error = Some(tr("Введите название вида").to_owned());
+            return;
+        }
+
+        // Сохраняем вид и закрываем окно, если всё прошло успешно
+        let view = self.graph_state().get_view();
+        match self.save_view(&name, &view) {
+            Ok(()) => self.close_views(),
+            Err(err) => {
+                self.views_state_mut().error = Some(trf("Не удалось сохранить вид: {}", &[&err]))
+            }
+        }
+    }
+
+    /// Открывает выбранный вид во вкладке
+    fn open_selected_view(&mut self) {
+        // Получаем название выбранного вида
+        let Some(name) = self.views_state_mut().selected_name().map(str::to_owned) else {
+            return;
+        };
+
+        // Загружаем вид и применяем его
+        match self.load_view(&name) {
+            Ok(view) => {
+                self.close_views();
+                self.apply_graph_view(&view);
+            }
+            Err(err) => {
+                self.views_state_mut().error = Some(trf("Не удалось открыть вид: {}", &[&err]))
+            }
+        }
+    }
+
+    /// Удаляет выбранный вид
+    fn delete_selected_view(&mut self) {
+        // Получаем название выбранного вида
+        let Some(name) = self.views_state_mut().selected_name().map(str::to_owned) else {
+            return;
+        };
+
+        // Удаляем вид и обновляем список
+        let result = self
+            .delete_view(&name)
+            .and_then(|()| self.get_views_names());
+        let state = self.views_state_mut();
+        match result {
+            Ok(names) => state.set_names(names),
+            Err(err) => state.error = Some(trf("Не удалось удалить вид: {}", &[&err])),
+        }
+    }
+}
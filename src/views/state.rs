@@ -0,0 +1,70 @@
+use crate::ui::input::InputState;
+
+/// Описывает состояние окна сохранённых видов графика
+#[derive(Debug, Default)]
+pub struct ViewsState {
+    /// Сохраняет названия сохранённых видов
+    pub names: Vec<String>,
+
+    /// Сохраняет индекс выбранного вида
+    pub selection_index: usize,
+
+    /// Сохраняет поле ввода названия (есть, только если вид сохраняется)
+    pub name_input: Option<InputState>,
+
+    /// Сохраняет ошибку последнего действия, если она была
+    pub error: Option<String>,
+}
+
+impl ViewsState {
+    /// Создаёт новое состояние окна видов. `saving` - открыто ли окно для сохранения вида
+    pub fn new(names: Vec<String>, saving: bool) -> Self {
+        let name_input = saving.then(|| {
+            let mut input_state = InputState::default();
+            input_state.open();
+            input_state
+        });
+
+        Self {
+            names,
+            selection_index: 0,
+            name_input,
+            error: None,
+        }
+    }
+
+    /// Выбирает предыдущий вид
+    pub fn prev(&mut self) {
+        if !self.names.is_empty() {
+            self.selection_index = (self.selection_index + self.names.len() - 1) % self.names.len();
+            self.copy_name_to_input();
+        }
+    }
+
+    /// Выбирает следующий вид
+    pub fn next(&mut self) {
+        if !self.names.is_empty() {
+            self.selection_index = (self.selection_index + 1) % self.names.len();
+            self.copy_name_to_input();
+        }
+    }
+
+    /// Возвращает название выбранного вида
+    pub fn selected_name(&self) -> Option<&str> {
+        self.names.get(self.selection_index).map(String::as_str)
+    }
+
+    /// Заменяет названия видов (например, после удаления), сохраняя выбор в границах
+    pub fn set_names(&mut self, names: Vec<String>) {
+        self.selection_index = self.selection_index.min(names.len().saturating_sub(1));
+        self.names = names;
+    }
+
+    /// При сохранении подставляет название выбранного вида, чтобы его было удобно перезаписать
+    fn copy_name_to_input(&mut self) {
+        let name = self.names[self.selection_index].clone();
+        if let Some(input_state) = self.name_input.as_mut() {
+            input_state.set_content(name);
+        }
+    }
+}
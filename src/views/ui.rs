@@ -0,0 +1,98 @@
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans, Text},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::{
+    locale::tr,
+    ui::{input::Input, utils::get_popup_area},
+};
+
+use super::state::ViewsState;
+
+/// Рендерит окно сохранённых видов графика
+pub fn draw_views<B: Backend>(frame: &mut Frame<B>, state: &mut ViewsState, area: Rect) {
+    // Выделяем область под окошко и очищаем её, чтобы не видеть график под ним
+    let popup_area = get_popup_area(50, 60, area);
+    frame.render_widget(Clear, popup_area);
+
+    // Делаем блок
+    let title = match state.name_input {
+        Some(_) => tr("Сохранение вида"),
+        None => tr("Сохранённые виды"),
+    };
+    let block = Block::default().title(title).borders(Borders::ALL);
+    let inner_area = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    // Сверху поле ввода названия (если сохраняем), снизу строка ошибки
+    let input_height = state.name_input.as_ref().map_or(0, |_| 2);
+    let area_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(input_height),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .split(inner_area);
+
+    // Рендерим поле ввода названия
+    if let Some(input_state) = state.name_input.as_mut() {
+        let area = area_chunks[0];
+        let paragraph = Paragraph::new(Text::from(tr("Название:")));
+        frame.render_widget(paragraph, Rect { height: 1, ..area });
+
+        let input = Input::new().style(Style::default().add_modifier(Modifier::UNDERLINED));
+        let input_area = Rect::new(area.x, area.y + 1, area.width, 1);
+        frame.render_stateful_widget(input, input_area, input_state);
+    }
+
+    // Рендерим список видов
+    draw_views_list(frame, state, area_chunks[1]);
+
+    // Рендерим ошибку последнего действия
+    if let Some(error) = &state.error {
+        let paragraph =
+            Paragraph::new(Text::from(error.as_str())).style(Style::default().fg(Color::Red));
+        frame.render_widget(paragraph, area_chunks[2]);
+    }
+}
+
+/// Рендерит список сохранённых видов
+fn draw_views_list<B: Backend>(frame: &mut Frame<B>, state: &ViewsState, area: Rect) {
+    // Если видов нет, сообщаем об этом
+    if state.names.is_empty() {
+        let paragraph = Paragraph::new(Text::from(tr("--- Сохранённых видов нет ---")));
+        frame.render_widget(paragraph, area);
+        return;
+    }
+
+    // Прокручиваем список так, чтобы выбранный вид был виден
+    let height = area.height.max(1) as usize;
+    let start = state.selection_index.saturating_sub(height - 1);
+
+    let names: Vec<_> = state
+        .names
+        .iter()
+        .enumerate()
+        .skip(start)
+        .take(height)
+        .map(|(i, name)| {
+            // Делаем выбранный вид выделенным
+            let style = match i == state.selection_index {
+                true => Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+                false => Style::default(),
+            };
+            Spans::from(Span::styled(name.as_str(), style))
+        })
+        .collect();
+
+    let paragraph = Paragraph::new(names);
+    frame.render_widget(paragraph, area);
+}